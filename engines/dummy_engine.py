@@ -0,0 +1,47 @@
+#!/usr/bin/env python3
+"""Dummy engine for the pistiflex engine protocol (see src/engine.rs).
+
+Takes the board when it holds a card of the same rank as the top card,
+otherwise plays the first card of its hand.
+"""
+import sys
+
+seat = None
+hand = []
+board = []
+
+
+def send(line):
+    sys.stdout.write(line + "\n")
+    sys.stdout.flush()
+
+
+for line in sys.stdin:
+    words = line.split()
+    if not words:
+        continue
+    cmd, args = words[0], words[1:]
+    if cmd == "pisti":
+        send("id name dummy")
+        send("pistiok")
+    elif cmd == "newgame":
+        seat = args[0]
+        hand = []
+    elif cmd == "board":
+        board = args
+    elif cmd == "deal":
+        hand = args
+    elif cmd == "played":
+        who, card, stat = args
+        if who == seat and card in hand:
+            hand.remove(card)
+        board = [] if stat != "pass" else board + [card]
+    elif cmd == "go":
+        card = hand[0]
+        if board:
+            for a_card in hand:
+                if a_card[0] == board[-1][0]:
+                    card = a_card
+        send("play " + card)
+    elif cmd == "quit":
+        break
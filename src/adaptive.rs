@@ -0,0 +1,256 @@
+use std::{fs, path::PathBuf};
+
+use crate::{
+    features::{evaluate, SeatView},
+    game::{Card, Game, Move, Player, Rules, WinStatus, R_A, R_J},
+    storage::{data_dir, parse_key_values, write_file},
+    strategy::{Strategy, StrategyKind, DEFAULT_PROFILE},
+    weighted::DEFAULT_WEIGHTS,
+};
+
+const CARDS_PER_DEAL: usize = 8;
+
+/// how an opponent played in earlier games, counted from their decisions
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpponentModel {
+    /// could take the board with a card of the same rank as the top card
+    pub capture_chances: u32,
+    pub captures_taken: u32,
+    /// could only take the board with a J
+    pub jack_chances: u32,
+    pub jacks_used: u32,
+    /// first card of a deal while holding point and no point cards
+    pub low_card_chances: u32,
+    pub low_cards_played: u32,
+}
+
+/// (successes + 1) / (chances + 2), so an unknown opponent starts at one half
+fn rate(successes: u32, chances: u32) -> f64 {
+    (successes as f64 + 1.0) / (chances as f64 + 2.0)
+}
+
+impl OpponentModel {
+    pub fn capture_rate(&self) -> f64 {
+        rate(self.captures_taken, self.capture_chances)
+    }
+
+    pub fn jack_use_rate(&self) -> f64 {
+        rate(self.jacks_used, self.jack_chances)
+    }
+
+    pub fn low_card_rate(&self) -> f64 {
+        rate(self.low_cards_played, self.low_card_chances)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut model = OpponentModel::default();
+        for (line_no, key, value) in parse_key_values(text)? {
+            let value: u32 = value
+                .parse()
+                .map_err(|_| format!("line {}: invalid count {}", line_no, value))?;
+            match key.as_str() {
+                "capture_chances" => model.capture_chances = value,
+                "captures_taken" => model.captures_taken = value,
+                "jack_chances" => model.jack_chances = value,
+                "jacks_used" => model.jacks_used = value,
+                "low_card_chances" => model.low_card_chances = value,
+                "low_cards_played" => model.low_cards_played = value,
+                _ => {}
+            }
+        }
+        Ok(model)
+    }
+
+    pub fn to_text(&self) -> String {
+        format!(
+            "capture_chances = {}\ncaptures_taken = {}\njack_chances = {}\njacks_used = {}\nlow_card_chances = {}\nlow_cards_played = {}\n",
+            self.capture_chances,
+            self.captures_taken,
+            self.jack_chances,
+            self.jacks_used,
+            self.low_card_chances,
+            self.low_cards_played
+        )
+    }
+
+    /// counts the decisions of `a_player` in a finished game, the hand of every
+    /// decision is known afterwards from the cards played later in the same deal
+    pub fn learn_from_game(&mut self, game: &Game, a_player: Player) {
+        let mut board: Vec<Card> = game.initial_board();
+        for (i, a_move) in game.moves.iter().enumerate() {
+            if a_move.player == a_player {
+                let deal_end = (i / CARDS_PER_DEAL + 1) * CARDS_PER_DEAL;
+                let hand: Vec<Card> = game.moves[i..deal_end.min(game.moves.len())]
+                    .iter()
+                    .filter(|m| m.player == a_player)
+                    .map(|m| m.card)
+                    .collect();
+                self.learn_decision(&game.rules, &board, &hand, a_move, i % CARDS_PER_DEAL < 2);
+            }
+            board.push(a_move.card);
+            if a_move.stat != WinStatus::Pass {
+                board.clear();
+            }
+        }
+    }
+
+    fn learn_decision(
+        &mut self,
+        rules: &Rules,
+        board: &[Card],
+        hand: &[Card],
+        a_move: &Move,
+        first_move: bool,
+    ) {
+        if let Some(top_card) = board.last() {
+            if hand.iter().any(|c| c.rank == top_card.rank) {
+                self.capture_chances += 1;
+                if a_move.card.rank == top_card.rank {
+                    self.captures_taken += 1;
+                }
+            } else if hand.iter().any(|c| c.rank == R_J) {
+                self.jack_chances += 1;
+                if a_move.card.rank == R_J {
+                    self.jacks_used += 1;
+                }
+            }
+        }
+        let has_low = hand.iter().any(|c| rules.card_points(c) == 0);
+        let has_points = hand.iter().any(|c| rules.card_points(c) > 0);
+        if first_move && has_low && has_points {
+            self.low_card_chances += 1;
+            if rules.card_points(&a_move.card) == 0 {
+                self.low_cards_played += 1;
+            }
+        }
+    }
+
+    /// how likely the opponent holds each rank, from their moves in the current deal
+    pub fn rank_weights(&self, game: &Game, opponent: Player) -> [f64; 14] {
+        let mut weights = [1.0; 14];
+        let deal_start = game.moves.len() - game.moves.len() % CARDS_PER_DEAL;
+        let mut board: Vec<Card> = game.initial_board();
+        for (i, a_move) in game.moves.iter().enumerate() {
+            if i >= deal_start && a_move.player == opponent {
+                if let Some(top_card) = board.last() {
+                    // someone who usually takes the board does not hold the rank they passed on
+                    if a_move.card.rank != top_card.rank {
+                        weights[top_card.rank as usize] *= 1.0 - self.capture_rate();
+                    }
+                    if a_move.stat == WinStatus::Pass {
+                        weights[R_J as usize] *= 1.0 - self.jack_use_rate();
+                    }
+                }
+                // a no point card played first says less about the rest of the hand
+                // when the opponent likes to get rid of them early
+                if i % CARDS_PER_DEAL < 2 && game.rules.card_points(&a_move.card) == 0 {
+                    let low_rate = self.low_card_rate();
+                    for rank in [R_A, R_J] {
+                        weights[rank as usize] *= 0.5 + low_rate;
+                    }
+                }
+            }
+            board.push(a_move.card);
+            if a_move.stat != WinStatus::Pass {
+                board.clear();
+            }
+        }
+        weights
+    }
+}
+
+pub fn opponent_model_path(profile: &str) -> PathBuf {
+    let file_name: String = profile
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    data_dir()
+        .join("opponents")
+        .join(format!("{}.txt", file_name))
+}
+
+pub fn load_opponent_model(profile: &str) -> OpponentModel {
+    match fs::read_to_string(opponent_model_path(profile)) {
+        Ok(text) => OpponentModel::parse(&text).unwrap_or_else(|e| {
+            println!("cannot read the model of {}: {}", profile, e);
+            OpponentModel::default()
+        }),
+        Err(_) => OpponentModel::default(),
+    }
+}
+
+/// weighted AI that learns how its opponent plays and saves it under the opponent's profile,
+/// only a human opponent is learned so games between AIs leave the models alone
+pub struct AdaptiveStrategy {
+    profile: String,
+    model: OpponentModel,
+    /// the profile was given, a human opponent does not change it
+    fixed: bool,
+    learning: bool,
+}
+
+impl AdaptiveStrategy {
+    /// plays with the model of `profile`
+    pub fn new(profile: &str) -> Self {
+        AdaptiveStrategy {
+            profile: profile.to_string(),
+            model: load_opponent_model(profile),
+            fixed: true,
+            learning: false,
+        }
+    }
+
+    /// plays with the model of the human it meets, the default one until then
+    pub fn for_any_opponent() -> Self {
+        AdaptiveStrategy {
+            fixed: false,
+            ..AdaptiveStrategy::new(DEFAULT_PROFILE)
+        }
+    }
+}
+
+impl Strategy for AdaptiveStrategy {
+    fn name(&self) -> String {
+        format!("{}:{}", StrategyKind::Adaptive, self.profile)
+    }
+
+    fn pick_card(&mut self, game: &Game, player: Player) -> usize {
+        let mut view = SeatView::new(game, player);
+        view.rank_weights = self.model.rank_weights(game, player.other());
+        let mut best_i = 0;
+        let mut best_value = f64::NEG_INFINITY;
+        for (i, a_card) in view.hand.iter().enumerate() {
+            let value = evaluate(&DEFAULT_WEIGHTS, &view.features(*a_card));
+            if value > best_value {
+                best_i = i;
+                best_value = value;
+            }
+        }
+        best_i
+    }
+
+    fn game_over(&mut self, game: &Game, player: Player) {
+        if !self.learning {
+            return;
+        }
+        self.model.learn_from_game(game, player.other());
+        if let Err(e) = write_file(&opponent_model_path(&self.profile), &self.model.to_text()) {
+            println!("cannot save the model of {}: {}", self.profile, e);
+        }
+    }
+
+    fn against_human(&mut self, profile: Option<&str>) {
+        let profile = profile.unwrap_or(DEFAULT_PROFILE);
+        if !self.fixed && profile != self.profile {
+            self.profile = profile.to_string();
+            self.model = load_opponent_model(profile);
+        }
+        self.learning = true;
+    }
+}
@@ -0,0 +1,285 @@
+use std::{fmt, path::Path};
+
+use crate::{
+    engine::seat_number,
+    game::{Card, DealStatus, Game, Player, RulePreset, Rules},
+    save::load_game,
+    strategy::Strategy,
+    weighted::{WeightedStrategy, DEFAULT_WEIGHTS},
+};
+
+/// points of Player1 minus points of Player2 in a finished game
+fn point_difference(my_game: &Game) -> i64 {
+    my_game.player1_point as i64 - my_game.player2_point as i64
+}
+
+/// score of every card in the hand of the player to move when both play the rest perfectly,
+/// `maximizer` wants the highest `score` of the finished game and the other player the lowest;
+/// exact when every card left is known, like in the final deal where both hands can be counted
+pub fn solved_moves(my_game: &Game, score: &dyn Fn(&Game) -> i64, maximizer: Player) -> Vec<i64> {
+    let player = my_game.turn;
+    (0..my_game.get_player_cards(player).len())
+        .map(|hand_index| {
+            let mut next = my_game.clone();
+            next.play_turn(player, hand_index);
+            match next.next_deal() {
+                DealStatus::GameOver => score(&next),
+                _ => solve(&next, score, maximizer),
+            }
+        })
+        .collect()
+}
+
+pub fn solve(my_game: &Game, score: &dyn Fn(&Game) -> i64, maximizer: Player) -> i64 {
+    let values = solved_moves(my_game, score, maximizer).into_iter();
+    if my_game.turn == maximizer {
+        values.max().unwrap_or(0)
+    } else {
+        values.min().unwrap_or(0)
+    }
+}
+
+/// best point difference for Player1 when both play the final deal perfectly
+pub fn solve_final_deal(my_game: &Game) -> i64 {
+    solve(my_game, &point_difference, Player::Player1)
+}
+
+/// plays the game to the end with the weighted AI on both seats, the final deal is solved
+fn play_out(mut my_game: Game, strategy: &mut WeightedStrategy) -> i64 {
+    loop {
+        if my_game.deck.is_empty() {
+            return solve_final_deal(&my_game);
+        }
+        let player = my_game.turn;
+        let hand_index = strategy.pick_card(&my_game, player);
+        my_game.play_turn(player, hand_index);
+        if my_game.next_deal() == DealStatus::GameOver {
+            return point_difference(&my_game);
+        }
+    }
+}
+
+/// one decision of a game and where each card of the hand would have led
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    pub move_number: usize,
+    pub player: Player,
+    pub board: Vec<Card>,
+    pub played: Card,
+    /// point difference for the player to move at the end of the game, for every card of the hand
+    pub outcomes: Vec<(Card, i64)>,
+    /// the outcomes of the final deal are solved, the others are played out by the weighted AI
+    pub exact: bool,
+}
+
+impl Decision {
+    pub fn best(&self) -> i64 {
+        self.outcomes.iter().map(|(_, o)| *o).max().unwrap_or(0)
+    }
+
+    pub fn outcome_of(&self, a_card: Card) -> i64 {
+        self.outcomes
+            .iter()
+            .find(|(c, _)| *c == a_card)
+            .map_or(0, |(_, o)| *o)
+    }
+
+    /// points lost against the best card, 0 when the played card was one of the best
+    pub fn loss(&self) -> i64 {
+        self.best() - self.outcome_of(self.played)
+    }
+}
+
+/// outcome of every card the player to move holds
+pub fn card_outcomes(my_game: &Game) -> (Vec<(Card, i64)>, bool) {
+    let player = my_game.turn;
+    let sign = match player {
+        Player::Player1 => 1,
+        Player::Player2 => -1,
+    };
+    let exact = my_game.deck.is_empty();
+    let mut strategy = WeightedStrategy::new(DEFAULT_WEIGHTS);
+    let outcomes = my_game
+        .get_player_cards(player)
+        .iter()
+        .enumerate()
+        .map(|(hand_index, a_card)| {
+            let mut next = my_game.clone();
+            next.play_turn(player, hand_index);
+            let difference = match next.next_deal() {
+                DealStatus::GameOver => point_difference(&next),
+                _ if exact => solve_final_deal(&next),
+                _ => play_out(next, &mut strategy),
+            };
+            (*a_card, sign * difference)
+        })
+        .collect();
+    (outcomes, exact)
+}
+
+/// goes through `moves` from the start of `my_game`, without moves the weighted AI plays both seats
+pub fn analyze_game(mut my_game: Game, moves: Option<&[Card]>) -> Result<Vec<Decision>, String> {
+    let mut strategy = WeightedStrategy::new(DEFAULT_WEIGHTS);
+    let mut decisions = Vec::new();
+    while !my_game.is_game_over() {
+        let move_number = my_game.moves.len();
+        let played = match moves {
+            Some(moves) if move_number < moves.len() => moves[move_number],
+            Some(_) => break,
+            None => {
+                my_game.get_player_cards(my_game.turn)[strategy.pick_card(&my_game, my_game.turn)]
+            }
+        };
+        let (outcomes, exact) = card_outcomes(&my_game);
+        decisions.push(Decision {
+            move_number: move_number + 1,
+            player: my_game.turn,
+            board: my_game.board.to_vec(),
+            played,
+            outcomes,
+            exact,
+        });
+        let player = my_game.turn;
+        let hand_index = my_game
+            .get_player_cards(player)
+            .iter()
+            .position(|c| *c == played)
+            .ok_or(format!(
+                "move {}: {} is not in the hand of the player",
+                move_number + 1,
+                played.notation()
+            ))?;
+        my_game.play_turn(player, hand_index);
+        my_game.next_deal();
+    }
+    Ok(decisions)
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let top_card = self
+            .board
+            .last()
+            .map_or(String::from("empty"), |c| c.notation());
+        writeln!(
+            f,
+            "move {} player {}, board {} cards, top {}{}",
+            self.move_number,
+            seat_number(self.player),
+            self.board.len(),
+            top_card,
+            if self.exact { ", solved" } else { "" }
+        )?;
+        let mut outcomes = self.outcomes.clone();
+        outcomes.sort_by_key(|(_, outcome)| -outcome);
+        for (a_card, outcome) in outcomes {
+            let mark = if a_card == self.played {
+                " <- played"
+            } else {
+                ""
+            };
+            writeln!(f, "  {} {:+}{}", a_card.notation(), outcome, mark)?;
+        }
+        match self.loss() {
+            0 => Ok(()),
+            loss => writeln!(f, "  mistake, {} points lost", loss),
+        }
+    }
+}
+
+/// the cards in the order they are dealt: four onto the board, then one to each player in turn
+pub fn parse_deck(text: &str) -> Result<Vec<Card>, String> {
+    let mut deck = text
+        .split_whitespace()
+        .map(|c| c.parse::<Card>())
+        .collect::<Result<Vec<Card>, String>>()?;
+    if deck.len() != 52 {
+        return Err(format!("a deck has 52 cards, not {}", deck.len()));
+    }
+    for (i, a_card) in deck.iter().enumerate() {
+        if deck[..i].contains(a_card) {
+            return Err(format!("{} is twice in the deck", a_card.notation()));
+        }
+    }
+    // the game deals from the end of the deck
+    deck.reverse();
+    Ok(deck)
+}
+
+const USAGE: &str = "usage: pistiflex analyze (--seed S | --deck \"<52 cards>\" | --load <file>) [--moves \"<cards>\"] [--rules <preset>]";
+
+/// `pistiflex analyze --seed S [--moves "7H KD ..."]`
+pub fn analyze_main(args: &[String]) -> Result<(), String> {
+    let mut seed = None;
+    let mut deck = None;
+    let mut load = None;
+    let mut moves = None;
+    let mut rules = Rules::default();
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        let value = args_iter.next().ok_or(USAGE)?;
+        match arg.as_str() {
+            "--seed" => seed = Some(value.parse::<u64>().map_err(|_| "--seed needs a number")?),
+            "--deck" => deck = Some(parse_deck(value)?),
+            "--load" => load = Some(value.clone()),
+            "--moves" => {
+                moves = Some(
+                    value
+                        .split_whitespace()
+                        .map(|c| c.parse::<Card>())
+                        .collect::<Result<Vec<Card>, String>>()?,
+                )
+            }
+            "--rules" => {
+                rules = value
+                    .parse::<RulePreset>()
+                    .map_err(|_| format!("unknown rules: {}", value))?
+                    .rules()
+            }
+            _ => return Err(String::from(USAGE)),
+        }
+    }
+    let my_game = match (seed, deck, load) {
+        (Some(seed), None, None) => {
+            let mut my_game = Game::with_seed(seed);
+            my_game.rules = rules;
+            my_game.start_game_and_give_cards_to_players();
+            my_game
+        }
+        (None, Some(deck), None) => {
+            let mut my_game = Game::with_seed(0);
+            my_game.rules = rules;
+            my_game.start_game_with_deck(&deck);
+            if my_game.is_reshuffle_required() {
+                return Err(String::from("the deck puts a J on top of the board"));
+            }
+            my_game
+        }
+        (None, None, Some(path)) => {
+            let saved = load_game(Path::new(&path))?.game;
+            moves = moves.or(Some(saved.moves.iter().map(|m| m.card).collect()));
+            let mut my_game = Game::with_seed(saved.seed);
+            my_game.rules = saved.rules;
+            my_game.start_game_with_deck(&saved.initial_deck);
+            my_game
+        }
+        _ => return Err(String::from(USAGE)),
+    };
+    let decisions = analyze_game(my_game, moves.as_deref())?;
+    for decision in &decisions {
+        println!("{}", decision);
+    }
+    for a_player in [Player::Player1, Player::Player2] {
+        let lost: i64 = decisions
+            .iter()
+            .filter(|d| d.player == a_player)
+            .map(|d| d.loss())
+            .sum();
+        println!(
+            "player {} lost {} points by mistakes",
+            seat_number(a_player),
+            lost
+        );
+    }
+    Ok(())
+}
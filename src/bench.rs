@@ -0,0 +1,100 @@
+use std::{hint::black_box, time::Instant};
+
+use crate::{
+    analyze::solve_final_deal,
+    game::{Game, Rules},
+    harness::play_ai_game,
+    seat::{Seat, Seats},
+    strategy::strategy_from_spec,
+};
+
+const USAGE: &str = "usage: pistiflex bench [--games N] [--seed S]";
+
+/// how fast the parts the AI search leans on are, in operations per second
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchReport {
+    pub games_per_second: f64,
+    pub clones_per_second: f64,
+    pub solves_per_second: f64,
+}
+
+fn per_second(count: usize, start: Instant) -> f64 {
+    count as f64 / start.elapsed().as_secs_f64().max(1e-9)
+}
+
+/// a game played by the basic AI until the deck is empty, the final deal is left to play
+fn final_deal(seed: u64) -> Game {
+    let mut strategy = strategy_from_spec("basic").expect("basic is a known strategy");
+    let mut my_game = Game::with_seed(seed);
+    my_game.start_game_and_give_cards_to_players();
+    while !my_game.deck.is_empty() {
+        let player = my_game.turn;
+        let hand_index = strategy.pick_card(&my_game, player);
+        my_game.play_turn(player, hand_index);
+        my_game.next_deal();
+    }
+    my_game
+}
+
+pub fn run_bench(games: usize, seed: u64) -> Result<BenchReport, String> {
+    let mut seats = Seats::new(
+        Seat::Ai(strategy_from_spec("basic")?),
+        Seat::Ai(strategy_from_spec("basic")?),
+    );
+    let start = Instant::now();
+    for i in 0..games {
+        black_box(play_ai_game(&mut seats, seed.wrapping_add(i as u64), Rules::default()));
+    }
+    let games_per_second = per_second(games, start);
+
+    let clones = games * 1000;
+    let mut my_game = Game::with_seed(seed);
+    my_game.start_game_and_give_cards_to_players();
+    let start = Instant::now();
+    for _ in 0..clones {
+        black_box(black_box(&my_game).clone());
+    }
+    let clones_per_second = per_second(clones, start);
+
+    let positions: Vec<Game> = (0..games).map(|i| final_deal(seed.wrapping_add(i as u64))).collect();
+    let start = Instant::now();
+    for a_game in &positions {
+        black_box(solve_final_deal(a_game));
+    }
+    let solves_per_second = per_second(games, start);
+
+    Ok(BenchReport {
+        games_per_second,
+        clones_per_second,
+        solves_per_second,
+    })
+}
+
+/// `pistiflex bench --games 200`, build with --release for numbers worth comparing
+pub fn bench_main(args: &[String]) -> Result<(), String> {
+    let mut games = 200;
+    let mut seed = 1;
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        let value = args_iter.next().ok_or(USAGE)?;
+        match arg.as_str() {
+            "--games" => games = value.parse().map_err(|_| "--games needs a number")?,
+            "--seed" => seed = value.parse().map_err(|_| "--seed needs a number")?,
+            _ => return Err(String::from(USAGE)),
+        }
+    }
+    let report = run_bench(games, seed)?;
+    println!(
+        "games per second:              {:.0}",
+        report.games_per_second
+    );
+    println!(
+        "game clones per second:        {:.0}",
+        report.clones_per_second
+    );
+    println!(
+        "final deals solved per second: {:.0}",
+        report.solves_per_second
+    );
+    Ok(())
+}
@@ -0,0 +1,9 @@
+use pistiflex::server::server_main;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = server_main(&args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
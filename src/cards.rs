@@ -0,0 +1,197 @@
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use crate::game::{Card, Rules, Suit, R_A, R_J};
+
+/// bit of a card in a `CardSet`, 13 bits per suit from A to K
+const fn bit(rank: u8, suit: Suit) -> u64 {
+    1 << (suit as u64 * 13 + rank as u64 - 1)
+}
+
+/// the cards of `rank` in all four suits
+const fn rank_mask(rank: u8) -> u64 {
+    (1 | 1 << 13 | 1 << 26 | 1 << 39) << (rank - 1)
+}
+
+const SUITS: [Suit; 4] = [Suit::Spade, Suit::Heart, Suit::Diamond, Suit::Club];
+
+/// a set of cards in one 64-bit word, order does not matter and a card is in it at most once
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CardSet(pub u64);
+
+impl CardSet {
+    pub fn new() -> Self {
+        CardSet(0)
+    }
+
+    pub fn insert(&mut self, a_card: Card) {
+        self.0 |= bit(a_card.rank, a_card.suit);
+    }
+
+    pub fn contains(&self, a_card: Card) -> bool {
+        self.0 & bit(a_card.rank, a_card.suit) != 0
+    }
+
+    pub fn extend_from_slice(&mut self, cards: &[Card]) {
+        for a_card in cards {
+            self.insert(*a_card);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// how many cards of `rank` are in the set
+    pub fn rank_count(&self, rank: u8) -> usize {
+        (self.0 & rank_mask(rank)).count_ones() as usize
+    }
+
+    /// points of the cards under `rules`, the same as adding `Rules::card_points` of each card
+    pub fn points(&self, rules: &Rules) -> usize {
+        let count = |mask: u64| (self.0 & mask).count_ones() as usize;
+        count(rank_mask(R_A)) * rules.ace
+            + count(rank_mask(R_J)) * rules.jack
+            + count(bit(2, Suit::Club)) * rules.club_2
+            + count(bit(10, Suit::Diamond)) * rules.diamond_10
+    }
+
+    /// the cards from A♠︎ to K♣︎
+    pub fn iter(&self) -> impl Iterator<Item = Card> + '_ {
+        SUITS.iter().flat_map(move |suit| {
+            (1..14)
+                .filter(move |rank| self.0 & bit(*rank, *suit) != 0)
+                .map(move |rank| Card { rank, suit: *suit })
+        })
+    }
+}
+
+impl FromIterator<Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = Card>>(iter: I) -> Self {
+        let mut set = CardSet::new();
+        for a_card in iter {
+            set.insert(a_card);
+        }
+        set
+    }
+}
+
+impl fmt::Debug for CardSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// a list of at most N items kept inline, copying it never allocates
+#[derive(Copy, Clone)]
+pub struct FixedVec<T: Copy + Default, const N: usize> {
+    items: [T; N],
+    len: usize,
+}
+
+impl<T: Copy + Default, const N: usize> FixedVec<T, N> {
+    pub fn new() -> Self {
+        FixedVec {
+            items: [T::default(); N],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, item: T) {
+        assert!(self.len < N, "a FixedVec of {} items is full", N);
+        self.items[self.len] = item;
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(self.items[self.len])
+    }
+
+    /// removes the item at `index` and shifts the ones after it, like `Vec::remove`
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(
+            index < self.len,
+            "index {} out of {} items",
+            index,
+            self.len
+        );
+        let item = self.items[index];
+        self.items.copy_within(index + 1..self.len, index);
+        self.len -= 1;
+        item
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<T: Copy + Default, const N: usize> Default for FixedVec<T, N> {
+    fn default() -> Self {
+        FixedVec::new()
+    }
+}
+
+impl<T: Copy + Default, const N: usize> Deref for FixedVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items[..self.len]
+    }
+}
+
+impl<T: Copy + Default, const N: usize> DerefMut for FixedVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.items[..self.len]
+    }
+}
+
+impl<T: Copy + Default + PartialEq, const N: usize> PartialEq for FixedVec<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T: Copy + Default + fmt::Debug, const N: usize> fmt::Debug for FixedVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Copy + Default, const N: usize> FromIterator<T> for FixedVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = FixedVec::new();
+        for item in iter {
+            list.push(item);
+        }
+        list
+    }
+}
+
+impl<T: Copy + Default, const N: usize> IntoIterator for FixedVec<T, N> {
+    type Item = T;
+    type IntoIter = std::iter::Take<std::array::IntoIter<T, N>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter().take(self.len)
+    }
+}
+
+impl<'a, T: Copy + Default, const N: usize> IntoIterator for &'a FixedVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
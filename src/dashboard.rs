@@ -0,0 +1,279 @@
+use std::{cell::RefCell, rc::Rc};
+
+use fltk::{
+    draw,
+    enums::{Align, CallbackTrigger, Color, Font},
+    frame::Frame,
+    input::Input,
+    menu::Choice,
+    prelude::*,
+    window::Window,
+};
+use strum::IntoEnumIterator;
+
+use pistiflex::{
+    game::RulePreset,
+    history::{
+        history_stats, opponent_stats, score_histogram, win_rate_trend, GameEntry, HistoryFilter,
+    },
+};
+
+const DASHBOARD_W: i32 = 900;
+const DASHBOARD_H: i32 = 720;
+/// points of one bar of the score histogram
+const SCORE_BIN: usize = 10;
+/// games the win rate trend is averaged over
+const TREND_WINDOW: usize = 10;
+const BAR_COLOR: Color = Color::from_rgb(70, 110, 170);
+const LINE_COLOR: Color = Color::from_rgb(200, 60, 50);
+
+/// the border and the title of a chart, returns the area left for the chart
+fn draw_panel(title: &str, x: i32, y: i32, w: i32, h: i32) -> (i32, i32, i32, i32) {
+    draw::draw_rect_fill(x, y, w, h, Color::White);
+    draw::draw_rect_with_color(x, y, w, h, Color::Dark3);
+    draw::set_draw_color(Color::Black);
+    draw::set_font(Font::HelveticaBold, 14);
+    draw::draw_text2(title, x, y + 4, w, 20, Align::Center);
+    draw::set_font(Font::Helvetica, 11);
+    (x + 40, y + 30, w - 55, h - 55)
+}
+
+/// the averages of runs of `values`, at most `columns` of them
+fn group_values(values: &[f64], columns: usize) -> Vec<f64> {
+    if values.len() <= columns {
+        return values.to_vec();
+    }
+    let per_column = values.len().div_ceil(columns);
+    values
+        .chunks(per_column)
+        .map(|run| run.iter().sum::<f64>() / run.len() as f64)
+        .collect()
+}
+
+/// bars from the bottom of the area, `labels` are written under every bar, a series wider
+/// than the area is averaged so every bar gets a column of its own
+fn draw_bars(area: (i32, i32, i32, i32), values: &[f64], labels: &[String]) {
+    let (x, y, w, h) = area;
+    let grouped = group_values(values, w.max(1) as usize);
+    let labels = if grouped.len() < values.len() { &[] } else { labels };
+    let values = &grouped;
+    let max = values.iter().cloned().fold(0.0, f64::max);
+    draw::set_draw_color(Color::Black);
+    draw::draw_line(x, y + h, x + w, y + h);
+    draw::draw_text2(&format!("{}", max), x - 38, y - 6, 34, 12, Align::Right);
+    if values.is_empty() || max <= 0.0 {
+        draw::draw_text2("no games", x, y, w, h, Align::Center);
+        return;
+    }
+    let bar_w = w / values.len() as i32;
+    for (i, value) in values.iter().enumerate() {
+        let bar_h = (value / max * h as f64) as i32;
+        let bar_x = x + i as i32 * bar_w;
+        draw::draw_rect_fill(
+            bar_x + 1,
+            y + h - bar_h,
+            (bar_w - 2).max(1),
+            bar_h,
+            BAR_COLOR,
+        );
+        if let Some(label) = labels.get(i) {
+            draw::set_draw_color(Color::Black);
+            draw::draw_text2(label, bar_x, y + h + 2, bar_w, 12, Align::Center);
+        }
+    }
+}
+
+/// a line through `values` from 0 to 1, with the 50% line under it
+fn draw_trend(area: (i32, i32, i32, i32), values: &[f64]) {
+    let (x, y, w, h) = area;
+    draw::set_draw_color(Color::Black);
+    draw::draw_line(x, y + h, x + w, y + h);
+    draw::draw_text2("100%", x - 38, y - 6, 34, 12, Align::Right);
+    draw::draw_text2("50%", x - 38, y + h / 2 - 6, 34, 12, Align::Right);
+    draw::draw_text2("0%", x - 38, y + h - 6, 34, 12, Align::Right);
+    draw::set_draw_color(Color::Light1);
+    draw::draw_line(x, y + h / 2, x + w, y + h / 2);
+    if values.is_empty() {
+        draw::set_draw_color(Color::Black);
+        draw::draw_text2("no games", x, y, w, h, Align::Center);
+        return;
+    }
+    let step = w as f64 / (values.len().max(2) - 1) as f64;
+    let point = |i: usize| {
+        (
+            x + (i as f64 * step) as i32,
+            y + h - (values[i] * h as f64) as i32,
+        )
+    };
+    draw::set_draw_color(LINE_COLOR);
+    draw::set_line_style(draw::LineStyle::Solid, 2);
+    for i in 1..values.len() {
+        let ((x1, y1), (x2, y2)) = (point(i - 1), point(i));
+        draw::draw_line(x1, y1, x2, y2);
+    }
+    draw::set_line_style(draw::LineStyle::Solid, 0);
+    draw::set_draw_color(Color::Black);
+    draw::draw_text2(
+        &format!("{} games", values.len()),
+        x,
+        y + h + 2,
+        w,
+        12,
+        Align::Right,
+    );
+}
+
+/// a row of every opponent: games, a bar as long as the win rate and the average points
+fn draw_opponents(area: (i32, i32, i32, i32), entries: &[&GameEntry]) {
+    let (x, y, w, _) = area;
+    let x = x - 30;
+    draw::set_draw_color(Color::Black);
+    draw::draw_text2("opponent", x, y, 120, 14, Align::Left);
+    draw::draw_text2("games", x + 120, y, 50, 14, Align::Right);
+    draw::draw_text2("win rate", x + 180, y, 150, 14, Align::Left);
+    draw::draw_text2("points", x + w - 80, y, 70, 14, Align::Right);
+    for (i, (opponent, stats)) in opponent_stats(entries).iter().enumerate() {
+        let row_y = y + 20 + i as i32 * 20;
+        draw::set_draw_color(Color::Black);
+        draw::draw_text2(opponent, x, row_y, 120, 14, Align::Left);
+        draw::draw_text2(
+            &stats.games.to_string(),
+            x + 120,
+            row_y,
+            50,
+            14,
+            Align::Right,
+        );
+        let bar_w = (stats.win_rate() * 100.0) as i32;
+        draw::draw_rect_fill(x + 180, row_y + 2, bar_w.max(1), 10, BAR_COLOR);
+        draw::set_draw_color(Color::Black);
+        draw::draw_text2(
+            &format!("{:.0}%", stats.win_rate() * 100.0),
+            x + 185 + bar_w,
+            row_y,
+            50,
+            14,
+            Align::Left,
+        );
+        draw::draw_text2(
+            &format!(
+                "{:.1} - {:.1}",
+                stats.average_points(),
+                stats.average_opponent_points()
+            ),
+            x + w - 110,
+            row_y,
+            100,
+            14,
+            Align::Right,
+        );
+    }
+}
+
+fn draw_dashboard(x: i32, y: i32, w: i32, h: i32, entries: &[&GameEntry]) {
+    let (half_w, half_h) = (w / 2 - 5, h / 2 - 5);
+    let right = x + half_w + 10;
+    let bottom = y + half_h + 10;
+
+    let counts = score_histogram(entries, SCORE_BIN);
+    let values: Vec<f64> = counts.iter().map(|c| *c as f64).collect();
+    let labels: Vec<String> = (0..counts.len())
+        .map(|i| (i * SCORE_BIN).to_string())
+        .collect();
+    let area = draw_panel("Points of the human", x, y, half_w, half_h);
+    draw_bars(area, &values, &labels);
+
+    let area = draw_panel(
+        &format!("Win rate of the last {} games", TREND_WINDOW),
+        right,
+        y,
+        half_w,
+        half_h,
+    );
+    draw_trend(area, &win_rate_trend(entries, TREND_WINDOW));
+
+    let pistis: Vec<f64> = entries
+        .iter()
+        .filter_map(|e| e.human().map(|me| e.pistis(me) as f64))
+        .collect();
+    let stats = history_stats(entries.iter().copied());
+    let area = draw_panel(
+        &format!("Pistis per game, {:.2} on average", stats.pistis_per_game()),
+        x,
+        bottom,
+        half_w,
+        half_h,
+    );
+    draw_bars(area, &pistis, &[]);
+
+    let area = draw_panel("Against every AI", right, bottom, half_w, half_h);
+    draw_opponents(area, entries);
+}
+
+/// a window with charts of the history of `profile`, or of every game without one,
+/// the filters redraw them
+pub fn open_dashboard(entries: Vec<GameEntry>, profile: Option<String>) {
+    let title = match &profile {
+        Some(name) => format!("Statistics of {}", name),
+        None => String::from("Statistics"),
+    };
+    let filter = Rc::new(RefCell::new(HistoryFilter {
+        profile,
+        ..HistoryFilter::default()
+    }));
+    let mut win = Window::default()
+        .with_size(DASHBOARD_W, DASHBOARD_H)
+        .with_label(&title);
+    let mut rules = Choice::new(60, 10, 140, 30, "Rules");
+    rules.add_choice("all");
+    for preset in RulePreset::iter() {
+        rules.add_choice(&preset.to_string());
+    }
+    rules.set_value(0);
+    let mut from = Input::new(260, 10, 110, 30, "From");
+    let mut to = Input::new(410, 10, 110, 30, "To");
+    let mut summary = Frame::new(530, 10, DASHBOARD_W - 540, 30, "");
+    summary.set_align(Align::Left | Align::Inside);
+    let mut chart = Frame::new(10, 50, DASHBOARD_W - 20, DASHBOARD_H - 60, "");
+    win.end();
+
+    let draw_filter = Rc::clone(&filter);
+    chart.draw(move |f| {
+        let filter = draw_filter.borrow();
+        let shown: Vec<&GameEntry> = entries.iter().filter(|e| filter.matches(e)).collect();
+        let stats = history_stats(shown.iter().copied());
+        summary.set_label(&format!(
+            "{} games against the AI, won {:.0}%, {:.1} points on average",
+            stats.games,
+            stats.win_rate() * 100.0,
+            stats.average_points()
+        ));
+        summary.redraw();
+        draw_dashboard(f.x(), f.y(), f.w(), f.h(), &shown);
+    });
+
+    let rules_filter = Rc::clone(&filter);
+    let mut rules_chart = chart.clone();
+    rules.set_callback(move |c| {
+        rules_filter.borrow_mut().rules = c.choice().and_then(|r| r.parse::<RulePreset>().ok());
+        rules_chart.redraw();
+    });
+    // the dates are matched while they are typed, `2026` or `2026.10` are enough
+    for (input, is_from) in [(&mut from, true), (&mut to, false)] {
+        input.set_trigger(CallbackTrigger::Changed);
+        let date_filter = Rc::clone(&filter);
+        let mut date_chart = chart.clone();
+        input.set_callback(move |i| {
+            let value = i.value().trim().to_string();
+            let date = (!value.is_empty()).then_some(value);
+            let mut filter = date_filter.borrow_mut();
+            if is_from {
+                filter.from = date;
+            } else {
+                filter.to = date;
+            }
+            date_chart.redraw();
+        });
+    }
+    win.show();
+}
@@ -0,0 +1,270 @@
+//! Line based protocol to let an external program play a seat, similar to UCI for chess.
+//!
+//! Every message is one line of space separated words. Cards are written as
+//! rank + suit letters: `AS`, `TD`, `7H`, `JC`. A card that is face down is `xx`.
+//! Seats are `1` (Player1, leads every deal) and `2` (Player2).
+//!
+//! ```text
+//! GUI -> engine                          engine -> GUI
+//! pisti                                  id name <name>        (optional)
+//!                                        pistiok
+//! rules preset=standard pisti=10 jackpisti=10 majority=3 ace=1 jack=1 2c=2 10d=3 lastplayer=2 lastcapture=0
+//! newgame <your seat>
+//! board xx xx xx 7H                      (cards put onto the board at the start)
+//! deal 4C KH 9S 2D                       (your new hand, sent every deal)
+//! played <seat> <card> <pass|win|pisti>  (every move, your own moves included)
+//! go <milliseconds>                      play <card>
+//! illegal <card>                         (the reply was not in your hand, a fallback card is played)
+//! gameover <points of seat 1> <points of seat 2>
+//! quit
+//! ```
+//!
+//! `lastplayer` is the seat that gets the cards left on the board when the game ends,
+//! unless `lastcapture` is 1 and they go to the last seat that took the board.
+//! When the engine does not answer `go` in time, dies or plays an illegal card the
+//! move is made by the basic strategy instead. The reason goes to stderr, where
+//! `pistiflex tui 2>engine.log` keeps it off the game screen.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    game::{Card, Game, Player},
+    strategy::{BasicStrategy, Strategy},
+};
+
+pub const ENGINE_HANDSHAKE_TIMEOUT_MS: u64 = 5000;
+pub const ENGINE_MOVE_TIMEOUT_MS: u64 = 2000;
+const CARDS_PER_DEAL: usize = 8;
+
+pub fn seat_number(a_player: Player) -> u8 {
+    match a_player {
+        Player::Player1 => 1,
+        Player::Player2 => 2,
+    }
+}
+
+pub fn cards_to_str(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(|c| c.notation())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+pub struct EngineStrategy {
+    name: String,
+    child: Child,
+    stdin: Option<ChildStdin>,
+    lines: Receiver<String>,
+    move_timeout: Duration,
+    started: bool,
+    sent_moves: usize,
+    sent_deals: usize,
+    fallback: BasicStrategy,
+}
+
+impl EngineStrategy {
+    /// starts `command` (program followed by its arguments) and waits for `pistiok`
+    pub fn spawn(command: &str) -> Result<Self, String> {
+        let mut words = command.split_whitespace();
+        let program = words.next().ok_or("empty engine command")?;
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("cannot start engine {}: {}", command, e))?;
+        let stdout = child.stdout.take().unwrap();
+        let (line_s, line_r) = mpsc::channel::<String>();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(a_line) => {
+                        if line_s.send(a_line).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+        let mut engine = EngineStrategy {
+            name: command.to_string(),
+            stdin: child.stdin.take(),
+            child,
+            lines: line_r,
+            move_timeout: Duration::from_millis(ENGINE_MOVE_TIMEOUT_MS),
+            started: false,
+            sent_moves: 0,
+            sent_deals: 0,
+            fallback: BasicStrategy,
+        };
+        engine.send("pisti");
+        let deadline = Instant::now() + Duration::from_millis(ENGINE_HANDSHAKE_TIMEOUT_MS);
+        loop {
+            match engine.read_line(deadline) {
+                Ok(a_line) if a_line == "pistiok" => break,
+                Ok(a_line) => {
+                    if let Some(a_name) = a_line.strip_prefix("id name ") {
+                        engine.name = a_name.trim().to_string();
+                    }
+                }
+                Err(e) => return Err(format!("engine {} did not say pistiok: {}", command, e)),
+            }
+        }
+        Ok(engine)
+    }
+
+    fn send(&mut self, line: &str) {
+        if let Some(stdin) = self.stdin.as_mut() {
+            if let Err(e) = writeln!(stdin, "{}", line).and_then(|_| stdin.flush()) {
+                eprintln!("Cannot send message to engine {}: {}", self.name, e);
+                self.stdin = None;
+            }
+        }
+    }
+
+    fn read_line(&self, deadline: Instant) -> Result<String, String> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match self.lines.recv_timeout(timeout) {
+            Ok(a_line) => Ok(a_line.trim().to_string()),
+            Err(RecvTimeoutError::Timeout) => Err(String::from("timeout")),
+            Err(RecvTimeoutError::Disconnected) => Err(String::from("engine exited")),
+        }
+    }
+
+    /// sends what happened since the last time the engine was asked for a card
+    fn send_updates(&mut self, game: &Game, player: Player) {
+        if !self.started {
+            self.started = true;
+            let rules = game.rules;
+            self.send(&format!(
+                "rules preset={} pisti={} jackpisti={} majority={} ace={} jack={} 2c={} 10d={} lastplayer={} lastcapture={}",
+                rules.preset,
+                rules.pisti,
+                rules.jack_pisti,
+                rules.majority,
+                rules.ace,
+                rules.jack,
+                rules.club_2,
+                rules.diamond_10,
+                seat_number(game.get_last_player()),
+                if rules.last_capture_takes_board { 1 } else { 0 },
+            ));
+            self.send(&format!("newgame {}", seat_number(player)));
+            let board: Vec<String> = game
+                .initial_board()
+                .iter()
+                .enumerate()
+                .map(|(i, c)| {
+                    if i < 3 {
+                        String::from("xx")
+                    } else {
+                        c.notation()
+                    }
+                })
+                .collect();
+            self.send(&format!("board {}", board.join(" ")));
+            self.send_deal(game, player);
+        }
+        let deals_done = 1 + game.moves.len() / CARDS_PER_DEAL;
+        for i in self.sent_moves..game.moves.len() {
+            // a new hand is only known when we are asked right after the deal
+            if i > 0 && i % CARDS_PER_DEAL == 0 && self.sent_deals < i / CARDS_PER_DEAL + 1 {
+                self.send_deal(game, player);
+            }
+            let a_move = game.moves[i];
+            self.send(&format!(
+                "played {} {} {}",
+                seat_number(a_move.player),
+                a_move.card.notation(),
+                a_move.stat
+            ));
+        }
+        self.sent_moves = game.moves.len();
+        if self.sent_deals < deals_done && !game.is_game_over() {
+            self.send_deal(game, player);
+        }
+    }
+
+    fn send_deal(&mut self, game: &Game, player: Player) {
+        let hand = cards_to_str(game.get_player_cards(player));
+        self.send(&format!("deal {}", hand));
+        self.sent_deals += 1;
+    }
+
+    fn ask_for_card(&mut self, game: &Game, player: Player) -> Result<usize, String> {
+        // drop late answers to an earlier go
+        while self.lines.try_recv().is_ok() {}
+        self.send(&format!("go {}", self.move_timeout.as_millis()));
+        let deadline = Instant::now() + self.move_timeout;
+        let reply = loop {
+            let a_line = self.read_line(deadline)?;
+            if let Some(a_card) = a_line.strip_prefix("play ") {
+                break a_card.trim().to_string();
+            }
+        };
+        let hand = game.get_player_cards(player);
+        match reply.parse::<Card>() {
+            Ok(a_card) if hand.contains(&a_card) => Ok(game.get_index_of_card(a_card, player)),
+            _ => {
+                self.send(&format!("illegal {}", reply));
+                Err(format!("illegal card {}", reply))
+            }
+        }
+    }
+}
+
+impl Strategy for EngineStrategy {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn pick_card(&mut self, game: &Game, player: Player) -> usize {
+        self.send_updates(game, player);
+        match self.ask_for_card(game, player) {
+            Ok(i) => i,
+            Err(e) => {
+                eprintln!("Engine {} failed to play: {}", self.name, e);
+                self.fallback.pick_card(game, player)
+            }
+        }
+    }
+
+    fn game_over(&mut self, game: &Game, player: Player) {
+        if !self.started {
+            return;
+        }
+        self.send_updates(game, player);
+        self.send(&format!(
+            "gameover {} {}",
+            game.player1_point, game.player2_point
+        ));
+        // the next game starts with newgame again
+        self.started = false;
+        self.sent_moves = 0;
+        self.sent_deals = 0;
+    }
+}
+
+impl Drop for EngineStrategy {
+    fn drop(&mut self) {
+        self.send("quit");
+        self.stdin = None;
+        let deadline = Instant::now() + Duration::from_millis(500);
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{
+    cards::CardSet,
+    game::{win_status_of, Card, Game, Player, Rules, WinStatus, R_J},
+};
+
+pub const FEATURE_COUNT: usize = 14;
+
+pub const FEATURE_NAMES: [&str; FEATURE_COUNT] = [
+    "capture",
+    "capture_points",
+    "capture_cards",
+    "pisti",
+    "jack_used",
+    "jacks_held",
+    "card_points",
+    "pisti_threat",
+    "capture_risk",
+    "board_points",
+    "board_cards",
+    "unseen_same_rank",
+    "deck_remaining",
+    "jack_used_early",
+];
+
+pub type Features = [f64; FEATURE_COUNT];
+
+/// what one seat knows about the game: its own hand, the visible board and the played cards
+#[derive(Debug, Clone)]
+pub struct SeatView {
+    pub hand: Vec<Card>,
+    /// cards on the board, None for the cards put face down at the start
+    pub board: Vec<Option<Card>>,
+    pub deck_len: usize,
+    pub opponent_hand_len: usize,
+    /// how many cards of each rank (index 1..=13) the seat has not seen yet
+    pub unseen_ranks: [u8; 14],
+    pub unseen_count: usize,
+    /// how much more likely than average the opponent holds an unseen card of each rank
+    pub rank_weights: [f64; 14],
+    pub rules: Rules,
+}
+
+impl SeatView {
+    pub fn new(game: &Game, player: Player) -> Self {
+        let mut seen: CardSet = game.moves.iter().map(|m| m.card).collect();
+        seen.extend_from_slice(game.get_player_cards(player));
+        let initial_board = game.initial_board();
+        if let Some(top_card) = initial_board.last() {
+            seen.insert(*top_card);
+        }
+        let hidden: CardSet = initial_board.iter().take(3).copied().collect();
+        let board = game
+            .board
+            .iter()
+            .map(|c| if hidden.contains(*c) { None } else { Some(*c) })
+            .collect();
+        let mut unseen_ranks = [0u8; 14];
+        for (rank, unseen) in unseen_ranks.iter_mut().enumerate().skip(1) {
+            *unseen = 4 - seen.rank_count(rank as u8) as u8;
+        }
+        SeatView {
+            hand: game.get_player_cards(player).to_vec(),
+            board,
+            deck_len: game.deck.len(),
+            opponent_hand_len: game.get_player_cards(player.other()).len(),
+            unseen_count: 52 - seen.len(),
+            unseen_ranks,
+            rank_weights: [1.0; 14],
+            rules: game.rules,
+        }
+    }
+
+    pub fn top_card(&self) -> Option<Card> {
+        self.board.last().copied().flatten()
+    }
+
+    /// points of the board as far as the seat can see
+    pub fn board_points(&self) -> usize {
+        self.board
+            .iter()
+            .flatten()
+            .map(|c| self.rules.card_points(c))
+            .sum()
+    }
+
+    pub fn jacks_held(&self) -> usize {
+        self.hand.iter().filter(|c| c.rank == R_J).count()
+    }
+
+    /// chance that the opponent holds at least one card of `rank`
+    pub fn opponent_holds_rank(&self, rank: u8) -> f64 {
+        if self.unseen_count == 0 {
+            return 0.0;
+        }
+        let total: f64 = (1..14)
+            .map(|r| self.unseen_ranks[r] as f64 * self.rank_weights[r])
+            .sum();
+        if total <= 0.0 {
+            return 0.0;
+        }
+        let per_card = (self.unseen_ranks[rank as usize] as f64 * self.rank_weights[rank as usize]
+            / total)
+            .min(1.0);
+        1.0 - (1.0 - per_card).powi(self.opponent_hand_len as i32)
+    }
+
+    /// features of playing `a_card` from the hand
+    pub fn features(&self, a_card: Card) -> Features {
+        let mut f = [0.0; FEATURE_COUNT];
+        let board_len = self.board.len();
+        let stat = win_status_of(self.top_card(), board_len, a_card);
+        let deck_remaining = self.deck_len as f64 / 52.0;
+        let is_jack = a_card.rank == R_J;
+        f[4] = if is_jack { 1.0 } else { 0.0 };
+        f[5] = (self.jacks_held() - if is_jack { 1 } else { 0 }) as f64;
+        f[11] = self.unseen_ranks[a_card.rank as usize] as f64;
+        f[12] = deck_remaining;
+        f[13] = f[4] * deck_remaining;
+        match stat {
+            WinStatus::Pisti | WinStatus::Win => {
+                f[0] = 1.0;
+                f[1] = (self.board_points() + self.rules.card_points(&a_card)) as f64;
+                f[2] = (board_len + 1) as f64;
+                if stat == WinStatus::Pisti {
+                    f[3] = 1.0;
+                }
+            }
+            WinStatus::Pass => {
+                let board_points = (self.board_points() + self.rules.card_points(&a_card)) as f64;
+                f[6] = self.rules.card_points(&a_card) as f64;
+                if board_len == 0 {
+                    f[7] = self.opponent_holds_rank(a_card.rank);
+                }
+                let risk = self
+                    .opponent_holds_rank(a_card.rank)
+                    .max(self.opponent_holds_rank(R_J));
+                f[8] = risk * (board_points + (board_len + 1) as f64 / 10.0);
+                f[9] = board_points;
+                f[10] = (board_len + 1) as f64;
+            }
+        }
+        f
+    }
+}
+
+pub fn evaluate(weights: &Features, features: &Features) -> f64 {
+    weights
+        .iter()
+        .zip(features.iter())
+        .map(|(w, f)| w * f)
+        .sum()
+}
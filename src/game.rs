@@ -1,36 +1,127 @@
 extern crate rand;
 
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{Rng, SeedableRng};
 use std::fmt;
+use std::str::FromStr;
 use strum::IntoEnumIterator;
-use strum_macros::EnumIter;
+use strum_macros::{Display, EnumIter, EnumString};
 
-type PlayCards = Vec<Card>;
+use crate::cards::{CardSet, FixedVec};
 
-#[derive(Copy, Clone, Debug)]
+type PlayCards = FixedVec<Card, 52>;
+/// a hand never holds more than the 4 cards of a deal
+type Hand = FixedVec<Card, 4>;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub enum Player {
+    #[default]
     Player1,
     Player2,
 }
 
-const R_A: u8 = 1;
-const R_J: u8 = 11;
-const R_Q: u8 = 12;
-const R_K: u8 = 13;
+impl Player {
+    pub fn other(&self) -> Player {
+        match self {
+            Player::Player1 => Player::Player2,
+            Player::Player2 => Player::Player1,
+        }
+    }
+}
+
+pub const R_A: u8 = 1;
+pub const R_J: u8 = 11;
+pub const R_Q: u8 = 12;
+pub const R_K: u8 = 13;
+
+#[derive(Debug, EnumIter, EnumString, Display, Copy, Clone, PartialEq)]
+#[strum(serialize_all = "kebab-case")]
+pub enum RulePreset {
+    /// the rules the game always had
+    Standard,
+    /// a pisti made with a J is worth 20 points
+    JackPisti,
+    /// the cards left on the board at the end go to the last player who took the board
+    LastCapture,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Rules {
+    pub preset: RulePreset,
+    pub pisti: usize,
+    pub jack_pisti: usize,
+    /// for having more cards than the opponent
+    pub majority: usize,
+    pub ace: usize,
+    pub jack: usize,
+    pub club_2: usize,
+    pub diamond_10: usize,
+    /// otherwise the player who plays last gets them
+    pub last_capture_takes_board: bool,
+}
+
+impl RulePreset {
+    pub fn rules(&self) -> Rules {
+        let standard = Rules {
+            preset: *self,
+            pisti: 10,
+            jack_pisti: 10,
+            majority: 3,
+            ace: 1,
+            jack: 1,
+            club_2: 2,
+            diamond_10: 3,
+            last_capture_takes_board: false,
+        };
+        match self {
+            RulePreset::Standard => standard,
+            RulePreset::JackPisti => Rules {
+                jack_pisti: 20,
+                ..standard
+            },
+            RulePreset::LastCapture => Rules {
+                last_capture_takes_board: true,
+                ..standard
+            },
+        }
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        RulePreset::Standard.rules()
+    }
+}
+
+impl Rules {
+    /// points of a won card, pisti and majority points are not included
+    pub fn card_points(&self, p_card: &Card) -> usize {
+        match (p_card.rank, p_card.suit) {
+            (R_A, _) => self.ace,
+            (R_J, _) => self.jack,
+            (2, Suit::Club) => self.club_2,
+            (10, Suit::Diamond) => self.diamond_10,
+            _ => 0,
+        }
+    }
+}
 
-#[derive(Debug, EnumIter, Copy, Clone, PartialEq)]
+#[derive(Debug, EnumIter, Copy, Clone, Default, PartialEq)]
 pub enum Suit {
+    #[default]
     Spade,
     Heart,
     Diamond,
     Club,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Default, Display, EnumString, PartialEq)]
+#[strum(serialize_all = "lowercase")]
 pub enum WinStatus {
     Pisti,
     Win,
+    #[default]
     Pass,
 }
 
@@ -46,7 +137,7 @@ impl fmt::Display for Suit {
     }
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Default, PartialEq)]
 pub struct Card {
     pub rank: u8,
     pub suit: Suit,
@@ -64,41 +155,145 @@ impl fmt::Debug for Card {
     }
 }
 
+impl Card {
+    /// ascii notation of the card like "AS", "TD", "7H"
+    pub fn notation(&self) -> String {
+        let first_letter = match self.rank {
+            10 => "T".to_string(),
+            x => rank_to_str(x),
+        };
+        let second_letter = match self.suit {
+            Suit::Spade => "S",
+            Suit::Heart => "H",
+            Suit::Diamond => "D",
+            Suit::Club => "C",
+        };
+        format!("{}{}", first_letter, second_letter)
+    }
+}
+
+impl FromStr for Card {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.trim().to_uppercase().chars().collect();
+        if chars.len() != 2 {
+            return Err(format!("invalid card: {}", s));
+        }
+        let rank = match chars[0] {
+            'A' => R_A,
+            'T' => 10,
+            'J' => R_J,
+            'Q' => R_Q,
+            'K' => R_K,
+            x => match x.to_digit(10) {
+                Some(d) if d > 1 => d as u8,
+                _ => return Err(format!("invalid rank: {}", s)),
+            },
+        };
+        let suit = match chars[1] {
+            'S' => Suit::Spade,
+            'H' => Suit::Heart,
+            'D' => Suit::Diamond,
+            'C' => Suit::Club,
+            _ => return Err(format!("invalid suit: {}", s)),
+        };
+        Ok(Card { rank, suit })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DealStatus {
+    Playing,
+    /// new (bottom hand, top hand)
+    Dealt(Vec<Card>, Vec<Card>),
+    GameOver,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Move {
+    pub player: Player,
+    pub card: Card,
+    pub stat: WinStatus,
+}
+
+/// where the points of a player come from, they add up to what `calculate_points` gives
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ScoreBreakdown {
+    pub pistis: usize,
+    /// for having more cards than the opponent
+    pub majority: usize,
+    /// the aces, jacks, 2♣︎ and 10♦︎ won
+    pub cards: usize,
+}
+
+impl ScoreBreakdown {
+    pub fn total(&self) -> usize {
+        self.pistis + self.majority + self.cards
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Game {
     pub board: PlayCards,
     pub deck: PlayCards,
-    pub bottom_hand: PlayCards,
-    pub top_hand: PlayCards,
-    pub player1_won_cards: PlayCards,
-    pub player2_won_cards: PlayCards,
+    pub bottom_hand: Hand,
+    pub top_hand: Hand,
+    pub player1_won_cards: CardSet,
+    pub player2_won_cards: CardSet,
     pub player1_pisti_count: u8,
     pub player2_pisti_count: u8,
     pub first_player: Player,
-    pub ai_player: Option<Player>, // if there are two human players set this None
+    pub turn: Player,
     pub player1_point: usize,
     pub player2_point: usize,
+    /// deck order the game was dealt from, cards are dealt from the end
+    pub initial_deck: PlayCards,
+    /// a game has 48 moves, the 4 cards put onto the board at the start are not moves
+    pub moves: FixedVec<Move, 48>,
+    /// the same seed deals the same cards
+    pub seed: u64,
+    /// how many times the deck was reshuffled because a J was on top of the board
+    pub reshuffles: usize,
+    pub rules: Rules,
+    rng: StdRng,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game::new()
+    }
 }
 
 impl Game {
     pub fn new() -> Self {
+        Game::with_seed(rand::random())
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
         Game {
-            board: vec![],
-            deck: vec![],
-            bottom_hand: vec![],
-            top_hand: vec![],
-            player1_won_cards: vec![],
-            player2_won_cards: vec![],
+            board: FixedVec::new(),
+            deck: FixedVec::new(),
+            bottom_hand: FixedVec::new(),
+            top_hand: FixedVec::new(),
+            player1_won_cards: CardSet::new(),
+            player2_won_cards: CardSet::new(),
             player1_pisti_count: 0,
             player2_pisti_count: 0,
             first_player: Player::Player1,
-            ai_player: Some(Player::Player1),
+            turn: Player::Player1,
             player1_point: 0,
             player2_point: 0,
+            initial_deck: FixedVec::new(),
+            moves: FixedVec::new(),
+            seed,
+            reshuffles: 0,
+            rules: Rules::default(),
+            rng: StdRng::seed_from_u64(seed),
         }
     }
     pub fn create_deck(&mut self) {
-        let mut deck_vec: PlayCards = Vec::with_capacity(52);
+        let mut deck_vec = PlayCards::new();
         for a_suit in Suit::iter() {
             for a_rank in 1..14 {
                 deck_vec.push(Card {
@@ -112,8 +307,7 @@ impl Game {
 
     pub fn shuffle_deck(&mut self) {
         if self.deck.len() > 0 {
-            let mut rng = thread_rng();
-            self.deck.shuffle(&mut rng);
+            self.deck.shuffle(&mut self.rng);
         }
     }
 
@@ -142,50 +336,15 @@ impl Game {
         }
     }
 
-    pub fn pick_card_for_ai(&mut self) -> usize {
-        let ai_cards = self.get_ai_player_hand().unwrap();
-        let mut tmp_i = get_random_index(&self.top_hand);
-
-        return if self.board.len() > 0 {
-            let card_on_board = self.board.last().unwrap();
-            for i in 0..ai_cards.len() {
-                let pp_card = ai_cards.get(i).unwrap();
-                if pp_card.rank == card_on_board.rank {
-                    tmp_i = i;
-                }
-            }
-            tmp_i
-        } else {
-            tmp_i
-        };
-    }
-
     pub fn play_card(&mut self, a_card: Card) -> WinStatus {
+        let stat = win_status_of(self.board.last().copied(), self.board.len(), a_card);
         self.board.push(a_card);
-        let board_len = self.board.len();
-        return if board_len > 1 {
-            let last_card_1 = self.board.get(board_len - 1).unwrap();
-            let last_card_2 = self.board.get(board_len - 2).unwrap();
-            if last_card_1.rank == last_card_2.rank {
-                if board_len == 2 {
-                    WinStatus::Pisti
-                } else {
-                    WinStatus::Win
-                }
-            } else if last_card_1.rank == R_J {
-                WinStatus::Win
-            } else {
-                WinStatus::Pass
-            }
-        } else {
-            WinStatus::Pass
-        };
+        stat
     }
 
     pub fn create_pisti(&mut self, stat: WinStatus, player: Player) {
         match stat {
             WinStatus::Pisti => {
-                println!("Pisti!!!");
                 match player {
                     Player::Player1 => {
                         self.player1_pisti_count += 1;
@@ -203,11 +362,13 @@ impl Game {
         match stat {
             WinStatus::Pisti | WinStatus::Win => match player {
                 Player::Player1 => {
-                    self.player1_won_cards.append(&mut self.board);
+                    self.player1_won_cards.extend_from_slice(&self.board);
+                    self.board.clear();
                     self.create_pisti(stat, player);
                 }
                 Player::Player2 => {
-                    self.player2_won_cards.append(&mut self.board);
+                    self.player2_won_cards.extend_from_slice(&self.board);
+                    self.board.clear();
                     self.create_pisti(stat, player);
                 }
             },
@@ -216,13 +377,60 @@ impl Game {
         // self.calculate_points();
     }
 
-    pub fn get_last_player(&self) -> Player {
-        match self.first_player {
-            Player::Player1 => Player::Player2,
-            Player::Player2 => Player::Player1,
+    /// plays the card at `hand_index` of `player` and collects the board if it wins
+    pub fn play_turn(&mut self, player: Player, hand_index: usize) -> (Card, WinStatus) {
+        let a_card = self.get_player_cards_mut(player).remove(hand_index);
+        let stat = self.play_card(a_card);
+        self.move_cards_if_win(stat, player);
+        self.turn = player.other();
+        self.moves.push(Move {
+            player,
+            card: a_card,
+            stat,
+        });
+        (a_card, stat)
+    }
+
+    pub fn is_deal_over(&self) -> bool {
+        self.bottom_hand.is_empty() && self.top_hand.is_empty()
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.is_deal_over() && self.deck.len() < 8
+    }
+
+    /// deals new hands once both hands are played, or ends the game when the deck is out of cards
+    pub fn next_deal(&mut self) -> DealStatus {
+        if !self.is_deal_over() {
+            DealStatus::Playing
+        } else if self.deck.len() > 7 {
+            let (bottom_hand, top_hand) = self.give_cards_to_players();
+            DealStatus::Dealt(bottom_hand, top_hand)
+        } else {
+            self.finish_game();
+            DealStatus::GameOver
         }
     }
 
+    /// the player who gets the cards left on the board at the end
+    pub fn board_taker(&self) -> Player {
+        let last_capture = self.moves.iter().rev().find(|m| m.stat != WinStatus::Pass);
+        match last_capture {
+            Some(a_move) if self.rules.last_capture_takes_board => a_move.player,
+            _ => self.get_last_player(),
+        }
+    }
+
+    /// gives all remaining cards on board to the board taker
+    pub fn finish_game(&mut self) {
+        self.move_cards_if_win(WinStatus::Win, self.board_taker());
+        self.calculate_points();
+    }
+
+    pub fn get_last_player(&self) -> Player {
+        self.first_player.other()
+    }
+
     pub fn get_index_of_card(&self, a_card: Card, a_player: Player) -> usize {
         let mut ri = 0;
         for (i, t_card) in self.get_player_cards(a_player).iter().enumerate() {
@@ -240,91 +448,146 @@ impl Game {
         }
     }
 
-    pub fn get_player_cards(&self, a_player: Player) -> &PlayCards {
+    pub fn get_player_cards(&self, a_player: Player) -> &Hand {
         match a_player {
             Player::Player1 => &self.bottom_hand,
             Player::Player2 => &self.top_hand,
         }
     }
 
-    pub fn get_ai_player_hand(&self) -> Option<&PlayCards> {
-        match self.ai_player {
-            None => None,
-            Some(a_player) => Some(self.get_player_cards(a_player)),
+    pub fn get_player_cards_mut(&mut self, a_player: Player) -> &mut Hand {
+        match a_player {
+            Player::Player1 => &mut self.bottom_hand,
+            Player::Player2 => &mut self.top_hand,
         }
     }
 
-    pub fn calculate_points(&mut self) {
-        let player_1_card_count_score =
-            if self.player1_won_cards.len() > self.player2_won_cards.len() {
-                3
-            } else {
-                0
-            };
-        let player_2_card_count_score =
-            if self.player2_won_cards.len() > self.player1_won_cards.len() {
-                3
+    /// pistis made with a J can be worth more, they are counted from the moves
+    pub fn pisti_points(&self, a_player: Player) -> usize {
+        let pisti_count = match a_player {
+            Player::Player1 => self.player1_pisti_count as usize,
+            Player::Player2 => self.player2_pisti_count as usize,
+        };
+        let jack_pistis = self
+            .moves
+            .iter()
+            .filter(|m| m.player == a_player && m.stat == WinStatus::Pisti && m.card.rank == R_J)
+            .count()
+            .min(pisti_count);
+        (pisti_count - jack_pistis) * self.rules.pisti + jack_pistis * self.rules.jack_pisti
+    }
+
+    pub fn score_breakdown(&self, a_player: Player) -> ScoreBreakdown {
+        let (won, other) = match a_player {
+            Player::Player1 => (self.player1_won_cards, self.player2_won_cards),
+            Player::Player2 => (self.player2_won_cards, self.player1_won_cards),
+        };
+        ScoreBreakdown {
+            pistis: self.pisti_points(a_player),
+            majority: if won.len() > other.len() {
+                self.rules.majority
             } else {
                 0
-            };
-        self.player1_point = (self.player1_pisti_count * 10) as usize + player_1_card_count_score;
-        self.player2_point = (self.player2_pisti_count * 10) as usize + player_2_card_count_score;
-        for p_card in &self.player1_won_cards {
-            if p_card.rank == R_A || p_card.rank == R_J {
-                self.player1_point += 1;
-            } else if p_card.rank == 2
-                && match p_card.suit {
-                    Suit::Club => true,
-                    _ => false,
-                }
-            {
-                self.player1_point += 2;
-            } else if p_card.rank == 10
-                && match p_card.suit {
-                    Suit::Diamond => true,
-                    _ => false,
-                }
-            {
-                self.player1_point += 3;
-            }
-        }
-        for p_card in &self.player2_won_cards {
-            if p_card.rank == R_A || p_card.rank == R_J {
-                self.player2_point += 1;
-            } else if p_card.rank == 2
-                && match p_card.suit {
-                    Suit::Club => true,
-                    _ => false,
-                }
-            {
-                self.player2_point += 2;
-            } else if p_card.rank == 10
-                && match p_card.suit {
-                    Suit::Diamond => true,
-                    _ => false,
-                }
-            {
-                self.player2_point += 3;
-            }
+            },
+            cards: won.points(&self.rules),
         }
     }
 
+    pub fn calculate_points(&mut self) {
+        self.player1_point = self.score_breakdown(Player::Player1).total();
+        self.player2_point = self.score_breakdown(Player::Player2).total();
+    }
+
     pub fn start_game_and_give_cards_to_players(&mut self) {
         self.create_deck();
         self.shuffle_deck();
+        self.initial_deck = self.deck;
         self.put_cards_onto_board();
         while self.is_reshuffle_required() {
-            println!("J is the top card on board. Reshuffling");
+            self.reshuffles += 1;
+            self.board.clear();
             self.create_deck();
             self.shuffle_deck();
+            self.initial_deck = self.deck;
             self.put_cards_onto_board();
         }
         self.give_cards_to_players();
     }
+
+    /// starts with `deck` instead of a shuffled deck, the cards are dealt from its end
+    pub fn start_game_with_deck(&mut self, deck: &[Card]) {
+        self.deck = deck.iter().copied().collect();
+        self.initial_deck = self.deck;
+        self.put_cards_onto_board();
+        self.give_cards_to_players();
+    }
+
+    /// the cards put onto the board at the start, the last one is face up
+    pub fn initial_board(&self) -> Vec<Card> {
+        self.initial_deck.iter().rev().take(4).copied().collect()
+    }
+
+    /// the cards put face down at the start that are still on the board
+    pub fn face_down_cards(&self) -> Vec<Card> {
+        self.initial_board()
+            .into_iter()
+            .take(3)
+            .filter(|c| self.board.contains(c))
+            .collect()
+    }
+
+    /// the hand of `a_player` as it was dealt at the start of this deal, the played cards included
+    pub fn dealt_hand(&self, a_player: Player) -> Vec<Card> {
+        if self.initial_deck.is_empty() {
+            return self.get_player_cards(a_player).to_vec();
+        }
+        self.hand_of_deal(a_player, self.moves.len() / 8)
+    }
+
+    /// the hand `a_player` gets in deal `deal`, counted from 0
+    pub fn hand_of_deal(&self, a_player: Player, deal: usize) -> Vec<Card> {
+        self.initial_deck
+            .iter()
+            .rev()
+            .skip(deal_position(a_player, deal, 0))
+            .step_by(2)
+            .take(4)
+            .copied()
+            .collect()
+    }
+}
+
+/// how far from the end of the initial deck card `index` of the hand of `a_player` in deal
+/// `deal` is: 4 cards go onto the board, then each deal gives one card to each player in turn
+pub fn deal_position(a_player: Player, deal: usize, index: usize) -> usize {
+    let seat_offset = match a_player {
+        Player::Player1 => 0,
+        Player::Player2 => 1,
+    };
+    4 + 8 * deal + seat_offset + 2 * index
+}
+
+/// what playing `a_card` onto a board of `board_len` cards with `top_card` on top would do
+pub fn win_status_of(top_card: Option<Card>, board_len: usize, a_card: Card) -> WinStatus {
+    match top_card {
+        Some(last_card) if last_card.rank == a_card.rank => {
+            if board_len == 1 {
+                WinStatus::Pisti
+            } else {
+                WinStatus::Win
+            }
+        }
+        Some(_) if a_card.rank == R_J => WinStatus::Win,
+        _ => WinStatus::Pass,
+    }
 }
 
-pub fn get_random_index(a_vec: &PlayCards) -> usize {
-    (rand::random::<f32>() * a_vec.len() as f32).floor() as usize
+/// a random index into `a_vec` that only depends on the seed of the game and the moves made,
+/// the AIs play a game with the same seed the same way again
+pub fn get_random_index(my_game: &Game, a_vec: &[Card]) -> usize {
+    let moves = my_game.moves.len() as u64 + 1;
+    let mut rng = StdRng::seed_from_u64(my_game.seed ^ moves.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    (rng.gen::<f32>() * a_vec.len() as f32).floor() as usize
 }
 
 
@@ -0,0 +1,219 @@
+use std::fmt;
+
+use crate::{
+    game::{DealStatus, Game, Player, Rules},
+    seat::{Seat, Seats},
+    strategy::{strategy_from_spec, Strategy},
+};
+
+/// plays a whole game between the AI seats without any window
+pub fn play_ai_game(seats: &mut Seats, seed: u64, rules: Rules) -> Game {
+    let mut my_game = Game::with_seed(seed);
+    my_game.rules = rules;
+    my_game.start_game_and_give_cards_to_players();
+    while !my_game.is_game_over() {
+        let player = my_game.turn;
+        let card_index = seats
+            .pick_card_for_ai(&my_game, player)
+            .expect("every seat must be an AI to play without window");
+        my_game.play_turn(player, card_index);
+        if my_game.next_deal() == DealStatus::GameOver {
+            seats.game_over(&my_game);
+        }
+    }
+    my_game
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MatchReport {
+    pub name_a: String,
+    pub name_b: String,
+    pub games: usize,
+    pub wins_a: usize,
+    pub wins_b: usize,
+    pub draws: usize,
+    pub points_a: usize,
+    pub points_b: usize,
+    pub pistis_a: usize,
+    pub pistis_b: usize,
+    /// score of A for every deal, averaged over both seats (win 1, draw 0.5, loss 0)
+    pub deal_scores: Vec<f64>,
+}
+
+impl MatchReport {
+    /// score of A, draws count as half a win
+    pub fn score(&self) -> f64 {
+        if self.games == 0 {
+            return 0.5;
+        }
+        (self.wins_a as f64 + self.draws as f64 / 2.0) / self.games as f64
+    }
+
+    pub fn average_point_difference(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        (self.points_a as f64 - self.points_b as f64) / self.games as f64
+    }
+
+    /// 95% confidence interval of the score, deals are the samples since both seats share the cards
+    pub fn score_interval(&self) -> (f64, f64) {
+        let n = self.deal_scores.len() as f64;
+        if n < 2.0 {
+            return (0.0, 1.0);
+        }
+        let mean = self.deal_scores.iter().sum::<f64>() / n;
+        let variance = self
+            .deal_scores
+            .iter()
+            .map(|s| (s - mean) * (s - mean))
+            .sum::<f64>()
+            / (n - 1.0);
+        let margin = 1.96 * (variance / n).sqrt();
+        ((mean - margin).max(0.0), (mean + margin).min(1.0))
+    }
+}
+
+/// elo difference that gives the expected `score`
+pub fn elo_difference(score: f64) -> f64 {
+    let s = score.clamp(0.001, 0.999);
+    -400.0 * (1.0 / s - 1.0).log10()
+}
+
+impl fmt::Display for MatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (low, high) = self.score_interval();
+        writeln!(
+            f,
+            "{} vs {}: {} games",
+            self.name_a, self.name_b, self.games
+        )?;
+        writeln!(
+            f,
+            "wins: {} - {} ({} draws), win rate of {}: {:.1}%",
+            self.wins_a,
+            self.wins_b,
+            self.draws,
+            self.name_a,
+            self.score() * 100.0
+        )?;
+        writeln!(
+            f,
+            "average point difference: {:+.2}",
+            self.average_point_difference()
+        )?;
+        let games = self.games.max(1) as f64;
+        writeln!(
+            f,
+            "pisti per game: {:.3} - {:.3}",
+            self.pistis_a as f64 / games,
+            self.pistis_b as f64 / games
+        )?;
+        writeln!(
+            f,
+            "95% interval: {:.1}% .. {:.1}%",
+            low * 100.0,
+            high * 100.0
+        )?;
+        write!(
+            f,
+            "elo difference: {:+.0} ({:+.0} .. {:+.0})",
+            elo_difference(self.score()),
+            elo_difference(low),
+            elo_difference(high)
+        )
+    }
+}
+
+fn points_and_pistis(my_game: &Game, a_player: Player) -> (usize, usize) {
+    match a_player {
+        Player::Player1 => (my_game.player1_point, my_game.player1_pisti_count as usize),
+        Player::Player2 => (my_game.player2_point, my_game.player2_pisti_count as usize),
+    }
+}
+
+pub fn run_match(
+    spec_a: &str,
+    spec_b: &str,
+    deals: usize,
+    seed: u64,
+) -> Result<MatchReport, String> {
+    let strategy_a = strategy_from_spec(spec_a)?;
+    let strategy_b = strategy_from_spec(spec_b)?;
+    Ok(run_match_between(strategy_a, strategy_b, deals, seed))
+}
+
+/// plays every deal twice so both strategies get both sides of the same cards
+pub fn run_match_between(
+    strategy_a: Box<dyn Strategy>,
+    strategy_b: Box<dyn Strategy>,
+    deals: usize,
+    seed: u64,
+) -> MatchReport {
+    let mut report = MatchReport {
+        name_a: strategy_a.name(),
+        name_b: strategy_b.name(),
+        ..MatchReport::default()
+    };
+    let mut seats = Seats::new(Seat::Ai(strategy_a), Seat::Ai(strategy_b));
+    for deal in 0..deals {
+        let deal_seed = seed.wrapping_add(deal as u64);
+        let mut deal_score = 0.0;
+        for player_a in [Player::Player1, Player::Player2] {
+            let my_game = play_ai_game(&mut seats, deal_seed, Rules::default());
+            let (points_a, pistis_a) = points_and_pistis(&my_game, player_a);
+            let (points_b, pistis_b) = points_and_pistis(&my_game, player_a.other());
+            report.games += 1;
+            report.points_a += points_a;
+            report.points_b += points_b;
+            report.pistis_a += pistis_a;
+            report.pistis_b += pistis_b;
+            if points_a > points_b {
+                report.wins_a += 1;
+                deal_score += 0.5;
+            } else if points_a < points_b {
+                report.wins_b += 1;
+            } else {
+                report.draws += 1;
+                deal_score += 0.25;
+            }
+            std::mem::swap(&mut seats.player1, &mut seats.player2);
+        }
+        report.deal_scores.push(deal_score);
+    }
+    report
+}
+
+/// `pistiflex match <strategy a> <strategy b> [--deals N] [--seed S]`
+pub fn match_main(args: &[String]) -> Result<(), String> {
+    let mut specs = Vec::new();
+    let mut deals = 1000;
+    let mut seed = rand::random::<u64>();
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--deals" => {
+                deals = args_iter
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or("--deals needs a number")?
+            }
+            "--seed" => {
+                seed = args_iter
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or("--seed needs a number")?
+            }
+            _ => specs.push(arg.clone()),
+        }
+    }
+    if specs.len() != 2 {
+        return Err(String::from(
+            "usage: pistiflex match <strategy a> <strategy b> [--deals N] [--seed S]",
+        ));
+    }
+    println!("seed: {}", seed);
+    let report = run_match(&specs[0], &specs[1], deals, seed)?;
+    println!("{}", report);
+    Ok(())
+}
@@ -0,0 +1,504 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    cards::CardSet,
+    game::{Card, Game, Player, RulePreset, ScoreBreakdown, WinStatus},
+    record::{mark_of, parse_move},
+    seat::{human_from_spec, seat_spec, Seats},
+    storage::{data_dir, parse_key_values},
+};
+
+/// one finished game of the history
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameEntry {
+    /// `YYYY.MM.DD`
+    pub date: String,
+    /// "human" or the strategy of each seat
+    pub player1: String,
+    pub player2: String,
+    pub rules: RulePreset,
+    pub seed: u64,
+    pub player1_score: ScoreBreakdown,
+    pub player2_score: ScoreBreakdown,
+    pub player1_pistis: u8,
+    pub player2_pistis: u8,
+    pub player1_won: CardSet,
+    pub player2_won: CardSet,
+    /// the card of every move and what it did, the way a game record writes them
+    pub moves: Vec<(Card, WinStatus)>,
+    /// how long the game was on the table since it was started or resumed, older entries have none
+    pub seconds: Option<u64>,
+}
+
+/// every game finished in the game window or the terminal, one block of `key = value` lines each
+pub fn history_path() -> PathBuf {
+    data_dir().join("history.txt")
+}
+
+impl GameEntry {
+    pub fn new(my_game: &Game, seats: &Seats, date: &str) -> Self {
+        GameEntry {
+            date: date.to_string(),
+            player1: seat_spec(&seats.player1),
+            player2: seat_spec(&seats.player2),
+            rules: my_game.rules.preset,
+            seed: my_game.seed,
+            player1_score: my_game.score_breakdown(Player::Player1),
+            player2_score: my_game.score_breakdown(Player::Player2),
+            player1_pistis: my_game.player1_pisti_count,
+            player2_pistis: my_game.player2_pisti_count,
+            player1_won: my_game.player1_won_cards,
+            player2_won: my_game.player2_won_cards,
+            moves: my_game.moves.iter().map(|m| (m.card, m.stat)).collect(),
+            seconds: None,
+        }
+    }
+
+    /// the seat of the only human at the table, None for hotseat and AI against AI
+    pub fn human(&self) -> Option<Player> {
+        let is_human = |spec: &str| human_from_spec(spec).is_some();
+        match (is_human(&self.player1), is_human(&self.player2)) {
+            (true, false) => Some(Player::Player1),
+            (false, true) => Some(Player::Player2),
+            _ => None,
+        }
+    }
+
+    /// the profile of the only human, None without a profile or without one human
+    pub fn profile(&self) -> Option<&str> {
+        let spec = match self.human()? {
+            Player::Player1 => &self.player1,
+            Player::Player2 => &self.player2,
+        };
+        spec.strip_prefix("human:")
+    }
+
+    pub fn score(&self, a_player: Player) -> ScoreBreakdown {
+        match a_player {
+            Player::Player1 => self.player1_score,
+            Player::Player2 => self.player2_score,
+        }
+    }
+
+    pub fn pistis(&self, a_player: Player) -> u8 {
+        match a_player {
+            Player::Player1 => self.player1_pistis,
+            Player::Player2 => self.player2_pistis,
+        }
+    }
+
+    pub fn won_cards(&self, a_player: Player) -> CardSet {
+        match a_player {
+            Player::Player1 => self.player1_won,
+            Player::Player2 => self.player2_won,
+        }
+    }
+}
+
+fn cards_text(cards: CardSet) -> String {
+    cards
+        .iter()
+        .map(|c| c.notation())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn score_text(score: &ScoreBreakdown) -> String {
+    format!("{} {} {}", score.pistis, score.majority, score.cards)
+}
+
+fn parse_score(value: &str) -> Result<ScoreBreakdown, String> {
+    let parts: Vec<usize> = value
+        .split_whitespace()
+        .map(|p| p.parse::<usize>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("invalid score {}", value))?;
+    match parts[..] {
+        [pistis, majority, cards] => Ok(ScoreBreakdown {
+            pistis,
+            majority,
+            cards,
+        }),
+        _ => Err(format!("a score is pistis majority cards, not {}", value)),
+    }
+}
+
+pub fn entry_to_text(entry: &GameEntry) -> String {
+    let moves: Vec<String> = entry
+        .moves
+        .iter()
+        .map(|(a_card, stat)| format!("{}{}", a_card.notation(), mark_of(*stat)))
+        .collect();
+    let mut text = format!(
+        "date = {}\nplayer1 = {}\nplayer2 = {}\nrules = {}\nseed = {}\n\
+         # pistis majority cards\nplayer1_score = {}\nplayer2_score = {}\n\
+         player1_pistis = {}\nplayer2_pistis = {}\n\
+         player1_won = {}\nplayer2_won = {}\nmoves = {}\n",
+        entry.date,
+        entry.player1,
+        entry.player2,
+        entry.rules,
+        entry.seed,
+        score_text(&entry.player1_score),
+        score_text(&entry.player2_score),
+        entry.player1_pistis,
+        entry.player2_pistis,
+        cards_text(entry.player1_won),
+        cards_text(entry.player2_won),
+        moves.join(" ")
+    );
+    if let Some(seconds) = entry.seconds {
+        text.push_str(&format!("seconds = {}\n", seconds));
+    }
+    text
+}
+
+fn parse_cards(value: &str) -> Result<CardSet, String> {
+    value
+        .split_whitespace()
+        .map(|c| c.parse::<Card>())
+        .collect()
+}
+
+fn parse_entry(text: &str) -> Result<GameEntry, String> {
+    let mut entry = GameEntry {
+        date: String::new(),
+        player1: String::new(),
+        player2: String::new(),
+        rules: RulePreset::Standard,
+        seed: 0,
+        player1_score: ScoreBreakdown::default(),
+        player2_score: ScoreBreakdown::default(),
+        player1_pistis: 0,
+        player2_pistis: 0,
+        player1_won: CardSet::new(),
+        player2_won: CardSet::new(),
+        moves: Vec::new(),
+        seconds: None,
+    };
+    let pisti_count = |value: &str| {
+        value
+            .parse::<u8>()
+            .map_err(|_| format!("invalid pisti count {}", value))
+    };
+    for (_, key, value) in parse_key_values(text)? {
+        match key.as_str() {
+            "date" => entry.date = value,
+            "player1" => entry.player1 = value,
+            "player2" => entry.player2 = value,
+            "rules" => {
+                entry.rules = value
+                    .parse::<RulePreset>()
+                    .map_err(|_| format!("unknown rules {}", value))?
+            }
+            "seed" => {
+                entry.seed = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid seed {}", value))?
+            }
+            "player1_score" => entry.player1_score = parse_score(&value)?,
+            "player2_score" => entry.player2_score = parse_score(&value)?,
+            "player1_pistis" => entry.player1_pistis = pisti_count(&value)?,
+            "player2_pistis" => entry.player2_pistis = pisti_count(&value)?,
+            "player1_won" => entry.player1_won = parse_cards(&value)?,
+            "player2_won" => entry.player2_won = parse_cards(&value)?,
+            "seconds" => {
+                entry.seconds = Some(
+                    value
+                        .parse::<u64>()
+                        .map_err(|_| format!("invalid seconds {}", value))?,
+                )
+            }
+            "moves" => {
+                entry.moves = value
+                    .split_whitespace()
+                    .map(|m| parse_move(m).map(|(c, mark)| (c, mark.unwrap_or(WinStatus::Pass))))
+                    .collect::<Result<_, _>>()?
+            }
+            _ => {}
+        }
+    }
+    if entry.date.is_empty() || entry.player1.is_empty() || entry.player2.is_empty() {
+        return Err(String::from("the date or a player is missing"));
+    }
+    Ok(entry)
+}
+
+/// the entries are separated by empty lines, a broken entry is skipped with a message
+pub fn parse_history(text: &str) -> Vec<GameEntry> {
+    let mut entries = Vec::new();
+    for (i, block) in text.split("\n\n").enumerate() {
+        if block.trim().is_empty() {
+            continue;
+        }
+        match parse_entry(block) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => println!("game {} of the history is skipped: {}", i + 1, e),
+        }
+    }
+    entries
+}
+
+/// no file is an empty history
+pub fn load_history(path: &Path) -> Result<Vec<GameEntry>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    Ok(parse_history(&text))
+}
+
+/// adds the game to the end of the file, the games before it are never rewritten
+pub fn append_game(path: &Path, entry: &GameEntry) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+    }
+    let cannot_write = |e: std::io::Error| format!("cannot write {}: {}", path.display(), e);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(cannot_write)?;
+    file.write_all(format!("\n{}", entry_to_text(entry)).as_bytes())
+        .map_err(cannot_write)
+}
+
+/// stores a game finished at the table, puzzles are not stored
+pub fn remember_game(entry: &GameEntry) {
+    if let Err(e) = append_game(&history_path(), entry) {
+        println!("{}", e);
+    }
+}
+
+/// the games of the human against the AI added up, from the side of the human
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryStats {
+    pub games: usize,
+    pub wins: usize,
+    pub draws: usize,
+    pub points: usize,
+    pub opponent_points: usize,
+    pub pistis: usize,
+    /// in how many games each card was won, indexed like the bits of a `CardSet`
+    pub captures: Vec<usize>,
+}
+
+impl HistoryStats {
+    pub fn add_game(&mut self, entry: &GameEntry) {
+        let Some(me) = entry.human() else {
+            return;
+        };
+        let (mine, theirs) = (entry.score(me).total(), entry.score(me.other()).total());
+        self.games += 1;
+        if mine > theirs {
+            self.wins += 1;
+        } else if mine == theirs {
+            self.draws += 1;
+        }
+        self.points += mine;
+        self.opponent_points += theirs;
+        self.pistis += entry.pistis(me) as usize;
+        self.captures.resize(52, 0);
+        for a_card in entry.won_cards(me).iter() {
+            self.captures[card_index(a_card)] += 1;
+        }
+    }
+
+    fn per_game(&self, count: usize) -> f64 {
+        count as f64 / self.games.max(1) as f64
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        self.per_game(self.wins)
+    }
+
+    pub fn average_points(&self) -> f64 {
+        self.per_game(self.points)
+    }
+
+    pub fn average_opponent_points(&self) -> f64 {
+        self.per_game(self.opponent_points)
+    }
+
+    pub fn pistis_per_game(&self) -> f64 {
+        self.per_game(self.pistis)
+    }
+
+    /// the share of the games in which `a_card` was won
+    pub fn capture_rate(&self, a_card: Card) -> f64 {
+        self.per_game(self.captures.get(card_index(a_card)).copied().unwrap_or(0))
+    }
+}
+
+fn card_index(a_card: Card) -> usize {
+    a_card.suit as usize * 13 + a_card.rank as usize - 1
+}
+
+pub fn history_stats<'a>(entries: impl IntoIterator<Item = &'a GameEntry>) -> HistoryStats {
+    let mut stats = HistoryStats::default();
+    for entry in entries {
+        stats.add_game(entry);
+    }
+    stats
+}
+
+/// the stats of every month, `YYYY.MM` in the order of the history
+pub fn monthly_stats(entries: &[GameEntry]) -> Vec<(String, HistoryStats)> {
+    let mut months: Vec<(String, HistoryStats)> = Vec::new();
+    for entry in entries {
+        let month = entry.date.get(..7).unwrap_or(&entry.date).to_string();
+        match months.iter_mut().find(|(m, _)| *m == month) {
+            Some((_, stats)) => stats.add_game(entry),
+            None => months.push((month, history_stats([entry]))),
+        }
+    }
+    months.retain(|(_, stats)| stats.games > 0);
+    months
+}
+
+/// which games the stats are about, dates are `YYYY.MM.DD` or a part of it like `YYYY.MM`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryFilter {
+    /// only the games of this profile, every game when it is None
+    pub profile: Option<String>,
+    pub rules: Option<RulePreset>,
+    /// the first date, included
+    pub from: Option<String>,
+    /// the last date, included
+    pub to: Option<String>,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, entry: &GameEntry) -> bool {
+        let date = entry.date.as_str();
+        self.profile
+            .as_deref()
+            .is_none_or(|profile| entry.profile() == Some(profile))
+            && self.rules.is_none_or(|rules| rules == entry.rules)
+            && self.from.as_ref().is_none_or(|from| date >= from.as_str())
+            && self
+                .to
+                .as_ref()
+                .is_none_or(|to| date.get(..to.len()).unwrap_or(date) <= to.as_str())
+    }
+}
+
+/// how many games the human ended with 0 to `bin` - 1 points, `bin` to 2 * `bin` - 1 and so on
+pub fn score_histogram(entries: &[&GameEntry], bin: usize) -> Vec<usize> {
+    let mut counts = Vec::new();
+    for entry in entries {
+        if let Some(me) = entry.human() {
+            let index = entry.score(me).total() / bin.max(1);
+            if counts.len() <= index {
+                counts.resize(index + 1, 0);
+            }
+            counts[index] += 1;
+        }
+    }
+    counts
+}
+
+/// the win rate of the last `window` games after every game of the human
+pub fn win_rate_trend(entries: &[&GameEntry], window: usize) -> Vec<f64> {
+    let results: Vec<bool> = entries
+        .iter()
+        .filter_map(|entry| {
+            let me = entry.human()?;
+            Some(entry.score(me).total() > entry.score(me.other()).total())
+        })
+        .collect();
+    (0..results.len())
+        .map(|i| {
+            let last = &results[(i + 1).saturating_sub(window)..=i];
+            last.iter().filter(|won| **won).count() as f64 / last.len() as f64
+        })
+        .collect()
+}
+
+/// the stats against every AI the human played, in the order they were first played
+pub fn opponent_stats(entries: &[&GameEntry]) -> Vec<(String, HistoryStats)> {
+    let mut opponents: Vec<(String, HistoryStats)> = Vec::new();
+    for entry in entries {
+        let Some(me) = entry.human() else {
+            continue;
+        };
+        let opponent = match me {
+            Player::Player1 => &entry.player2,
+            Player::Player2 => &entry.player1,
+        };
+        match opponents.iter_mut().find(|(o, _)| o == opponent) {
+            Some((_, stats)) => stats.add_game(entry),
+            None => opponents.push((opponent.clone(), history_stats([*entry]))),
+        }
+    }
+    opponents
+}
+
+const USAGE: &str = "usage: pistiflex stats [--file <history>] [--profile <name>]";
+
+/// `pistiflex stats --profile Ann`, the games of the human against the AI
+pub fn stats_main(args: &[String]) -> Result<(), String> {
+    let mut path = history_path();
+    let mut filter = HistoryFilter::default();
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        let value = args_iter.next().ok_or(USAGE)?;
+        match arg.as_str() {
+            "--file" => path = PathBuf::from(value),
+            "--profile" => filter.profile = Some(value.clone()),
+            _ => return Err(String::from(USAGE)),
+        }
+    }
+    let mut entries = load_history(&path)?;
+    entries.retain(|e| filter.matches(e));
+    let stats = history_stats(&entries);
+    if stats.games == 0 {
+        println!("{} has no finished game against the AI yet", path.display());
+        return Ok(());
+    }
+    println!(
+        "games: {}, won {} ({:.0}%), draws {}",
+        stats.games,
+        stats.wins,
+        stats.win_rate() * 100.0,
+        stats.draws
+    );
+    println!(
+        "average points: {:.1}, the AI {:.1}",
+        stats.average_points(),
+        stats.average_opponent_points()
+    );
+    println!("pistis per game: {:.2}", stats.pistis_per_game());
+    println!("\nmonth     games  win rate  points  pistis");
+    for (month, month_stats) in monthly_stats(&entries) {
+        println!(
+            "{:<9} {:>5}  {:>7.0}%  {:>6.1}  {:>6.2}",
+            month,
+            month_stats.games,
+            month_stats.win_rate() * 100.0,
+            month_stats.average_points(),
+            month_stats.pistis_per_game()
+        );
+    }
+    println!("\ngames in which the card was won");
+    // all 52 cards
+    let all_cards = CardSet((1 << 52) - 1);
+    let mut line = Vec::new();
+    for a_card in all_cards.iter() {
+        line.push(format!(
+            "{:>3} {:>3.0}%",
+            a_card.notation(),
+            stats.capture_rate(a_card) * 100.0
+        ));
+        if line.len() == 13 {
+            println!("{}", line.join("  "));
+            line.clear();
+        }
+    }
+    Ok(())
+}
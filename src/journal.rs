@@ -0,0 +1,51 @@
+use std::{fs, path::PathBuf};
+
+use crate::{
+    game::Game,
+    save::{game_to_text, load_game, SavedGame},
+    seat::Seats,
+    storage::{data_dir, replace_file},
+};
+
+/// the game as of its last move, it is removed when the window is closed
+pub fn journal_path() -> PathBuf {
+    data_dir().join("journal.txt")
+}
+
+/// keeps the journal up to date after a move, a finished game has nothing to resume
+pub fn write_journal(my_game: &Game, seats: &Seats) {
+    if my_game.is_game_over() {
+        clear_journal();
+    } else if let Err(e) = replace_file(&journal_path(), &game_to_text(my_game, seats)) {
+        println!("{}", e);
+    }
+}
+
+pub fn clear_journal() {
+    let path = journal_path();
+    if path.exists() {
+        if let Err(e) = fs::remove_file(&path) {
+            println!("cannot remove {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// the game of a run that ended without closing its window, a journal that cannot be read is removed
+pub fn interrupted_game() -> Option<SavedGame> {
+    let path = journal_path();
+    if !path.exists() {
+        return None;
+    }
+    match load_game(&path) {
+        Ok(saved) if !saved.game.is_game_over() => Some(saved),
+        Ok(_) => {
+            clear_journal();
+            None
+        }
+        Err(e) => {
+            println!("the interrupted game cannot be resumed: {}", e);
+            clear_journal();
+            None
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use std::{
+    io::BufReader,
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    time::Instant,
+};
+
+use fltk::{app, dialog, prelude::*, window::DoubleWindow};
+
+use pistiflex::{
+    game::{Game, Player},
+    history::{remember_game, GameEntry},
+    net::{
+        accept_guest, new_session, seat_guest, send_message, spawn_reader, spawn_resumer,
+        GuestGame, NetMessage, RemoteSeat, Wire,
+    },
+    record::today,
+    seat::{game_over_text, Seats},
+    server::DEFAULT_GRACE,
+    settings::{save_settings, Settings},
+};
+
+use crate::{
+    ui::{FltkMessage, TableWindow, ThreadMessage},
+    widget::draw_game,
+};
+
+/// plays the game of the host as the guest until the window is closed, the moves come as
+/// `FltkMessage::Net` from the reader of the connection, returns the window, when the
+/// connection to a server drops `reconnect` takes the seat back with the session token
+pub fn run_guest(
+    mut guest: GuestGame,
+    mut stream: TcpStream,
+    table_window: TableWindow,
+    seats: &Seats,
+    settings: &mut Settings,
+    receiver: app::Receiver<FltkMessage>,
+    mut reconnect: impl FnMut(&str) -> Result<(GuestGame, TcpStream, TableWindow), String>,
+) -> DoubleWindow {
+    let TableWindow {
+        mut win,
+        mut table,
+        animations: mut t_s,
+    } = table_window;
+    let started = Instant::now();
+    let mut connected = true;
+    // a card was sent and the host did not answer yet
+    let mut waiting = false;
+    while app::wait() {
+        let Some(msg) = receiver.recv() else {
+            continue;
+        };
+        match msg {
+            FltkMessage::EM(msg) => {
+                if !connected
+                    || waiting
+                    || guest.game.is_game_over()
+                    || guest.game.turn != guest.seat
+                    || msg.the_player != guest.seat
+                {
+                    continue;
+                }
+                let a_card = table.card_on_slot(guest.seat, msg.card_index);
+                match send_message(&mut stream, &NetMessage::Play(a_card)) {
+                    Ok(()) => waiting = true,
+                    Err(e) => println!("{}", e),
+                }
+            }
+            FltkMessage::Net(Ok(NetMessage::Illegal(a_card))) => {
+                println!("the host did not take {}", a_card.notation());
+                waiting = false;
+            }
+            FltkMessage::Net(Err(e)) if connected && !guest.game.is_game_over() => {
+                let Some(token) = guest.session.clone() else {
+                    dialog::message_default(&format!("{} left the game", guest.host_name));
+                    connected = false;
+                    continue;
+                };
+                let txt = format!("The connection to {} dropped: {}", guest.host_name, e);
+                connected = false;
+                if dialog::choice2_default(&txt, "Leave", "Reconnect", "") != Some(1) {
+                    continue;
+                }
+                match reconnect(&token) {
+                    Ok((resumed, resumed_stream, table_window)) => {
+                        win.hide();
+                        (guest, stream) = (resumed, resumed_stream);
+                        (win, table, t_s) = (
+                            table_window.win,
+                            table_window.table,
+                            table_window.animations,
+                        );
+                        connected = true;
+                        waiting = false;
+                    }
+                    Err(e) => dialog::alert_default(&format!("Cannot reconnect: {}", e)),
+                }
+            }
+            FltkMessage::Net(Ok(NetMessage::Bye)) | FltkMessage::Net(Err(_)) => {
+                if connected && !guest.game.is_game_over() {
+                    dialog::message_default(&format!("{} left the game", guest.host_name));
+                }
+                connected = false;
+            }
+            FltkMessage::Net(Ok(NetMessage::Ping)) => {
+                if let Err(e) = send_message(&mut stream, &NetMessage::Pong) {
+                    println!("{}", e);
+                }
+            }
+            // the guest game shows the same
+            FltkMessage::Net(Ok(NetMessage::State(_))) => {}
+            FltkMessage::Net(Ok(message)) => {
+                let animations = match guest.apply(&message) {
+                    Ok(Some(a_move)) => {
+                        if a_move.player == guest.seat {
+                            waiting = false;
+                        } else {
+                            // the top row shows the card where the guest put it
+                            table.top_cards_values = a_move.dealt_hand;
+                        }
+                        table.move_animations(
+                            &guest.game,
+                            a_move.player,
+                            a_move.card,
+                            a_move.stat,
+                            a_move.deal,
+                        )
+                    }
+                    // the host sent the result and the cards agree with it
+                    Ok(None) if guest.game.is_game_over() => {
+                        let mut entry = GameEntry::new(&guest.game, seats, &today());
+                        entry.seconds = Some(started.elapsed().as_secs());
+                        remember_game(&entry);
+                        vec![ThreadMessage::GameOver(game_over_text(&guest.game, seats))]
+                    }
+                    Ok(None) => continue,
+                    Err(e) => {
+                        connected = false;
+                        dialog::alert_default(&format!(
+                            "Cannot follow the game of the host: {}",
+                            e
+                        ));
+                        continue;
+                    }
+                };
+                draw_game(animations, t_s.clone());
+            }
+            FltkMessage::Speed(anim_speed) => {
+                settings.anim_speed = anim_speed;
+                save_settings(settings);
+            }
+            _ => {}
+        }
+    }
+    if connected {
+        let _ = send_message(&mut stream, &NetMessage::Bye);
+    }
+    win
+}
+
+/// the guest of a LAN game as the host keeps it. When its connection drops before the game
+/// is over its seat waits `DEFAULT_GRACE` for it to come back with the session token
+pub struct LanGuest {
+    pub seat: Player,
+    pub name: String,
+    /// None while the seat is paused and after the guest left
+    pub link: Option<RemoteSeat>,
+    /// the guest may come back until then
+    pub paused: Option<Instant>,
+    host_name: String,
+    session: String,
+    /// counts the connections of the guest, the messages of an old one are left out
+    connection: usize,
+    resumed: mpsc::Receiver<(TcpStream, BufReader<TcpStream>)>,
+    sender: app::Sender<FltkMessage>,
+}
+
+impl LanGuest {
+    /// waits on `port` until a guest joins `seat`, the port stays open for it to come back
+    pub fn accept(
+        port: u16,
+        game: &Game,
+        seat: Player,
+        host_name: &str,
+        sender: app::Sender<FltkMessage>,
+    ) -> Result<LanGuest, String> {
+        let listener = TcpListener::bind(("0.0.0.0", port))
+            .map_err(|e| format!("cannot listen on port {}: {}", port, e))?;
+        println!("waiting for a player on port {}", port);
+        let session = new_session();
+        let (link, reader) = accept_guest(&listener, game, seat, host_name, &session)?;
+        let (resume, resumed) = mpsc::channel();
+        spawn_resumer(listener, session.clone(), move |stream, reader| {
+            let taken = resume.send((stream, reader)).is_ok();
+            sender.send(FltkMessage::Resumed);
+            taken
+        });
+        let mut guest = LanGuest {
+            seat,
+            name: link.name.clone(),
+            link: Some(link),
+            paused: None,
+            host_name: host_name.to_string(),
+            session,
+            connection: 0,
+            resumed,
+            sender,
+        };
+        guest.read(reader);
+        Ok(guest)
+    }
+
+    fn read(&mut self, reader: BufReader<TcpStream>) {
+        self.connection += 1;
+        let (connection, s) = (self.connection, self.sender);
+        spawn_reader(reader, move |message| {
+            s.send(FltkMessage::Guest(connection, message))
+        });
+    }
+
+    /// the message came from the connection the guest plays on now
+    pub fn is_current(&self, connection: usize) -> bool {
+        connection == self.connection && self.link.is_some()
+    }
+
+    /// the connection dropped, `FltkMessage::GraceOver` comes when the guest may no longer
+    /// come back
+    pub fn pause(&mut self) {
+        self.leave();
+        println!(
+            "{} dropped, the seat waits {}s",
+            self.name,
+            DEFAULT_GRACE.as_secs()
+        );
+        self.paused = Some(Instant::now() + DEFAULT_GRACE);
+        let s = self.sender;
+        app::add_timeout3(DEFAULT_GRACE.as_secs_f64(), move |_| {
+            s.send(FltkMessage::GraceOver)
+        });
+    }
+
+    /// the guest left or the grace period is over, a token that comes later is refused
+    pub fn leave(&mut self) {
+        if let Some(mut link) = self.link.take() {
+            // the connection is gone, not even bye can be sent
+            link.connected = false;
+        }
+        self.paused = None;
+    }
+
+    /// nobody came back in time
+    pub fn expired(&self) -> bool {
+        self.paused
+            .is_some_and(|deadline| deadline <= Instant::now())
+    }
+
+    /// seats the guest that came back with its token again and sends it the whole game,
+    /// a connection that takes the place of one the host still has makes it old
+    pub fn resume(&mut self, game: &Game) -> bool {
+        let Ok((mut stream, reader)) = self.resumed.try_recv() else {
+            return false;
+        };
+        if self.link.is_none() && self.paused.is_none() {
+            let refused = NetMessage::Refused(String::from("the session is over"));
+            let _ = send_message(&mut stream, &refused);
+            return false;
+        }
+        let session = Some(self.session.as_str());
+        match seat_guest(
+            stream,
+            Wire::Lines,
+            &self.name,
+            game,
+            self.seat,
+            &self.host_name,
+            session,
+        ) {
+            Ok(link) => {
+                self.link = Some(link);
+                self.paused = None;
+                self.read(reader);
+                true
+            }
+            Err(e) => {
+                println!("{}", e);
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! the rules, the AI and everything else that runs without a window,
+//! the table of `pistiflex` and `pistiflex-server` are built on it
+pub mod adaptive;
+pub mod analyze;
+pub mod bench;
+pub mod calc;
+pub mod cards;
+pub mod engine;
+pub mod features;
+pub mod game;
+pub mod harness;
+pub mod history;
+pub mod journal;
+pub mod json;
+pub mod net;
+pub mod options;
+pub mod profiles;
+pub mod puzzle;
+pub mod record;
+pub mod results;
+pub mod save;
+pub mod seat;
+pub mod server;
+pub mod settings;
+pub mod simulate;
+pub mod storage;
+pub mod strategy;
+pub mod tui;
+pub mod tune;
+pub mod weighted;
+pub mod websocket;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,1167 @@
+//! Line based protocol of a LAN game between the host, who deals and checks every move, and a
+//! guest at another computer, who is only sent what its seat may see.
+//!
+//! Every message is one line of space separated words, cards and seats are written as in the
+//! engine protocol of `src/engine.rs`. The guest says its protocol version first and the host
+//! refuses a version it does not speak.
+//!
+//! ```text
+//! guest -> host          host -> guest
+//! hello <version> <name>
+//!                        welcome <version> <your seat> <rules preset> <name>
+//!                        refused <reason>       (and the connection is closed)
+//!                        session <token>        (after welcome, see resume below)
+//!                        board xx xx xx 7H
+//!                        deal 4C KH 9S 2D       (your first hand)
+//!                        played <seat> <card> <pass|win|pisti> [deal <your next hand>]
+//!                        ready                  (the moves before it were made before you joined)
+//! play <card>
+//!                        illegal <card>         (not your turn or not your card)
+//!                        gameover <points 1> <points 2> <seed> <the 3 face down cards>
+//!                        state <turn> <points 1> <points 2> <deck> board .. hand .. legal ..
+//! bye                    bye
+//! ```
+//!
+//! `state` follows ready and every batch of moves, it is all a client that does not keep a
+//! game of its own needs: the seat to move, the points so far, the cards left in the deck,
+//! the board with `xx` for the face down cards, the hand and the cards it may play now.
+//!
+//! `pistiflex-server` of `src/server.rs` speaks the same protocol at many tables. It answers
+//! hello with the lobby, where a client lists, creates and joins tables, and joining a table
+//! goes on with welcome as above. A seat of `create` is `-` for a player or the name of an
+//! AI strategy, the seats of a table are listed as `-` when open, `ai:<strategy>` or the
+//! name of the player with `_` for spaces.
+//!
+//! ```text
+//! client -> server       server -> client
+//! hello <version> <name>
+//!                        lobby <server name>
+//! list
+//!                        tables <count>
+//!                        table <id> <rules preset> <seat 1> <seat 2> <status>
+//! create <rules preset> <seat 1> <seat 2>
+//!                        created <id>
+//! join <id> <seat>
+//!                        welcome ...            (or error)
+//!                        session <token>        (after welcome, the rest follows as above)
+//!                        error <reason>         (the client stays in the lobby)
+//! resume <token>
+//!                        welcome ...            (or error)
+//!                        ping                   (every few seconds while seated)
+//! pong
+//! ```
+//!
+//! When the connection of a player drops before the game is over its seat is paused, a
+//! player that answers no ping for a while has dropped too. The player may connect again,
+//! say hello and `resume` with the token of the seat, and is sent the whole game again from
+//! welcome on. After the grace period of the server its AI takes the seat and the token is
+//! no longer taken. A LAN host pauses the seat of its guest the same way, the guest sends
+//! `resume` right after hello because the host does not answer with a lobby.
+//!
+//! The same messages go as JSON over a WebSocket, see `src/websocket.rs`.
+//!
+//! The guest keeps a `Game` of its own in which the cards it has not seen are put anywhere
+//! they could be. A card it is shown is swapped to its place before the moves are played
+//! again. At the end the face down cards are shown too, so the points of the guest are
+//! counted from the same cards as the points of the host.
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    thread,
+    time::Duration,
+};
+
+use strum_macros::{Display, EnumString};
+
+use crate::{
+    engine::{cards_to_str, seat_number},
+    features::SeatView,
+    game::{deal_position, Card, DealStatus, Game, Player, RulePreset, WinStatus},
+    json::{json_to_string, parse_json},
+    profiles::check_name,
+    save::replay_moves,
+    strategy::StrategyKind,
+    websocket::{json_to_message, message_to_json, read_text, write_pong, write_text},
+};
+
+/// 2 added `state`, 3 `session` and `resume`
+pub const PROTOCOL_VERSION: u32 = 3;
+pub const DEFAULT_PORT: u16 = 7878;
+const HANDSHAKE_TIMEOUT_MS: u64 = 10000;
+/// the longest line a peer may send, a longer one is refused before it fills the memory
+pub const MAX_LINE: u64 = 64 * 1024;
+const CARDS_PER_DEAL: usize = 8;
+
+/// how the messages are written on a connection
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Wire {
+    /// a line of words for every message
+    Lines,
+    /// a JSON text frame for every message, written by the server
+    WebSocket,
+    /// the same written by a client, whose frames are masked
+    WebSocketClient,
+}
+
+/// what a seat sees of the game, sent as `state`
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableState {
+    pub turn: Player,
+    pub points: (usize, usize),
+    pub deck: usize,
+    /// None for the cards put face down at the start
+    pub board: Vec<Option<Card>>,
+    pub hand: Vec<Card>,
+    /// the cards that may be played now, none when it is not the turn of the seat
+    pub legal: Vec<Card>,
+}
+
+impl TableState {
+    pub fn new(game: &Game, seat: Player) -> Self {
+        let view = SeatView::new(game, seat);
+        let points = if game.is_game_over() {
+            (game.player1_point, game.player2_point)
+        } else {
+            (
+                game.score_breakdown(Player::Player1).total(),
+                game.score_breakdown(Player::Player2).total(),
+            )
+        };
+        let my_turn = game.turn == seat && !game.is_game_over();
+        TableState {
+            turn: game.turn,
+            points,
+            deck: view.deck_len,
+            board: view.board,
+            legal: if my_turn {
+                view.hand.clone()
+            } else {
+                Vec::new()
+            },
+            hand: view.hand,
+        }
+    }
+}
+
+#[derive(Debug, EnumString, Display, Copy, Clone, PartialEq)]
+#[strum(serialize_all = "lowercase")]
+pub enum TableStatus {
+    /// a seat is open
+    Waiting,
+    Playing,
+    /// the connection of a player dropped, the game waits for it
+    Paused,
+    Over,
+}
+
+/// a table of the server as it is listed in the lobby
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableInfo {
+    pub id: usize,
+    pub rules: RulePreset,
+    /// `-`, `ai:<strategy>` or the name of the player
+    pub seats: (String, String),
+    pub status: TableStatus,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum NetMessage {
+    Hello {
+        version: u32,
+        name: String,
+    },
+    Welcome {
+        version: u32,
+        seat: Player,
+        rules: RulePreset,
+        name: String,
+    },
+    Refused(String),
+    /// the cards put onto the board at the start, None for the face down ones
+    Board(Vec<Option<Card>>),
+    Deal(Vec<Card>),
+    /// a move, with the next hand of the guest when the move ended a deal
+    Played {
+        player: Player,
+        card: Card,
+        stat: WinStatus,
+        deal: Option<Vec<Card>>,
+    },
+    Ready,
+    Play(Card),
+    Illegal(Card),
+    GameOver {
+        points: (usize, usize),
+        seed: u64,
+        face_down: Vec<Card>,
+    },
+    Bye,
+    /// the host asks if the player is still there, it answers with pong
+    Ping,
+    Pong,
+    State(TableState),
+    /// the token that lets the player back to its seat
+    Session(String),
+    Resume(String),
+    /// the server answers hello with its name
+    Lobby(String),
+    List,
+    /// how many `Table` lines follow
+    Tables(usize),
+    Table(TableInfo),
+    /// None seats are open for players
+    Create {
+        rules: RulePreset,
+        seats: (Option<StrategyKind>, Option<StrategyKind>),
+    },
+    Created(usize),
+    Join {
+        id: usize,
+        seat: Player,
+    },
+    Error(String),
+}
+
+fn board_to_str(board: &[Option<Card>]) -> String {
+    board
+        .iter()
+        .map(|c| c.map_or(String::from("xx"), |c| c.notation()))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn parse_board(words: &[&str]) -> Result<Vec<Option<Card>>, String> {
+    words
+        .iter()
+        .map(|w| match *w {
+            "xx" => Ok(None),
+            _ => w.parse::<Card>().map(Some),
+        })
+        .collect()
+}
+
+fn create_seat_word(seat: Option<StrategyKind>) -> String {
+    seat.map_or(String::from("-"), |kind| kind.to_string())
+}
+
+pub fn message_to_line(message: &NetMessage) -> String {
+    match message {
+        NetMessage::Hello { version, name } => format!("hello {} {}", version, name),
+        NetMessage::Welcome {
+            version,
+            seat,
+            rules,
+            name,
+        } => format!(
+            "welcome {} {} {} {}",
+            version,
+            seat_number(*seat),
+            rules,
+            name
+        ),
+        NetMessage::Refused(reason) => format!("refused {}", reason),
+        NetMessage::Board(board) => format!("board {}", board_to_str(board)),
+        NetMessage::Deal(hand) => format!("deal {}", cards_to_str(hand)),
+        NetMessage::Played {
+            player,
+            card,
+            stat,
+            deal,
+        } => {
+            let mut line = format!(
+                "played {} {} {}",
+                seat_number(*player),
+                card.notation(),
+                stat
+            );
+            if let Some(hand) = deal {
+                line.push_str(&format!(" deal {}", cards_to_str(hand)));
+            }
+            line
+        }
+        NetMessage::Ready => String::from("ready"),
+        NetMessage::Play(card) => format!("play {}", card.notation()),
+        NetMessage::Illegal(card) => format!("illegal {}", card.notation()),
+        NetMessage::GameOver {
+            points,
+            seed,
+            face_down,
+        } => format!(
+            "gameover {} {} {} {}",
+            points.0,
+            points.1,
+            seed,
+            cards_to_str(face_down)
+        ),
+        NetMessage::Bye => String::from("bye"),
+        NetMessage::Ping => String::from("ping"),
+        NetMessage::Pong => String::from("pong"),
+        NetMessage::State(state) => {
+            let mut words = vec![
+                String::from("state"),
+                seat_number(state.turn).to_string(),
+                state.points.0.to_string(),
+                state.points.1.to_string(),
+                state.deck.to_string(),
+                String::from("board"),
+            ];
+            words.extend(
+                state
+                    .board
+                    .iter()
+                    .map(|c| c.map_or(String::from("xx"), |c| c.notation())),
+            );
+            words.push(String::from("hand"));
+            words.extend(state.hand.iter().map(|c| c.notation()));
+            words.push(String::from("legal"));
+            words.extend(state.legal.iter().map(|c| c.notation()));
+            words.join(" ")
+        }
+        NetMessage::Session(token) => format!("session {}", token),
+        NetMessage::Resume(token) => format!("resume {}", token),
+        NetMessage::Lobby(name) => format!("lobby {}", name),
+        NetMessage::List => String::from("list"),
+        NetMessage::Tables(count) => format!("tables {}", count),
+        NetMessage::Table(info) => format!(
+            "table {} {} {} {} {}",
+            info.id, info.rules, info.seats.0, info.seats.1, info.status
+        ),
+        NetMessage::Create { rules, seats } => format!(
+            "create {} {} {}",
+            rules,
+            create_seat_word(seats.0),
+            create_seat_word(seats.1)
+        ),
+        NetMessage::Created(id) => format!("created {}", id),
+        NetMessage::Join { id, seat } => format!("join {} {}", id, seat_number(*seat)),
+        NetMessage::Error(reason) => format!("error {}", reason),
+    }
+}
+
+fn parse_seat(word: &str) -> Result<Player, String> {
+    match word {
+        "1" => Ok(Player::Player1),
+        "2" => Ok(Player::Player2),
+        _ => Err(format!("invalid seat: {}", word)),
+    }
+}
+
+fn parse_rules(word: &str) -> Result<RulePreset, String> {
+    word.parse::<RulePreset>()
+        .map_err(|_| format!("unknown rules: {}", word))
+}
+
+/// only the strategies built in, a client may not start programs or read files of the server
+fn parse_create_seat(word: &str) -> Result<Option<StrategyKind>, String> {
+    match word {
+        "-" => Ok(None),
+        _ => word
+            .parse::<StrategyKind>()
+            .map(Some)
+            .map_err(|_| format!("unknown strategy: {}", word)),
+    }
+}
+
+/// exactly `count` cards
+fn parse_cards(words: &[&str], count: usize) -> Result<Vec<Card>, String> {
+    if words.len() != count {
+        return Err(format!("expected {} cards: {}", count, words.join(" ")));
+    }
+    words.iter().map(|w| w.parse::<Card>()).collect()
+}
+
+pub fn parse_message(line: &str) -> Result<NetMessage, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let word = |i: usize| {
+        words
+            .get(i)
+            .copied()
+            .ok_or(format!("too short: {}", line.trim()))
+    };
+    let number = |i: usize| {
+        word(i)?
+            .parse::<u64>()
+            .map_err(|_| format!("expected a number: {}", line.trim()))
+    };
+    // a name is the rest of the line
+    let rest = |i: usize| words.get(i..).unwrap_or(&[]).join(" ");
+    let message = match word(0)? {
+        "hello" => NetMessage::Hello {
+            version: number(1)? as u32,
+            name: rest(2),
+        },
+        "welcome" => NetMessage::Welcome {
+            version: number(1)? as u32,
+            seat: parse_seat(word(2)?)?,
+            rules: parse_rules(word(3)?)?,
+            name: rest(4),
+        },
+        "refused" => NetMessage::Refused(rest(1)),
+        "board" => {
+            if words.len() != 5 {
+                return Err(format!("expected 4 cards: {}", line.trim()));
+            }
+            NetMessage::Board(parse_board(&words[1..])?)
+        }
+        "deal" => NetMessage::Deal(parse_cards(&words[1..], 4)?),
+        "played" => {
+            let deal = match words.get(4) {
+                Some(&"deal") => Some(parse_cards(&words[5..], 4)?),
+                Some(other) => return Err(format!("unknown word {}: {}", other, line.trim())),
+                None => None,
+            };
+            NetMessage::Played {
+                player: parse_seat(word(1)?)?,
+                card: word(2)?.parse::<Card>()?,
+                stat: word(3)?
+                    .parse::<WinStatus>()
+                    .map_err(|_| format!("unknown result: {}", line.trim()))?,
+                deal,
+            }
+        }
+        "ready" => NetMessage::Ready,
+        "play" => NetMessage::Play(word(1)?.parse::<Card>()?),
+        "illegal" => NetMessage::Illegal(word(1)?.parse::<Card>()?),
+        "gameover" => NetMessage::GameOver {
+            points: (number(1)? as usize, number(2)? as usize),
+            seed: number(3)?,
+            face_down: parse_cards(words.get(4..).unwrap_or(&[]), 3)?,
+        },
+        "bye" => NetMessage::Bye,
+        "ping" => NetMessage::Ping,
+        "pong" => NetMessage::Pong,
+        "state" => {
+            let marker = |name: &str| {
+                words.iter().position(|w| *w == name).ok_or(format!(
+                    "{} is missing: {}",
+                    name,
+                    line.trim()
+                ))
+            };
+            let (board, hand, legal) = (marker("board")?, marker("hand")?, marker("legal")?);
+            if !(board == 5 && board < hand && hand < legal) {
+                return Err(format!("invalid state: {}", line.trim()));
+            }
+            let cards = |words: &[&str]| -> Result<Vec<Card>, String> {
+                words.iter().map(|w| w.parse::<Card>()).collect()
+            };
+            NetMessage::State(TableState {
+                turn: parse_seat(word(1)?)?,
+                points: (number(2)? as usize, number(3)? as usize),
+                deck: number(4)? as usize,
+                board: parse_board(&words[board + 1..hand])?,
+                hand: cards(&words[hand + 1..legal])?,
+                legal: cards(&words[legal + 1..])?,
+            })
+        }
+        "session" => NetMessage::Session(word(1)?.to_string()),
+        "resume" => NetMessage::Resume(word(1)?.to_string()),
+        "lobby" => NetMessage::Lobby(rest(1)),
+        "list" => NetMessage::List,
+        "tables" => NetMessage::Tables(number(1)? as usize),
+        "table" => NetMessage::Table(TableInfo {
+            id: number(1)? as usize,
+            rules: parse_rules(word(2)?)?,
+            seats: (word(3)?.to_string(), word(4)?.to_string()),
+            status: word(5)?
+                .parse::<TableStatus>()
+                .map_err(|_| format!("unknown status: {}", line.trim()))?,
+        }),
+        "create" => NetMessage::Create {
+            rules: parse_rules(word(1)?)?,
+            seats: (parse_create_seat(word(2)?)?, parse_create_seat(word(3)?)?),
+        },
+        "created" => NetMessage::Created(number(1)? as usize),
+        "join" => NetMessage::Join {
+            id: number(1)? as usize,
+            seat: parse_seat(word(2)?)?,
+        },
+        "error" => NetMessage::Error(rest(1)),
+        other => return Err(format!("unknown message: {}", other)),
+    };
+    Ok(message)
+}
+
+pub fn send_message(stream: &mut TcpStream, message: &NetMessage) -> Result<(), String> {
+    writeln!(stream, "{}", message_to_line(message))
+        .and_then(|_| stream.flush())
+        .map_err(|e| format!("cannot send: {}", e))
+}
+
+pub fn send_on(stream: &mut TcpStream, wire: Wire, message: &NetMessage) -> Result<(), String> {
+    match wire {
+        Wire::Lines => send_message(stream, message),
+        Wire::WebSocket | Wire::WebSocketClient => {
+            let text = json_to_string(&message_to_json(message));
+            write_text(stream, &text, wire == Wire::WebSocketClient)
+        }
+    }
+}
+
+/// the next message, the pings of a WebSocket are answered on the way, only while nobody
+/// else writes to the connection
+pub fn read_on(reader: &mut BufReader<TcpStream>, wire: Wire) -> Result<NetMessage, String> {
+    read_answering(reader, wire, |reader, payload| {
+        write_pong(reader.get_mut(), payload, wire == Wire::WebSocketClient)
+    })
+}
+
+/// the next message, `on_ping` answers the pings of a WebSocket on the way
+pub fn read_answering(
+    reader: &mut BufReader<TcpStream>,
+    wire: Wire,
+    on_ping: impl FnMut(&mut BufReader<TcpStream>, &[u8]) -> Result<(), String>,
+) -> Result<NetMessage, String> {
+    let text = match wire {
+        Wire::Lines => return read_message(reader),
+        Wire::WebSocket => read_text(reader, true, on_ping)?,
+        Wire::WebSocketClient => read_text(reader, false, on_ping)?,
+    };
+    json_to_message(&parse_json(&text)?)
+}
+
+/// `read_line` that reads at most `MAX_LINE` bytes, a longer line is an error
+pub fn read_bounded_line(
+    reader: &mut BufReader<TcpStream>,
+    line: &mut String,
+) -> Result<usize, String> {
+    let read = reader
+        .by_ref()
+        .take(MAX_LINE)
+        .read_line(line)
+        .map_err(|e| format!("cannot read: {}", e))?;
+    if read as u64 == MAX_LINE && !line.ends_with('\n') {
+        return Err(format!("a line is longer than {} bytes", MAX_LINE));
+    }
+    Ok(read)
+}
+
+/// the next message, an error when the connection is closed or the line makes no sense
+pub fn read_message(reader: &mut BufReader<TcpStream>) -> Result<NetMessage, String> {
+    let mut line = String::new();
+    match read_bounded_line(reader, &mut line)? {
+        0 => Err(String::from("the connection was closed")),
+        _ => parse_message(&line),
+    }
+}
+
+/// reads messages in a thread until bye or an error, which is passed on last
+pub fn spawn_reader(
+    mut reader: BufReader<TcpStream>,
+    mut on_message: impl FnMut(Result<NetMessage, String>) + Send + 'static,
+) {
+    thread::spawn(move || loop {
+        let message = read_message(&mut reader);
+        let last = !matches!(message, Ok(ref m) if *m != NetMessage::Bye);
+        on_message(message);
+        if last {
+            break;
+        }
+    });
+}
+
+/// how long a new connection may take to say hello
+pub fn handshake_timeout() -> Option<Duration> {
+    Some(Duration::from_millis(HANDSHAKE_TIMEOUT_MS))
+}
+
+/// the guest as the host sees it, it is sent what happened after every move
+pub struct RemoteSeat {
+    pub name: String,
+    pub seat: Player,
+    stream: TcpStream,
+    wire: Wire,
+    sent_moves: usize,
+    sent_game_over: bool,
+    /// false once a message could not be sent
+    pub connected: bool,
+}
+
+impl RemoteSeat {
+    fn send(&mut self, message: &NetMessage) {
+        let wire = self.wire;
+        self.write(|stream| send_on(stream, wire, message));
+    }
+
+    fn write(&mut self, write: impl FnOnce(&mut TcpStream) -> Result<(), String>) {
+        if !self.connected {
+            return;
+        }
+        if let Err(e) = write(&mut self.stream) {
+            println!("{} left: {}", self.name, e);
+            self.connected = false;
+            // the reader of the connection stops too, the player sees it dropped
+            let _ = self.stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    pub fn ping(&mut self) {
+        self.send(&NetMessage::Ping);
+    }
+
+    /// answers a ping of a WebSocket client, on the stream of every other message
+    pub fn pong(&mut self, payload: &[u8]) {
+        let masked = self.wire == Wire::WebSocketClient;
+        self.write(|stream| write_pong(stream, payload, masked));
+    }
+
+    /// sends the moves made since the last time, and the result once the game is over,
+    /// then the state when anything was sent
+    pub fn send_updates(&mut self, game: &Game) {
+        let changed = self.sent_moves < game.moves.len();
+        for i in self.sent_moves..game.moves.len() {
+            let a_move = game.moves[i];
+            let next_deal = (i + 1) / CARDS_PER_DEAL;
+            let deal = ((i + 1) % CARDS_PER_DEAL == 0
+                && deal_position(self.seat, next_deal, 3) < game.initial_deck.len())
+            .then(|| game.hand_of_deal(self.seat, next_deal));
+            self.send(&NetMessage::Played {
+                player: a_move.player,
+                card: a_move.card,
+                stat: a_move.stat,
+                deal,
+            });
+        }
+        self.sent_moves = game.moves.len();
+        if game.is_game_over() && !self.sent_game_over {
+            self.sent_game_over = true;
+            self.send(&NetMessage::GameOver {
+                points: (game.player1_point, game.player2_point),
+                seed: game.seed,
+                face_down: game.initial_board().into_iter().take(3).collect(),
+            });
+        }
+        if changed {
+            self.send_state(game);
+        }
+    }
+
+    pub fn send_state(&mut self, game: &Game) {
+        self.send(&NetMessage::State(TableState::new(game, self.seat)));
+    }
+
+    pub fn illegal(&mut self, a_card: Card) {
+        self.send(&NetMessage::Illegal(a_card));
+    }
+}
+
+impl Drop for RemoteSeat {
+    fn drop(&mut self) {
+        self.send(&NetMessage::Bye);
+    }
+}
+
+/// the token a LAN host gives its guest, the guest takes its seat back with it
+pub fn new_session() -> String {
+    format!("{:016x}", rand::random::<u64>())
+}
+
+/// waits until a guest with our protocol version says hello, then sends it what `seat`
+/// may see of `game` and the moves made so far, and the `session` token of its seat
+pub fn accept_guest(
+    listener: &TcpListener,
+    game: &Game,
+    seat: Player,
+    host_name: &str,
+    session: &str,
+) -> Result<(RemoteSeat, BufReader<TcpStream>), String> {
+    loop {
+        let (stream, address) = listener
+            .accept()
+            .map_err(|e| format!("cannot accept a player: {}", e))?;
+        match welcome_guest(stream, game, seat, host_name, session) {
+            Ok(guest) => return Ok(guest),
+            Err(e) => println!("{}: {}", address, e),
+        }
+    }
+}
+
+/// accepts the connections of a LAN host in a thread once its guest joined. One that says
+/// hello and `resume <session>` goes to `on_resume` to be seated again, which returns false
+/// when nobody takes it any more, every other connection is refused
+pub fn spawn_resumer(
+    listener: TcpListener,
+    session: String,
+    mut on_resume: impl FnMut(TcpStream, BufReader<TcpStream>) -> bool + Send + 'static,
+) {
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            match read_resume(&mut stream, &session) {
+                Ok(reader) => {
+                    if !on_resume(stream, reader) {
+                        break;
+                    }
+                }
+                Err(e) => println!("{}", e),
+            }
+        }
+    });
+}
+
+/// the hello and resume of a guest that comes back, a new guest only says hello and is
+/// refused after the handshake timeout
+fn read_resume(stream: &mut TcpStream, session: &str) -> Result<BufReader<TcpStream>, String> {
+    let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+    read_hello(stream, &mut reader, Wire::Lines)?;
+    stream
+        .set_read_timeout(handshake_timeout())
+        .map_err(|e| e.to_string())?;
+    let reason = match read_message(&mut reader) {
+        Ok(NetMessage::Resume(token)) if token == session => {
+            stream.set_read_timeout(None).map_err(|e| e.to_string())?;
+            return Ok(reader);
+        }
+        Ok(NetMessage::Resume(_)) => "the session is over",
+        _ => "the game has its guest",
+    };
+    let _ = send_message(stream, &NetMessage::Refused(reason.to_string()));
+    Err(format!("refused a player: {}", reason))
+}
+
+/// reads the hello of a new connection, refuses another protocol version,
+/// returns the name of the player
+pub fn read_hello(
+    stream: &mut TcpStream,
+    reader: &mut BufReader<TcpStream>,
+    wire: Wire,
+) -> Result<String, String> {
+    stream
+        .set_read_timeout(handshake_timeout())
+        .and_then(|_| stream.set_nodelay(true))
+        .map_err(|e| e.to_string())?;
+    let name = match read_on(reader, wire)? {
+        NetMessage::Hello { version, name } if version == PROTOCOL_VERSION => {
+            check_name(&name).unwrap_or(String::from("Guest"))
+        }
+        NetMessage::Hello { version, .. } => {
+            let reason = format!("the host speaks protocol version {}", PROTOCOL_VERSION);
+            let _ = send_on(stream, wire, &NetMessage::Refused(reason));
+            return Err(format!("refused protocol version {}", version));
+        }
+        other => return Err(format!("expected hello: {}", message_to_line(&other))),
+    };
+    stream.set_read_timeout(None).map_err(|e| e.to_string())?;
+    Ok(name)
+}
+
+fn welcome_guest(
+    mut stream: TcpStream,
+    game: &Game,
+    seat: Player,
+    host_name: &str,
+    session: &str,
+) -> Result<(RemoteSeat, BufReader<TcpStream>), String> {
+    let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+    let name = read_hello(&mut stream, &mut reader, Wire::Lines)?;
+    let remote = seat_guest(
+        stream,
+        Wire::Lines,
+        &name,
+        game,
+        seat,
+        host_name,
+        Some(session),
+    )?;
+    Ok((remote, reader))
+}
+
+/// sends the player `name` what `seat` may see of `game` and the moves made so far,
+/// after its hello
+pub fn seat_guest(
+    mut stream: TcpStream,
+    wire: Wire,
+    name: &str,
+    game: &Game,
+    seat: Player,
+    host_name: &str,
+    session: Option<&str>,
+) -> Result<RemoteSeat, String> {
+    let board = game
+        .initial_board()
+        .into_iter()
+        .enumerate()
+        .map(|(i, c)| (i == 3).then_some(c))
+        .collect();
+    let mut messages = vec![NetMessage::Welcome {
+        version: PROTOCOL_VERSION,
+        seat,
+        rules: game.rules.preset,
+        name: host_name.to_string(),
+    }];
+    messages.extend(session.map(|token| NetMessage::Session(token.to_string())));
+    messages.push(NetMessage::Board(board));
+    messages.push(NetMessage::Deal(game.hand_of_deal(seat, 0)));
+    for message in messages {
+        send_on(&mut stream, wire, &message)?;
+    }
+    let mut remote = RemoteSeat {
+        name: name.to_string(),
+        seat,
+        stream,
+        wire,
+        sent_moves: 0,
+        sent_game_over: false,
+        connected: true,
+    };
+    remote.send_updates(game);
+    remote.send(&NetMessage::Ready);
+    remote.send_state(game);
+    if !remote.connected {
+        return Err(String::from("the guest left while joining"));
+    }
+    Ok(remote)
+}
+
+/// a move of the host's game as the guest plays it again
+#[derive(Clone, Debug)]
+pub struct GuestMove {
+    pub player: Player,
+    pub card: Card,
+    pub stat: WinStatus,
+    pub deal: DealStatus,
+    /// the hand the card was played from as it was dealt, for the slots on the table
+    pub dealt_hand: Vec<Card>,
+}
+
+/// the game as far as the guest knows it, see the top of this file
+pub struct GuestGame {
+    pub game: Game,
+    pub seat: Player,
+    pub host_name: String,
+    /// the token to resume the seat at a server or LAN host
+    pub session: Option<String>,
+    /// the initial deck, the cards the guest has not seen are anywhere they could be
+    deck: Vec<Card>,
+    /// the places of `deck`, counted from its end, whose card the guest has seen
+    known: Vec<bool>,
+}
+
+impl GuestGame {
+    pub fn new(seat: Player, rules: RulePreset, host_name: &str) -> Self {
+        let mut game = Game::with_seed(0);
+        game.rules = rules.rules();
+        game.create_deck();
+        let deck: Vec<Card> = game.deck.iter().copied().collect();
+        GuestGame {
+            game,
+            seat,
+            host_name: host_name.to_string(),
+            session: None,
+            known: vec![false; deck.len()],
+            deck,
+        }
+    }
+
+    /// puts `a_card` `position` places from the end of the deck, the card there goes where
+    /// `a_card` was, neither may have been seen before
+    fn place(&mut self, a_card: Card, position: usize) -> Result<(), String> {
+        let last = self.deck.len() - 1;
+        let misplaced = format!("{} cannot be where the host says", a_card.notation());
+        let target = last.checked_sub(position).ok_or(misplaced.clone())?;
+        let from = self.deck.iter().position(|c| *c == a_card).unwrap();
+        if from != target && (self.known[last - from] || self.known[position]) {
+            return Err(misplaced);
+        }
+        self.deck.swap(from, target);
+        self.known[position] = true;
+        Ok(())
+    }
+
+    /// deals the deck again and plays the moves made so far
+    fn rebuild(&mut self) -> Result<(), String> {
+        let moves: Vec<Card> = self.game.moves.iter().map(|m| m.card).collect();
+        let mut game = Game::with_seed(self.game.seed);
+        game.rules = self.game.rules;
+        game.start_game_with_deck(&self.deck);
+        replay_moves(&mut game, &moves)?;
+        self.game = game;
+        Ok(())
+    }
+
+    fn set_hand(&mut self, deal: usize, hand: &[Card]) -> Result<(), String> {
+        for (i, a_card) in hand.iter().enumerate() {
+            self.place(*a_card, deal_position(self.seat, deal, i))?;
+        }
+        Ok(())
+    }
+
+    /// applies a message of the host that changes the game, returns the move if it was one
+    pub fn apply(&mut self, message: &NetMessage) -> Result<Option<GuestMove>, String> {
+        match message {
+            NetMessage::Board(board) => {
+                for (i, a_card) in board.iter().enumerate() {
+                    if let Some(a_card) = a_card {
+                        self.place(*a_card, i)?;
+                    }
+                }
+            }
+            NetMessage::Deal(hand) => {
+                self.set_hand(self.game.moves.len() / CARDS_PER_DEAL, hand)?;
+            }
+            NetMessage::Played {
+                player,
+                card,
+                stat,
+                deal,
+            } => return self.play(*player, *card, *stat, deal.as_deref()).map(Some),
+            NetMessage::GameOver {
+                points,
+                seed,
+                face_down,
+            } => {
+                for (i, a_card) in face_down.iter().enumerate() {
+                    self.place(*a_card, i)?;
+                }
+                self.game.seed = *seed;
+                self.rebuild()?;
+                let counted = (self.game.player1_point, self.game.player2_point);
+                if !self.game.is_game_over() || counted != *points {
+                    return Err(format!(
+                        "the host counts {}-{}, the cards give {}-{}",
+                        points.0, points.1, counted.0, counted.1
+                    ));
+                }
+                return Ok(None);
+            }
+            // the guest sees the same in its own game
+            NetMessage::State(_) => return Ok(None),
+            NetMessage::Session(token) => {
+                self.session = Some(token.clone());
+                return Ok(None);
+            }
+            other => return Err(format!("unexpected message: {}", message_to_line(other))),
+        }
+        self.rebuild()?;
+        Ok(None)
+    }
+
+    fn play(
+        &mut self,
+        player: Player,
+        a_card: Card,
+        stat: WinStatus,
+        deal: Option<&[Card]>,
+    ) -> Result<GuestMove, String> {
+        if self.game.is_game_over() || player != self.game.turn {
+            return Err(format!("seat {} played out of turn", seat_number(player)));
+        }
+        let deal_index = self.game.moves.len() / CARDS_PER_DEAL;
+        if player != self.seat {
+            // the card comes from a slot of the opponent that was not played yet
+            let last = self.deck.len() - 1;
+            let slots: Vec<usize> = (0..4)
+                .map(|i| deal_position(player, deal_index, i))
+                .filter(|p| !self.known[*p])
+                .collect();
+            let position = slots
+                .iter()
+                .copied()
+                .find(|p| self.deck[last - p] == a_card)
+                .or(slots.first().copied())
+                .ok_or(format!("seat {} has no cards left", seat_number(player)))?;
+            self.place(a_card, position)?;
+        }
+        if let Some(hand) = deal {
+            self.set_hand(deal_index + 1, hand)?;
+        }
+        self.rebuild()?;
+        let dealt_hand = self.game.dealt_hand(player);
+        let hand_index = self
+            .game
+            .get_player_cards(player)
+            .iter()
+            .position(|c| *c == a_card)
+            .ok_or(format!(
+                "{} is not in the hand of seat {}",
+                a_card.notation(),
+                seat_number(player)
+            ))?;
+        let (a_card, played) = self.game.play_turn(player, hand_index);
+        if played != stat {
+            return Err(format!(
+                "the host says {} was {}, the cards say {}",
+                a_card.notation(),
+                stat,
+                played
+            ));
+        }
+        let deal = self.game.next_deal();
+        Ok(GuestMove {
+            player,
+            card: a_card,
+            stat,
+            deal,
+            dealt_hand,
+        })
+    }
+}
+
+/// `host:port`, or a host with the default port
+fn with_port(address: &str) -> String {
+    if address.contains(':') {
+        address.to_string()
+    } else {
+        format!("{}:{}", address, DEFAULT_PORT)
+    }
+}
+
+/// connects and says hello
+fn connect(address: &str, name: &str) -> Result<(TcpStream, BufReader<TcpStream>), String> {
+    let address = with_port(address);
+    let mut stream = TcpStream::connect(&address)
+        .map_err(|e| format!("cannot connect to {}: {}", address, e))?;
+    stream
+        .set_read_timeout(handshake_timeout())
+        .and_then(|_| stream.set_nodelay(true))
+        .map_err(|e| e.to_string())?;
+    let reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+    send_message(
+        &mut stream,
+        &NetMessage::Hello {
+            version: PROTOCOL_VERSION,
+            name: name.to_string(),
+        },
+    )?;
+    Ok((stream, reader))
+}
+
+/// connects to the host at `address`, `host:port` or a host with the default port, and
+/// plays the moves made before the guest joined, at a server the first table waiting for a
+/// player is joined, or a new one against the basic AI
+pub fn join_game(
+    address: &str,
+    name: &str,
+) -> Result<(GuestGame, TcpStream, BufReader<TcpStream>), String> {
+    let (mut stream, mut reader) = connect(address, name)?;
+    let guest = match read_message(&mut reader)? {
+        NetMessage::Lobby(_) => {
+            let (id, seat) = match list_tables(&mut stream, &mut reader, Wire::Lines)?
+                .into_iter()
+                .find_map(|t| Some(t.id).zip(open_seat(&t)))
+            {
+                Some(table) => table,
+                None => {
+                    let create = NetMessage::Create {
+                        rules: RulePreset::Standard,
+                        seats: (None, Some(StrategyKind::Basic)),
+                    };
+                    (
+                        create_table(&mut stream, &mut reader, &create, Wire::Lines)?,
+                        Player::Player1,
+                    )
+                }
+            };
+            send_message(&mut stream, &NetMessage::Join { id, seat })?;
+            read_welcome(&mut reader, None, Wire::Lines)?
+        }
+        first => read_welcome(&mut reader, Some(first), Wire::Lines)?,
+    };
+    stream.set_read_timeout(None).map_err(|e| e.to_string())?;
+    Ok((guest, stream, reader))
+}
+
+/// connects to the server or LAN host at `address` again and takes back the seat of
+/// `token`, the game is sent from the start
+pub fn resume_game(
+    address: &str,
+    name: &str,
+    token: &str,
+) -> Result<(GuestGame, TcpStream, BufReader<TcpStream>), String> {
+    let (mut stream, mut reader) = connect(address, name)?;
+    // a server answers hello with its lobby first, a LAN host waits for the token
+    send_message(&mut stream, &NetMessage::Resume(token.to_string()))?;
+    let guest = match read_message(&mut reader)? {
+        NetMessage::Lobby(_) => read_welcome(&mut reader, None, Wire::Lines)?,
+        first => read_welcome(&mut reader, Some(first), Wire::Lines)?,
+    };
+    stream.set_read_timeout(None).map_err(|e| e.to_string())?;
+    Ok((guest, stream, reader))
+}
+
+/// the first open seat of a table waiting for a player
+pub fn open_seat(table: &TableInfo) -> Option<Player> {
+    if table.status != TableStatus::Waiting {
+        return None;
+    }
+    [
+        (Player::Player1, &table.seats.0),
+        (Player::Player2, &table.seats.1),
+    ]
+    .into_iter()
+    .find(|(_, word)| *word == "-")
+    .map(|(seat, _)| seat)
+}
+
+/// the tables of the server, from the lobby
+pub fn list_tables(
+    stream: &mut TcpStream,
+    reader: &mut BufReader<TcpStream>,
+    wire: Wire,
+) -> Result<Vec<TableInfo>, String> {
+    send_on(stream, wire, &NetMessage::List)?;
+    let count = match read_on(reader, wire)? {
+        NetMessage::Tables(count) => count,
+        other => return Err(format!("expected tables: {}", message_to_line(&other))),
+    };
+    (0..count)
+        .map(|_| match read_on(reader, wire)? {
+            NetMessage::Table(info) => Ok(info),
+            other => Err(format!("expected a table: {}", message_to_line(&other))),
+        })
+        .collect()
+}
+
+/// sends `create` from the lobby, returns the id of the new table
+pub fn create_table(
+    stream: &mut TcpStream,
+    reader: &mut BufReader<TcpStream>,
+    create: &NetMessage,
+    wire: Wire,
+) -> Result<usize, String> {
+    send_on(stream, wire, create)?;
+    match read_on(reader, wire)? {
+        NetMessage::Created(id) => Ok(id),
+        NetMessage::Error(reason) => Err(format!("the server refused: {}", reason)),
+        other => Err(format!("expected created: {}", message_to_line(&other))),
+    }
+}
+
+/// reads the welcome of the host, or of a table after join, and the messages up to ready,
+/// `first` is the welcome when it was already read
+pub fn read_welcome(
+    reader: &mut BufReader<TcpStream>,
+    first: Option<NetMessage>,
+    wire: Wire,
+) -> Result<GuestGame, String> {
+    let first = match first {
+        Some(message) => message,
+        None => read_on(reader, wire)?,
+    };
+    let mut guest = match first {
+        NetMessage::Welcome {
+            version,
+            seat,
+            rules,
+            name,
+        } if version == PROTOCOL_VERSION => GuestGame::new(seat, rules, &name),
+        NetMessage::Welcome { version, .. } => {
+            return Err(format!("the host speaks protocol version {}", version))
+        }
+        NetMessage::Refused(reason) => return Err(format!("the host refused: {}", reason)),
+        NetMessage::Error(reason) => return Err(format!("cannot join: {}", reason)),
+        other => return Err(format!("expected welcome: {}", message_to_line(&other))),
+    };
+    loop {
+        match read_on(reader, wire)? {
+            NetMessage::Ready => break,
+            message => {
+                guest.apply(&message)?;
+            }
+        }
+    }
+    Ok(guest)
+}
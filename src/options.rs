@@ -0,0 +1,203 @@
+use std::path::PathBuf;
+
+use crate::{
+    game::{RulePreset, Rules},
+    profiles::check_name,
+    save::autosave_path,
+    settings::Settings,
+    strategy::Difficulty,
+};
+
+/// size of the table window when the settings have none
+pub const WIN_WIDTH: i32 = 800;
+pub const WIN_HEIGHT: i32 = 800;
+
+pub const USAGE: &str = "usage: pistiflex [options]
+       pistiflex match|tune|tui|simulate|analyze|bench|record|stats|results [arguments]
+
+options:
+  --seed S               deal the cards from seed S
+  --rules <preset>       standard, jack-pisti or last-capture
+  --seat 1|2|watch       play first, play second or watch AI vs AI
+  --difficulty <level>   easy, normal, hard or expert AI
+  --ai <strategy>        strategy of the AI seats, see the README
+  --speed 1-9            animation delay, 1 is the fastest
+  --no-animation         move the cards without animation
+  --load <file>          continue a saved game
+  --resume               continue the game left when the window was closed
+  --replay <record>      step through a game record with both hands face up
+  --hotseat              two players take turns at the same screen
+  --profile <name>       play as this profile, a new name makes a new profile
+  --host <port>          wait for a player of the LAN on this port and deal the game
+  --join <host[:port]>   play the game of a host in the LAN, the port is 7878 if not given
+  --puzzle <file>        play the first unsolved puzzle of a puzzle file
+  --puzzle-number N      play puzzle N of the file instead
+  --width N, --height N  window size
+  --help                 show this help";
+
+/// smallest window the table fits into
+const MIN_WIN_SIZE: i32 = 640;
+
+/// options of the GUI, what is not given is asked in a dialog or left at its default
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub seed: Option<u64>,
+    pub rules: Rules,
+    pub seat: Option<String>,
+    /// the AI offered in the strategy dialog
+    pub difficulty: Difficulty,
+    /// strategy spec of the AI seats, from `--ai` or `--difficulty`
+    pub ai: Option<String>,
+    /// 0 turns the animations off
+    pub anim_speed: u8,
+    pub load: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub hotseat: bool,
+    /// the profile of the first human seat
+    pub profile: Option<String>,
+    /// port a LAN game is hosted on
+    pub host: Option<u16>,
+    /// address of the host of the LAN game to join
+    pub join: Option<String>,
+    pub puzzle: Option<PathBuf>,
+    /// counted from 1
+    pub puzzle_number: Option<usize>,
+    pub width: i32,
+    pub height: i32,
+    pub help: bool,
+}
+
+impl Options {
+    /// the settings are the defaults, the command line only changes this launch
+    pub fn from_settings(settings: &Settings) -> Self {
+        let (width, height) = settings
+            .window
+            .map_or((WIN_WIDTH, WIN_HEIGHT), |(_, _, w, h)| {
+                (w.max(MIN_WIN_SIZE), h.max(MIN_WIN_SIZE))
+            });
+        Options {
+            seed: None,
+            rules: settings.rules.rules(),
+            seat: None,
+            difficulty: settings.difficulty,
+            ai: None,
+            anim_speed: settings.anim_speed,
+            load: None,
+            replay: None,
+            hotseat: false,
+            profile: None,
+            host: None,
+            join: None,
+            puzzle: None,
+            puzzle_number: None,
+            width,
+            height,
+            help: false,
+        }
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options::from_settings(&Settings::default())
+    }
+}
+
+fn window_size(flag: &str, value: &str) -> Result<i32, String> {
+    match value.parse::<i32>() {
+        Ok(size) if size >= MIN_WIN_SIZE => Ok(size),
+        _ => Err(format!(
+            "{} needs a number of at least {}",
+            flag, MIN_WIN_SIZE
+        )),
+    }
+}
+
+pub fn parse_options(args: &[String], defaults: Options) -> Result<Options, String> {
+    let mut options = defaults;
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--help" | "-h" => options.help = true,
+            "--no-animation" => options.anim_speed = 0,
+            "--hotseat" => options.hotseat = true,
+            "--resume" => options.load = Some(autosave_path()),
+            _ => {
+                let value = args_iter
+                    .next()
+                    .ok_or(format!("unknown option or missing value: {}", arg))?;
+                match arg.as_str() {
+                    "--seed" => {
+                        options.seed = Some(value.parse().map_err(|_| "--seed needs a number")?)
+                    }
+                    "--rules" => {
+                        options.rules = value
+                            .parse::<RulePreset>()
+                            .map_err(|_| format!("unknown rules: {}", value))?
+                            .rules()
+                    }
+                    "--seat" => match value.as_str() {
+                        "1" | "2" | "watch" => options.seat = Some(value.clone()),
+                        _ => return Err(format!("unknown seat: {}", value)),
+                    },
+                    "--difficulty" => {
+                        options.difficulty = value
+                            .parse::<Difficulty>()
+                            .map_err(|_| format!("unknown difficulty: {}", value))?;
+                        options.ai = Some(options.difficulty.strategy().to_string())
+                    }
+                    "--ai" => options.ai = Some(value.clone()),
+                    "--profile" => options.profile = Some(check_name(value)?),
+                    "--host" => {
+                        options.host = Some(value.parse().map_err(|_| "--host needs a port")?)
+                    }
+                    "--join" => options.join = Some(value.clone()),
+                    "--speed" => match value.parse::<u8>() {
+                        // --no-animation wins whatever the order of the flags
+                        Ok(speed @ 1..=9) if options.anim_speed > 0 => options.anim_speed = speed,
+                        Ok(1..=9) => {}
+                        _ => return Err(String::from("--speed needs a number from 1 to 9")),
+                    },
+                    "--load" => options.load = Some(PathBuf::from(value)),
+                    "--replay" => options.replay = Some(PathBuf::from(value)),
+                    "--puzzle" => options.puzzle = Some(PathBuf::from(value)),
+                    "--puzzle-number" => match value.parse::<usize>() {
+                        Ok(number) if number > 0 => options.puzzle_number = Some(number),
+                        _ => return Err(String::from("--puzzle-number needs a number from 1")),
+                    },
+                    "--width" => options.width = window_size(arg, value)?,
+                    "--height" => options.height = window_size(arg, value)?,
+                    _ => return Err(format!("unknown option: {}", arg)),
+                }
+            }
+        }
+    }
+    if options.hotseat && options.seat.is_some() {
+        return Err(String::from("--hotseat and --seat cannot be used together"));
+    }
+    if options.puzzle.is_some() && (options.load.is_some() || options.hotseat) {
+        return Err(String::from(
+            "--puzzle cannot be used with --load or --hotseat",
+        ));
+    }
+    if options.replay.is_some() && (options.load.is_some() || options.puzzle.is_some()) {
+        return Err(String::from(
+            "--replay cannot be used with --load, --resume or --puzzle",
+        ));
+    }
+    let lan = options.host.is_some() || options.join.is_some();
+    if lan && (options.hotseat || options.puzzle.is_some() || options.replay.is_some()) {
+        return Err(String::from(
+            "--host and --join cannot be used with --hotseat, --puzzle or --replay",
+        ));
+    }
+    if options.join.is_some() && (options.host.is_some() || options.load.is_some()) {
+        return Err(String::from(
+            "--join cannot be used with --host, --load or --resume",
+        ));
+    }
+    if options.host.is_some() && options.seat.as_deref() == Some("watch") {
+        return Err(String::from("the host of a LAN game plays a seat"));
+    }
+    Ok(options)
+}
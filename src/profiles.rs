@@ -0,0 +1,106 @@
+use std::{fs, path::PathBuf};
+
+use crate::storage::{config_dir, parse_key_values, write_file};
+
+/// colours a new profile gets in turn, a profile can also have
+/// `rrggbb`, without `#` which starts a comment in the file
+pub const PROFILE_COLORS: [(&str, (u8, u8, u8)); 6] = [
+    ("blue", (40, 90, 200)),
+    ("red", (200, 40, 40)),
+    ("green", (30, 140, 60)),
+    ("orange", (230, 120, 20)),
+    ("purple", (130, 50, 170)),
+    ("teal", (0, 130, 140)),
+];
+
+/// a player at this machine, the name is shown on the table and keeps the history apart
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    /// a name of `PROFILE_COLORS` or `rrggbb`
+    pub color: String,
+}
+
+impl Profile {
+    pub fn rgb(&self) -> (u8, u8, u8) {
+        parse_color(&self.color).unwrap_or(PROFILE_COLORS[0].1)
+    }
+}
+
+/// `name = colour` lines
+pub fn profiles_path() -> PathBuf {
+    config_dir().join("profiles.txt")
+}
+
+pub fn parse_color(value: &str) -> Option<(u8, u8, u8)> {
+    if let Some((_, rgb)) = PROFILE_COLORS.iter().find(|(name, _)| *name == value) {
+        return Some(*rgb);
+    }
+    let hex = Some(value).filter(|h| h.len() == 6)?;
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// a name has to fit on the table and in the files the seats are written to
+pub fn check_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > 20 {
+        return Err(String::from("a name has 1 to 20 characters"));
+    }
+    if name.contains(['=', '#', ':', '"', '\n']) {
+        return Err(format!("{} has one of = # : \"", name));
+    }
+    Ok(name.to_string())
+}
+
+/// a line with a bad name or colour is skipped with a message
+pub fn parse_profiles(text: &str) -> Vec<Profile> {
+    let pairs = match parse_key_values(text) {
+        Ok(pairs) => pairs,
+        Err(e) => {
+            println!("profiles: {}", e);
+            return Vec::new();
+        }
+    };
+    let mut profiles: Vec<Profile> = Vec::new();
+    for (line_no, name, color) in pairs {
+        match (check_name(&name), parse_color(&color)) {
+            (Ok(name), Some(_)) if !profiles.iter().any(|p| p.name == name) => {
+                profiles.push(Profile { name, color })
+            }
+            _ => println!("profiles line {}: invalid {} = {}", line_no, name, color),
+        }
+    }
+    profiles
+}
+
+pub fn profiles_to_text(profiles: &[Profile]) -> String {
+    profiles
+        .iter()
+        .map(|p| format!("{} = {}\n", p.name, p.color))
+        .collect()
+}
+
+pub fn load_profiles() -> Vec<Profile> {
+    fs::read_to_string(profiles_path())
+        .map(|text| parse_profiles(&text))
+        .unwrap_or_default()
+}
+
+/// the profile called `name`, a new one gets the next colour and is saved
+pub fn find_or_create(name: &str) -> Result<Profile, String> {
+    let name = check_name(name)?;
+    let mut profiles = load_profiles();
+    if let Some(profile) = profiles.iter().find(|p| p.name == name) {
+        return Ok(profile.clone());
+    }
+    let profile = Profile {
+        name,
+        color: PROFILE_COLORS[profiles.len() % PROFILE_COLORS.len()]
+            .0
+            .to_string(),
+    };
+    profiles.push(profile.clone());
+    write_file(&profiles_path(), &profiles_to_text(&profiles))?;
+    Ok(profile)
+}
@@ -0,0 +1,211 @@
+use std::{fs, path::Path};
+
+use crate::{
+    analyze::{solve, solved_moves},
+    game::{Card, Game, Player, RulePreset, Rules},
+    storage::{data_dir, parse_key_values, write_file},
+    strategy::Strategy,
+};
+
+/// most cards left in the deck, the solver looks through every deal to the end
+const MAX_PUZZLE_DECK: usize = 8;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Goal {
+    /// your points minus the points of the opponent
+    Difference,
+    Points,
+}
+
+/// a position to play to the end against the solver, only the cards taken in the puzzle count
+#[derive(Debug, Clone, PartialEq)]
+pub struct Puzzle {
+    pub name: String,
+    pub hand: Vec<Card>,
+    pub opponent: Vec<Card>,
+    /// from the bottom card to the top card
+    pub board: Vec<Card>,
+    /// in the order the cards are dealt, a whole deal or nothing
+    pub deck: Vec<Card>,
+    pub goal: Goal,
+    pub rules: Rules,
+}
+
+fn parse_cards(value: &str) -> Result<Vec<Card>, String> {
+    value
+        .split_whitespace()
+        .map(|c| c.parse::<Card>())
+        .collect()
+}
+
+impl Puzzle {
+    fn new(name: &str) -> Self {
+        Puzzle {
+            name: name.to_string(),
+            hand: Vec::new(),
+            opponent: Vec::new(),
+            board: Vec::new(),
+            deck: Vec::new(),
+            goal: Goal::Difference,
+            rules: Rules::default(),
+        }
+    }
+
+    /// Player1 starts every deal, so the one to move is Player2 when the opponent already played
+    pub fn player(&self) -> Player {
+        if self.hand.len() > self.opponent.len() {
+            Player::Player2
+        } else {
+            Player::Player1
+        }
+    }
+
+    fn check(&self) -> Result<(), String> {
+        let hands_fit =
+            self.hand.len() == self.opponent.len() || self.hand.len() == self.opponent.len() + 1;
+        if self.hand.is_empty() || self.hand.len() > 4 || !hands_fit {
+            return Err(String::from(
+                "the hand needs 1 to 4 cards, as many as the opponent or one more",
+            ));
+        }
+        if !self.deck.len().is_multiple_of(8) || self.deck.len() > MAX_PUZZLE_DECK {
+            return Err(format!(
+                "the deck can have 0 or {} cards, not {}",
+                MAX_PUZZLE_DECK,
+                self.deck.len()
+            ));
+        }
+        let cards: Vec<&Card> = [&self.hand, &self.opponent, &self.board, &self.deck]
+            .into_iter()
+            .flatten()
+            .collect();
+        for (i, a_card) in cards.iter().enumerate() {
+            if cards[..i].contains(a_card) {
+                return Err(format!("{} is used twice", a_card.notation()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_game(&self) -> Game {
+        let mut my_game = Game::with_seed(0);
+        my_game.rules = self.rules;
+        my_game.board = self.board.iter().copied().collect();
+        my_game.deck = self.deck.iter().rev().copied().collect();
+        let player = self.player();
+        *my_game.get_player_cards_mut(player) = self.hand.iter().copied().collect();
+        *my_game.get_player_cards_mut(player.other()) = self.opponent.iter().copied().collect();
+        my_game.turn = player;
+        my_game
+    }
+
+    /// result of a finished game for the player of the puzzle
+    pub fn score(&self, my_game: &Game) -> i64 {
+        let (mine, theirs) = match self.player() {
+            Player::Player1 => (my_game.player1_point, my_game.player2_point),
+            Player::Player2 => (my_game.player2_point, my_game.player1_point),
+        };
+        match self.goal {
+            Goal::Difference => mine as i64 - theirs as i64,
+            Goal::Points => mine as i64,
+        }
+    }
+
+    /// the best result against an opponent who plays perfectly
+    pub fn best_score(&self) -> i64 {
+        solve(&self.to_game(), &|g| self.score(g), self.player())
+    }
+
+    pub fn goal_text(&self) -> String {
+        match self.goal {
+            Goal::Difference => String::from("win by as many points as you can"),
+            Goal::Points => String::from("take as many points as you can"),
+        }
+    }
+}
+
+/// every `name` line starts a new puzzle, the other keys belong to the puzzle above them
+pub fn parse_puzzles(text: &str) -> Result<Vec<Puzzle>, String> {
+    let mut puzzles: Vec<Puzzle> = Vec::new();
+    for (line_no, key, value) in parse_key_values(text)? {
+        if key == "name" {
+            puzzles.push(Puzzle::new(&value));
+            continue;
+        }
+        let puzzle = puzzles
+            .last_mut()
+            .ok_or(format!("line {}: a puzzle starts with its name", line_no))?;
+        let at_line = |e: String| format!("line {}: {}", line_no, e);
+        match key.as_str() {
+            "hand" => puzzle.hand = parse_cards(&value).map_err(at_line)?,
+            "opponent" => puzzle.opponent = parse_cards(&value).map_err(at_line)?,
+            "board" => puzzle.board = parse_cards(&value).map_err(at_line)?,
+            "deck" => puzzle.deck = parse_cards(&value).map_err(at_line)?,
+            "goal" => {
+                puzzle.goal = match value.as_str() {
+                    "difference" => Goal::Difference,
+                    "points" => Goal::Points,
+                    _ => return Err(at_line(format!("unknown goal {}", value))),
+                }
+            }
+            "rules" => {
+                puzzle.rules = value
+                    .parse::<RulePreset>()
+                    .map_err(|_| at_line(format!("unknown rules {}", value)))?
+                    .rules()
+            }
+            _ => {}
+        }
+    }
+    for puzzle in &puzzles {
+        puzzle
+            .check()
+            .map_err(|e| format!("{}: {}", puzzle.name, e))?;
+    }
+    Ok(puzzles)
+}
+
+pub fn load_puzzles(path: &Path) -> Result<Vec<Puzzle>, String> {
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    parse_puzzles(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn solved_path() -> std::path::PathBuf {
+    data_dir().join("solved_puzzles.txt")
+}
+
+/// names of the puzzles solved so far, one per line
+pub fn solved_puzzles() -> Vec<String> {
+    fs::read_to_string(solved_path())
+        .map(|text| text.lines().map(|l| l.to_string()).collect())
+        .unwrap_or_default()
+}
+
+pub fn mark_solved(name: &str) {
+    let mut solved = solved_puzzles();
+    if solved.iter().any(|s| s == name) {
+        return;
+    }
+    solved.push(name.to_string());
+    if let Err(e) = write_file(&solved_path(), &(solved.join("\n") + "\n")) {
+        println!("cannot save the solved puzzles: {}", e);
+    }
+}
+
+/// the opponent of a puzzle, plays the card that leaves the player the lowest score
+pub struct PuzzleOpponent {
+    pub puzzle: Puzzle,
+}
+
+impl Strategy for PuzzleOpponent {
+    fn name(&self) -> String {
+        String::from("solver")
+    }
+
+    fn pick_card(&mut self, game: &Game, _player: Player) -> usize {
+        let puzzle = &self.puzzle;
+        let values = solved_moves(game, &|g| puzzle.score(g), puzzle.player());
+        (0..values.len()).min_by_key(|i| values[*i]).unwrap_or(0)
+    }
+}
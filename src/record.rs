@@ -0,0 +1,346 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    analyze::parse_deck,
+    game::{Card, Game, Move, RulePreset, Rules, WinStatus},
+    harness::play_ai_game,
+    save::load_game,
+    seat::{spec_name, Seat, Seats},
+    storage::write_file,
+    strategy::strategy_from_spec,
+};
+
+/// a game written down: tags like `Player1` or `Rules`, the deck in dealing order and the moves
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub tags: Vec<(String, String)>,
+    pub deck: Option<Vec<Card>>,
+    /// the card of every move and what the mark after it says the card did, if it has one
+    pub moves: Vec<(Card, Option<WinStatus>)>,
+}
+
+/// today as `YYYY.MM.DD`, the way the Date tag is written
+pub fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() / 86400) as i64;
+    // days since 1970-01-01 to the civil date, years start in March so the leap day comes last
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+/// the card with `x` after it when it takes the board and `+` when it makes a pisti
+fn move_text(a_move: &Move) -> String {
+    format!("{}{}", a_move.card.notation(), mark_of(a_move.stat))
+}
+
+pub fn mark_of(stat: WinStatus) -> &'static str {
+    match stat {
+        WinStatus::Pisti => "+",
+        WinStatus::Win => "x",
+        WinStatus::Pass => "",
+    }
+}
+
+fn result_text(my_game: &Game) -> String {
+    if my_game.is_game_over() {
+        format!("{}-{}", my_game.player1_point, my_game.player2_point)
+    } else {
+        String::from("*")
+    }
+}
+
+pub fn game_to_record(my_game: &Game, player1: &str, player2: &str, date: &str) -> String {
+    let result = result_text(my_game);
+    let mut text = String::new();
+    for (name, value) in [
+        ("Event", "Pisti"),
+        ("Date", date),
+        ("Player1", player1),
+        ("Player2", player2),
+        ("Rules", &my_game.rules.preset.to_string()),
+        ("Seed", &my_game.seed.to_string()),
+        ("Result", &result),
+    ] {
+        text.push_str(&format!("[{} \"{}\"]\n", name, value.replace('"', "'")));
+    }
+    let deck: Vec<String> = my_game
+        .initial_deck
+        .iter()
+        .rev()
+        .map(|c| c.notation())
+        .collect();
+    text.push_str(&format!("\nDeck: {}\n", deck.join(" ")));
+    let mut line = String::new();
+    for (i, pair) in my_game.moves.chunks(2).enumerate() {
+        let moves: Vec<String> = pair.iter().map(move_text).collect();
+        let item = format!("{}. {}", i + 1, moves.join(" "));
+        if !line.is_empty() && line.len() + item.len() >= 80 {
+            text.push_str(&line);
+            text.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&item);
+    }
+    if !line.is_empty() {
+        line.push(' ');
+    }
+    line.push_str(&result);
+    text.push_str(&line);
+    text.push('\n');
+    text
+}
+
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let (name, value) = inner.split_once(' ')?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((name.to_string(), value.to_string()))
+}
+
+pub fn parse_move(token: &str) -> Result<(Card, Option<WinStatus>), String> {
+    let (card, mark) = match token.strip_suffix('x') {
+        Some(card) => (card, Some(WinStatus::Win)),
+        None => match token.strip_suffix('+') {
+            Some(card) => (card, Some(WinStatus::Pisti)),
+            None => (token, None),
+        },
+    };
+    Ok((card.parse::<Card>()?, mark))
+}
+
+fn is_result(token: &str) -> bool {
+    token == "*"
+        || token
+            .split_once('-')
+            .is_some_and(|(a, b)| a.parse::<usize>().is_ok() && b.parse::<usize>().is_ok())
+}
+
+/// tags, then a `Deck:` line and the moves; move numbers like `12.` and the result are skipped
+pub fn parse_record(text: &str) -> Result<Record, String> {
+    let mut record = Record {
+        tags: Vec::new(),
+        deck: None,
+        moves: Vec::new(),
+    };
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let at_line = |e: String| format!("line {}: {}", line_no + 1, e);
+        if line.starts_with('[') {
+            let tag = parse_tag(line).ok_or(at_line(String::from("expected [Name \"value\"]")))?;
+            record.tags.push(tag);
+        } else if let Some(deck) = line.strip_prefix("Deck:") {
+            record.deck = Some(parse_deck(deck).map_err(at_line)?);
+        } else {
+            for token in line.split_whitespace() {
+                if token.ends_with('.') || is_result(token) {
+                    continue;
+                }
+                record.moves.push(parse_move(token).map_err(at_line)?);
+            }
+        }
+    }
+    Ok(record)
+}
+
+impl Record {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// plays the record through the rules, every card, mark and the result have to be right
+    pub fn to_game(&self) -> Result<Game, String> {
+        let rules = match self.tag("Rules") {
+            Some(preset) => preset
+                .parse::<RulePreset>()
+                .map_err(|_| format!("unknown rules {}", preset))?
+                .rules(),
+            None => Rules::default(),
+        };
+        let seed = match self.tag("Seed") {
+            Some(seed) => Some(
+                seed.parse::<u64>()
+                    .map_err(|_| format!("invalid seed {}", seed))?,
+            ),
+            None => None,
+        };
+        let mut my_game = Game::with_seed(seed.unwrap_or(0));
+        my_game.rules = rules;
+        match (&self.deck, seed) {
+            (Some(deck), _) => {
+                my_game.start_game_with_deck(deck);
+                if my_game.is_reshuffle_required() {
+                    return Err(String::from("the deck puts a J on top of the board"));
+                }
+            }
+            (None, Some(_)) => my_game.start_game_and_give_cards_to_players(),
+            (None, None) => return Err(String::from("a record needs a Deck or a Seed")),
+        }
+        for (i, (a_card, mark)) in self.moves.iter().enumerate() {
+            if my_game.is_game_over() {
+                return Err(format!("move {}: the game is already over", i + 1));
+            }
+            let player = my_game.turn;
+            let hand_index = my_game
+                .get_player_cards(player)
+                .iter()
+                .position(|c| c == a_card)
+                .ok_or(format!(
+                    "move {}: {} is not in the hand of the player",
+                    i + 1,
+                    a_card.notation()
+                ))?;
+            let (_, stat) = my_game.play_turn(player, hand_index);
+            if let Some(mark) = mark.filter(|m| *m != stat) {
+                return Err(format!(
+                    "move {}: written {}{} but it is {}",
+                    i + 1,
+                    a_card.notation(),
+                    mark_of(mark),
+                    move_text(&my_game.moves[i])
+                ));
+            }
+            my_game.next_deal();
+        }
+        match self.tag("Result") {
+            Some(result) if result != "*" && result != result_text(&my_game) => Err(format!(
+                "the result is {} but the moves lead to {}",
+                result,
+                result_text(&my_game)
+            )),
+            _ => Ok(my_game),
+        }
+    }
+}
+
+pub fn load_record(path: &Path) -> Result<Record, String> {
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    parse_record(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn export_record(path: &Path, my_game: &Game, seats: &Seats) -> Result<(), String> {
+    let text = game_to_record(
+        my_game,
+        &seats.player1.name(),
+        &seats.player2.name(),
+        &today(),
+    );
+    write_file(&path.to_path_buf(), &text)
+}
+
+const USAGE: &str = "usage: pistiflex record export (--load <file> | --seed S [--p1 <strategy>] [--p2 <strategy>] [--rules <preset>]) [--out <file>]
+       pistiflex record import <file>";
+
+fn export_main(args: &[String]) -> Result<(), String> {
+    let mut load = None;
+    let mut seed = None;
+    let mut p1 = String::from("basic");
+    let mut p2 = String::from("basic");
+    let mut rules = Rules::default();
+    let mut out = None;
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        let value = args_iter.next().ok_or(USAGE)?;
+        match arg.as_str() {
+            "--load" => load = Some(PathBuf::from(value)),
+            "--seed" => seed = Some(value.parse::<u64>().map_err(|_| "--seed needs a number")?),
+            "--p1" => p1 = value.clone(),
+            "--p2" => p2 = value.clone(),
+            "--rules" => {
+                rules = value
+                    .parse::<RulePreset>()
+                    .map_err(|_| format!("unknown rules: {}", value))?
+                    .rules()
+            }
+            "--out" => out = Some(PathBuf::from(value)),
+            _ => return Err(String::from(USAGE)),
+        }
+    }
+    let (my_game, names) = match (load, seed) {
+        (Some(path), None) => {
+            let saved = load_game(&path)?;
+            let names = saved
+                .seats
+                .as_ref()
+                .map_or((String::from("?"), String::from("?")), |(p1, p2)| {
+                    (spec_name(p1), spec_name(p2))
+                });
+            (saved.game, names)
+        }
+        (None, Some(seed)) => {
+            let mut seats = Seats::new(
+                Seat::Ai(strategy_from_spec(&p1)?),
+                Seat::Ai(strategy_from_spec(&p2)?),
+            );
+            let my_game = play_ai_game(&mut seats, seed, rules);
+            (my_game, (seats.player1.name(), seats.player2.name()))
+        }
+        _ => return Err(String::from(USAGE)),
+    };
+    let text = game_to_record(&my_game, &names.0, &names.1, &today());
+    match out {
+        Some(path) => write_file(&path, &text),
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+fn import_main(args: &[String]) -> Result<(), String> {
+    let path = match args {
+        [path] => Path::new(path),
+        _ => return Err(String::from(USAGE)),
+    };
+    let record = load_record(path)?;
+    let my_game = record
+        .to_game()
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    for (name, value) in &record.tags {
+        println!("{}: {}", name, value);
+    }
+    if my_game.is_game_over() {
+        println!(
+            "{} moves, the game is over {}-{}",
+            my_game.moves.len(),
+            my_game.player1_point,
+            my_game.player2_point
+        );
+    } else {
+        println!(
+            "{} moves, the game goes on, continue it with pistiflex --load {}",
+            my_game.moves.len(),
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// `pistiflex record export --seed 5 --out game.pisti`, `pistiflex record import game.pisti`
+pub fn record_main(args: &[String]) -> Result<(), String> {
+    match args.first().map(|a| a.as_str()) {
+        Some("export") => export_main(&args[1..]),
+        Some("import") => import_main(&args[1..]),
+        _ => Err(String::from(USAGE)),
+    }
+}
@@ -0,0 +1,232 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use fltk::{app, button::Button, dialog, frame::Frame, prelude::*, window::DoubleWindow};
+use spin_sleep::SpinSleeper;
+
+use pistiflex::{
+    game::{Card, DealStatus, Game},
+    record::load_record,
+    save::replay_moves,
+    settings::{save_settings, Settings},
+};
+
+use crate::{
+    ui::{open_table, FltkMessage, ReplayControl, TableLayout, TableWindow, ThreadMessage},
+    widget::draw_game,
+};
+
+/// pause between two moves while the replay plays
+const REPLAY_PAUSE: f64 = 0.4;
+
+/// a game record to step through
+pub struct Replay {
+    /// the game at the end of the record
+    pub last: Game,
+    pub players: (String, String),
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let record = load_record(path)?;
+        let last = record
+            .to_game()
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        let player = |tag: &str, default: &str| record.tag(tag).unwrap_or(default).to_string();
+        Ok(Replay {
+            last,
+            players: (player("Player1", "Player 1"), player("Player2", "Player 2")),
+        })
+    }
+
+    pub fn move_count(&self) -> usize {
+        self.last.moves.len()
+    }
+
+    /// the game after the first `moves` moves of the record
+    pub fn game_at(&self, moves: usize) -> Game {
+        let mut my_game = Game::with_seed(self.last.seed);
+        my_game.rules = self.last.rules;
+        my_game.start_game_with_deck(&self.last.initial_deck);
+        let cards: Vec<Card> = self.last.moves[..moves].iter().map(|m| m.card).collect();
+        replay_moves(&mut my_game, &cards).expect("the record was checked when it was loaded");
+        my_game
+    }
+
+    fn result_text(&self) -> String {
+        format!(
+            "{} {} - {} {}",
+            self.players.0, self.last.player1_point, self.last.player2_point, self.players.1
+        )
+    }
+}
+
+/// the table at one move of the replay and its buttons
+struct Viewer {
+    my_game: Game,
+    table_window: TableWindow,
+    move_text: Frame,
+    play_button: Button,
+    playing: bool,
+    /// animations sent to the animator thread and not played yet
+    pending: usize,
+}
+
+impl Viewer {
+    /// the window with the cards after `moves` moves
+    fn open(
+        replay: &Replay,
+        moves: usize,
+        layout: &TableLayout,
+        anim_speed: &Arc<Mutex<u8>>,
+        sleeper: SpinSleeper,
+        sender: app::Sender<FltkMessage>,
+    ) -> Self {
+        let my_game = replay.game_at(moves);
+        let mut table_window =
+            open_table(&my_game, layout, Arc::clone(anim_speed), sleeper, &sender);
+        table_window.win.begin();
+        let mut back_button = Button::new(10, 180, 80, 40, "Back");
+        back_button.emit(sender, FltkMessage::Replay(ReplayControl::Back));
+        let mut next_button = Button::new(10, 230, 80, 40, "Next");
+        next_button.emit(sender, FltkMessage::Replay(ReplayControl::Next));
+        let mut play_button = Button::new(10, 280, 80, 40, "Play");
+        play_button.emit(sender, FltkMessage::Replay(ReplayControl::PlayPause));
+        let mut jump_button = Button::new(10, 330, 80, 40, "Jump");
+        jump_button.emit(sender, FltkMessage::Replay(ReplayControl::Jump));
+        let move_text = Frame::new(10, 380, 80, 40, "");
+        table_window.win.end();
+        table_window.win.redraw();
+        let mut viewer = Viewer {
+            my_game,
+            table_window,
+            move_text,
+            play_button,
+            playing: false,
+            pending: 0,
+        };
+        viewer.show_position(replay);
+        viewer
+    }
+
+    fn show_position(&mut self, replay: &Replay) {
+        self.move_text.set_label(&format!(
+            "{} / {}",
+            self.my_game.moves.len(),
+            replay.move_count()
+        ));
+        self.play_button
+            .set_label(if self.playing { "Pause" } else { "Play" });
+    }
+
+    /// the same window with the cards after `moves` moves
+    fn jump(&mut self, replay: &Replay, moves: usize) {
+        self.my_game = replay.game_at(moves);
+        let table = &mut self.table_window.table;
+        table.top_cards_values = self.my_game.dealt_hand(table.bottom_player.other());
+        table.bottom_cards_values = self.my_game.dealt_hand(table.bottom_player);
+        self.pending += 1;
+        let show = ThreadMessage::Show(Box::new(self.my_game.clone()));
+        draw_game(vec![show], self.table_window.animations.clone());
+        self.show_position(replay);
+    }
+
+    fn at_end(&self, replay: &Replay) -> bool {
+        self.my_game.moves.len() >= replay.move_count()
+    }
+
+    /// plays the next move of the record with its animations
+    fn step(&mut self, replay: &Replay) {
+        if self.at_end(replay) {
+            self.playing = false;
+            self.show_position(replay);
+            return;
+        }
+        let a_card = replay.last.moves[self.my_game.moves.len()].card;
+        let player = self.my_game.turn;
+        let hand_index = self.my_game.get_index_of_card(a_card, player);
+        let (a_card, stat) = self.my_game.play_turn(player, hand_index);
+        let deal = self.my_game.next_deal();
+        let game_over = deal == DealStatus::GameOver;
+        let mut animations =
+            self.table_window
+                .table
+                .move_animations(&self.my_game, player, a_card, stat, deal);
+        if game_over {
+            animations.push(ThreadMessage::GameOver(replay.result_text()));
+        }
+        self.pending += animations.len();
+        draw_game(animations, self.table_window.animations.clone());
+        self.show_position(replay);
+    }
+}
+
+/// shows the record move by move until the window is closed, returns the window
+pub fn run_replay(
+    replay: Replay,
+    layout: TableLayout,
+    anim_speed: Arc<Mutex<u8>>,
+    sleeper: SpinSleeper,
+    settings: &mut Settings,
+    sender: app::Sender<FltkMessage>,
+    receiver: app::Receiver<FltkMessage>,
+) -> DoubleWindow {
+    let mut viewer = Viewer::open(&replay, 0, &layout, &anim_speed, sleeper, sender);
+    while app::wait() {
+        let Some(msg) = receiver.recv() else {
+            continue;
+        };
+        let mut jump_to = None;
+        match msg {
+            FltkMessage::Replay(ReplayControl::Next) => {
+                viewer.playing = false;
+                viewer.step(&replay);
+            }
+            FltkMessage::Replay(ReplayControl::Tick) if viewer.playing && viewer.pending == 0 => {
+                viewer.step(&replay);
+            }
+            FltkMessage::Replay(ReplayControl::PlayPause) => {
+                viewer.playing = !viewer.playing;
+                if viewer.playing && viewer.at_end(&replay) {
+                    jump_to = Some((0, true));
+                } else if viewer.playing && viewer.pending == 0 {
+                    viewer.step(&replay);
+                }
+                viewer.show_position(&replay);
+            }
+            FltkMessage::Replay(ReplayControl::Back) => {
+                jump_to = Some((viewer.my_game.moves.len().saturating_sub(1), false));
+            }
+            FltkMessage::Replay(ReplayControl::Jump) => {
+                let txt = format!("Jump to move (0-{})", replay.move_count());
+                let current = viewer.my_game.moves.len().to_string();
+                jump_to = dialog::input_default(&txt, &current)
+                    .and_then(|m| m.trim().parse::<usize>().ok())
+                    .map(|m| (m.min(replay.move_count()), false));
+            }
+            FltkMessage::Animated => {
+                viewer.pending = viewer.pending.saturating_sub(1);
+                if viewer.playing && viewer.pending == 0 {
+                    app::add_timeout3(REPLAY_PAUSE, move |_| {
+                        sender.send(FltkMessage::Replay(ReplayControl::Tick))
+                    });
+                }
+            }
+            FltkMessage::Speed(anim_speed) => {
+                settings.anim_speed = anim_speed;
+                save_settings(settings);
+            }
+            _ => {}
+        }
+        if let Some((moves, play)) = jump_to {
+            viewer.playing = play;
+            viewer.jump(&replay, moves);
+            if play {
+                viewer.step(&replay);
+            }
+        }
+    }
+    viewer.table_window.win
+}
@@ -0,0 +1,153 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    history::{history_path, load_history, GameEntry},
+    storage::write_file,
+};
+
+/// a value of a result, text is quoted in JSON and CSV when it has to be
+enum Field {
+    Text(String),
+    Number(u64),
+    Missing,
+}
+
+/// the columns of a result in the order they are written
+fn result_fields(entry: &GameEntry) -> Vec<(&'static str, Field)> {
+    let number = |n: usize| Field::Number(n as u64);
+    vec![
+        ("date", Field::Text(entry.date.clone())),
+        ("player1", Field::Text(entry.player1.clone())),
+        ("player2", Field::Text(entry.player2.clone())),
+        ("rules", Field::Text(entry.rules.to_string())),
+        ("seed", Field::Number(entry.seed)),
+        ("player1_points", number(entry.player1_score.total())),
+        ("player1_pisti_points", number(entry.player1_score.pistis)),
+        (
+            "player1_majority_points",
+            number(entry.player1_score.majority),
+        ),
+        ("player1_card_points", number(entry.player1_score.cards)),
+        ("player2_points", number(entry.player2_score.total())),
+        ("player2_pisti_points", number(entry.player2_score.pistis)),
+        (
+            "player2_majority_points",
+            number(entry.player2_score.majority),
+        ),
+        ("player2_card_points", number(entry.player2_score.cards)),
+        ("player1_pistis", number(entry.player1_pistis as usize)),
+        ("player2_pistis", number(entry.player2_pistis as usize)),
+        ("moves", number(entry.moves.len())),
+        (
+            "seconds",
+            entry.seconds.map_or(Field::Missing, Field::Number),
+        ),
+    ]
+}
+
+fn json_value(field: &Field) -> String {
+    match field {
+        Field::Text(text) => {
+            let mut quoted = String::from("\"");
+            for c in text.chars() {
+                match c {
+                    '"' => quoted.push_str("\\\""),
+                    '\\' => quoted.push_str("\\\\"),
+                    c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+                    c => quoted.push(c),
+                }
+            }
+            quoted.push('"');
+            quoted
+        }
+        Field::Number(n) => n.to_string(),
+        Field::Missing => String::from("null"),
+    }
+}
+
+fn csv_value(field: &Field) -> String {
+    match field {
+        Field::Text(text) if text.contains([',', '"', '\n', '\r']) => {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        }
+        Field::Text(text) => text.clone(),
+        Field::Number(n) => n.to_string(),
+        Field::Missing => String::new(),
+    }
+}
+
+/// one line of JSON, the way JSON lines files are written
+pub fn result_to_json(entry: &GameEntry) -> String {
+    let fields: Vec<String> = result_fields(entry)
+        .iter()
+        .map(|(key, value)| format!("\"{}\": {}", key, json_value(value)))
+        .collect();
+    format!("{{{}}}", fields.join(", "))
+}
+
+pub fn results_to_json_lines(entries: &[GameEntry]) -> String {
+    entries.iter().map(|e| result_to_json(e) + "\n").collect()
+}
+
+/// a header line and a line for every game
+pub fn results_to_csv(entries: &[GameEntry]) -> String {
+    let mut lines = Vec::new();
+    if let Some(first) = entries.first() {
+        let header: Vec<&str> = result_fields(first).iter().map(|(key, _)| *key).collect();
+        lines.push(header.join(","));
+    }
+    for entry in entries {
+        let row: Vec<String> = result_fields(entry)
+            .iter()
+            .map(|(_, value)| csv_value(value))
+            .collect();
+        lines.push(row.join(","));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// JSON lines unless the file ends in `.csv`
+pub fn write_results(path: &Path, entries: &[GameEntry]) -> Result<(), String> {
+    let is_csv = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+    let text = if is_csv {
+        results_to_csv(entries)
+    } else {
+        results_to_json_lines(entries)
+    };
+    write_file(&path.to_path_buf(), &text)
+}
+
+const USAGE: &str =
+    "usage: pistiflex results [--format json|csv] [--file <history>] [--out <file>]";
+
+/// `pistiflex results --format csv --out league.csv`, every game of the history
+pub fn results_main(args: &[String]) -> Result<(), String> {
+    let mut format = String::from("json");
+    let mut file = history_path();
+    let mut out = None;
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        let value = args_iter.next().ok_or(USAGE)?;
+        match arg.as_str() {
+            "--format" => format = value.clone(),
+            "--file" => file = PathBuf::from(value),
+            "--out" => out = Some(PathBuf::from(value)),
+            _ => return Err(String::from(USAGE)),
+        }
+    }
+    let entries = load_history(&file)?;
+    let text = match format.as_str() {
+        "json" => results_to_json_lines(&entries),
+        "csv" => results_to_csv(&entries),
+        _ => return Err(format!("unknown format: {}", format)),
+    };
+    match out {
+        Some(path) => write_file(&path, &text),
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
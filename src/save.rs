@@ -0,0 +1,253 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    cards::CardSet,
+    engine::seat_number,
+    game::{Card, Game, RulePreset, Rules},
+    record::parse_record,
+    seat::{seat_spec, Seats},
+    storage::{data_dir, parse_key_values, write_file},
+};
+
+/// version written into saved games, files of a newer version are refused
+pub const SAVE_VERSION: u32 = 2;
+
+/// a game read from a file and who sat at the table
+pub struct SavedGame {
+    pub game: Game,
+    /// "human" or the strategy of each seat, files of version 1 have none
+    pub seats: Option<(String, String)>,
+}
+
+/// where the game is saved when the window is closed before the end
+pub fn autosave_path() -> PathBuf {
+    data_dir().join("autosave.txt")
+}
+
+/// plays `moves` on a new game dealt from `seed`
+pub fn replay(seed: u64, rules: Rules, moves: &[Card]) -> Result<Game, String> {
+    let mut my_game = Game::with_seed(seed);
+    my_game.rules = rules;
+    my_game.start_game_and_give_cards_to_players();
+    replay_moves(&mut my_game, moves)?;
+    Ok(my_game)
+}
+
+/// plays `moves` on a started game, every card must be in the hand of the player to move
+pub fn replay_moves(my_game: &mut Game, moves: &[Card]) -> Result<(), String> {
+    for (i, a_card) in moves.iter().enumerate() {
+        let player = my_game.turn;
+        let hand_index = my_game
+            .get_player_cards(player)
+            .iter()
+            .position(|c| c == a_card)
+            .ok_or(format!(
+                "move {}: {} is not in the hand of the player",
+                i + 1,
+                a_card.notation()
+            ))?;
+        my_game.play_turn(player, hand_index);
+        my_game.next_deal();
+    }
+    Ok(())
+}
+
+fn cards_text(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(|c| c.notation())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn parse_cards(value: &str) -> Result<Vec<Card>, String> {
+    value
+        .split_whitespace()
+        .map(|c| c.parse::<Card>())
+        .collect()
+}
+
+fn won_text(won_cards: &CardSet) -> String {
+    cards_text(&won_cards.iter().collect::<Vec<Card>>())
+}
+
+/// the whole state of the game, the points are the ones the players would get if it ended now
+pub fn game_to_text(my_game: &Game, seats: &Seats) -> String {
+    let mut scored = my_game.clone();
+    scored.calculate_points();
+    let moves: Vec<Card> = my_game.moves.iter().map(|m| m.card).collect();
+    format!(
+        "version = {}\nseed = {}\nrules = {}\nplayer1 = {}\nplayer2 = {}\nreshuffles = {}\n\
+         # dealt from the end\ninitial_deck = {}\ndeck = {}\n\
+         # from the bottom card to the top card\nboard = {}\nface_down = {}\n\
+         player1_hand = {}\nplayer2_hand = {}\nplayer1_won = {}\nplayer2_won = {}\n\
+         player1_pistis = {}\nplayer2_pistis = {}\nturn = {}\npoints = {} {}\nmoves = {}\n",
+        SAVE_VERSION,
+        my_game.seed,
+        my_game.rules.preset,
+        seat_spec(&seats.player1),
+        seat_spec(&seats.player2),
+        my_game.reshuffles,
+        cards_text(&my_game.initial_deck),
+        cards_text(&my_game.deck),
+        cards_text(&my_game.board),
+        cards_text(&my_game.face_down_cards()),
+        cards_text(&my_game.bottom_hand),
+        cards_text(&my_game.top_hand),
+        won_text(&my_game.player1_won_cards),
+        won_text(&my_game.player2_won_cards),
+        my_game.player1_pisti_count,
+        my_game.player2_pisti_count,
+        seat_number(my_game.turn),
+        scored.player1_point,
+        scored.player2_point,
+        cards_text(&moves)
+    )
+}
+
+/// a key of the stored state and its value in `my_game`, written the way `game_to_text` writes it
+fn state_of(my_game: &Game, key: &str) -> Option<String> {
+    let text = match key {
+        "deck" => cards_text(&my_game.deck),
+        "board" => cards_text(&my_game.board),
+        "face_down" => cards_text(&my_game.face_down_cards()),
+        "player1_hand" => cards_text(&my_game.bottom_hand),
+        "player2_hand" => cards_text(&my_game.top_hand),
+        "player1_won" => won_text(&my_game.player1_won_cards),
+        "player2_won" => won_text(&my_game.player2_won_cards),
+        "player1_pistis" => my_game.player1_pisti_count.to_string(),
+        "player2_pistis" => my_game.player2_pisti_count.to_string(),
+        "turn" => seat_number(my_game.turn).to_string(),
+        _ => return None,
+    };
+    Some(text)
+}
+
+/// a stored value written like `state_of` writes it, won piles may be in any order
+fn normalized(key: &str, value: &str) -> Result<String, String> {
+    match key {
+        "player1_won" | "player2_won" => Ok(won_text(&parse_cards(value)?.into_iter().collect())),
+        "player1_pistis" | "player2_pistis" | "turn" => Ok(value.to_string()),
+        _ => Ok(cards_text(&parse_cards(value)?)),
+    }
+}
+
+/// the game is dealt again and the moves are played, the stored state has to match what they lead to
+pub fn parse_saved_game(text: &str) -> Result<SavedGame, String> {
+    let mut version = None;
+    let mut seed = None;
+    let mut rules = Rules::default();
+    let mut moves = Vec::new();
+    let mut initial_deck = None;
+    let mut reshuffles = 0;
+    let mut seat_specs = (None, None);
+    let mut state = Vec::new();
+    for (line_no, key, value) in parse_key_values(text)? {
+        let at_line = |e: String| format!("line {}: {}", line_no, e);
+        match key.as_str() {
+            "version" => {
+                version = Some(
+                    value
+                        .parse::<u32>()
+                        .map_err(|_| at_line(format!("invalid version {}", value)))?,
+                )
+            }
+            "seed" => {
+                seed = Some(
+                    value
+                        .parse::<u64>()
+                        .map_err(|_| at_line(format!("invalid seed {}", value)))?,
+                )
+            }
+            "rules" => {
+                rules = value
+                    .parse::<RulePreset>()
+                    .map_err(|_| at_line(format!("unknown rules {}", value)))?
+                    .rules()
+            }
+            "moves" => moves = parse_cards(&value).map_err(at_line)?,
+            "initial_deck" => initial_deck = Some(parse_cards(&value).map_err(at_line)?),
+            "reshuffles" => {
+                reshuffles = value
+                    .parse::<usize>()
+                    .map_err(|_| at_line(format!("invalid reshuffles {}", value)))?
+            }
+            "player1" => seat_specs.0 = Some(value),
+            "player2" => seat_specs.1 = Some(value),
+            _ => state.push((line_no, key, value)),
+        }
+    }
+    match version {
+        None => return Err(String::from("not a saved game, the version is missing")),
+        Some(v) if v > SAVE_VERSION => {
+            return Err(format!(
+                "saved by a newer version (file version {}, supported {})",
+                v, SAVE_VERSION
+            ))
+        }
+        Some(_) => {}
+    }
+    let seed = seed.ok_or("the seed is missing")?;
+    let my_game = match initial_deck {
+        Some(deck) => {
+            let all_cards: CardSet = deck.iter().copied().collect();
+            if deck.len() != 52 || all_cards.len() != 52 {
+                return Err(String::from(
+                    "the initial deck needs each of the 52 cards once",
+                ));
+            }
+            let mut my_game = Game::with_seed(seed);
+            my_game.rules = rules;
+            my_game.reshuffles = reshuffles;
+            my_game.start_game_with_deck(&deck);
+            if my_game.is_reshuffle_required() {
+                return Err(String::from(
+                    "the initial deck puts a J on top of the board",
+                ));
+            }
+            replay_moves(&mut my_game, &moves)?;
+            my_game
+        }
+        None => replay(seed, rules, &moves)?,
+    };
+    for (line_no, key, value) in state {
+        if let Some(expected) = state_of(&my_game, &key) {
+            let saved = normalized(&key, &value).map_err(|e| format!("line {}: {}", line_no, e))?;
+            if saved != expected {
+                return Err(format!(
+                    "line {}: {} does not match the moves, they lead to \"{}\"",
+                    line_no, key, expected
+                ));
+            }
+        }
+    }
+    let seats = match seat_specs {
+        (Some(player1), Some(player2)) => Some((player1, player2)),
+        _ => None,
+    };
+    Ok(SavedGame {
+        game: my_game,
+        seats,
+    })
+}
+
+/// a saved game or a game record, records have no seats
+pub fn load_game(path: &Path) -> Result<SavedGame, String> {
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let saved = if text.trim_start().starts_with('[') {
+        parse_record(&text)
+            .and_then(|record| record.to_game())
+            .map(|game| SavedGame { game, seats: None })
+    } else {
+        parse_saved_game(&text)
+    };
+    saved.map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn save_game(path: &Path, my_game: &Game, seats: &Seats) -> Result<(), String> {
+    write_file(&path.to_path_buf(), &game_to_text(my_game, seats))
+}
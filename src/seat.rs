@@ -0,0 +1,181 @@
+use crate::{
+    game::{Game, Player},
+    strategy::Strategy,
+};
+
+pub enum Seat {
+    /// plays by clicking cards on the bottom row, with the name of a profile if one was chosen
+    Human(Option<String>),
+    Ai(Box<dyn Strategy>),
+    /// a player at another computer of the LAN game, with the name it gave
+    Remote(String),
+}
+
+impl Seat {
+    pub fn is_human(&self) -> bool {
+        matches!(self, Seat::Human(_))
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Seat::Human(Some(name)) => name.clone(),
+            Seat::Human(None) => String::from("Human"),
+            Seat::Ai(strategy) => format!("AI ({})", strategy.name()),
+            Seat::Remote(name) => name.clone(),
+        }
+    }
+}
+
+/// "human", "human:<profile>", "remote:<name>" or a strategy, the way saved games and the history write a seat
+pub fn seat_spec(seat: &Seat) -> String {
+    match seat {
+        Seat::Human(None) => String::from("human"),
+        Seat::Human(Some(name)) => format!("human:{}", name),
+        Seat::Ai(strategy) => strategy.name(),
+        Seat::Remote(name) => format!("remote:{}", name),
+    }
+}
+
+/// the human seat a spec stands for, None when it is a strategy
+pub fn human_from_spec(spec: &str) -> Option<Seat> {
+    match spec.strip_prefix("human") {
+        Some("") => Some(Seat::Human(None)),
+        Some(rest) => rest
+            .strip_prefix(':')
+            .map(|name| Seat::Human(Some(name.to_string()))),
+        None => None,
+    }
+}
+
+/// the name shown for a seat spec: the profile, "Human", the remote player or "AI (<strategy>)"
+pub fn spec_name(spec: &str) -> String {
+    if let Some(name) = spec.strip_prefix("remote:") {
+        return name.to_string();
+    }
+    match human_from_spec(spec) {
+        Some(seat) => seat.name(),
+        None => format!("AI ({})", spec),
+    }
+}
+
+/// seat of Player1 and Player2
+pub struct Seats {
+    pub player1: Seat,
+    pub player2: Seat,
+}
+
+impl Seats {
+    pub fn new(player1: Seat, player2: Seat) -> Self {
+        Seats { player1, player2 }
+    }
+
+    pub fn get(&self, a_player: Player) -> &Seat {
+        match a_player {
+            Player::Player1 => &self.player1,
+            Player::Player2 => &self.player2,
+        }
+    }
+
+    pub fn get_mut(&mut self, a_player: Player) -> &mut Seat {
+        match a_player {
+            Player::Player1 => &mut self.player1,
+            Player::Player2 => &mut self.player2,
+        }
+    }
+
+    /// the player shown on the bottom row: the first human seat, otherwise Player1
+    pub fn bottom_player(&self) -> Player {
+        if !self.player1.is_human() && self.player2.is_human() {
+            Player::Player2
+        } else {
+            Player::Player1
+        }
+    }
+
+    pub fn human_count(&self) -> usize {
+        [&self.player1, &self.player2]
+            .iter()
+            .filter(|s| s.is_human())
+            .count()
+    }
+
+    /// asks the strategy of `a_player` for a card, None if the seat is not an AI
+    pub fn pick_card_for_ai(&mut self, game: &Game, a_player: Player) -> Option<usize> {
+        match self.get_mut(a_player) {
+            Seat::Ai(strategy) => Some(strategy.pick_card(game, a_player)),
+            Seat::Human(_) | Seat::Remote(_) => None,
+        }
+    }
+
+    /// lets the AI seats know which of them play against a human here, before the game
+    pub fn meet_humans(&mut self) {
+        for a_player in [Player::Player1, Player::Player2] {
+            let profile = match self.get(a_player.other()) {
+                Seat::Human(profile) => profile.clone(),
+                _ => continue,
+            };
+            if let Seat::Ai(strategy) = self.get_mut(a_player) {
+                strategy.against_human(profile.as_deref());
+            }
+        }
+    }
+
+    /// lets the AI seats know the final points
+    pub fn game_over(&mut self, game: &Game) {
+        for a_player in [Player::Player1, Player::Player2] {
+            if let Seat::Ai(strategy) = self.get_mut(a_player) {
+                strategy.game_over(game, a_player);
+            }
+        }
+    }
+}
+
+/// the result at the end, a human without a profile is "you"
+pub fn game_over_text(my_game: &Game, seats: &Seats) -> String {
+    let score = |a_player: Player| match a_player {
+        Player::Player1 => (my_game.player1_point, my_game.player1_pisti_count),
+        Player::Player2 => (my_game.player2_point, my_game.player2_pisti_count),
+    };
+    let me = seats.bottom_player();
+    if seats.human_count() == 1 && matches!(seats.get(me), Seat::Human(None)) {
+        let ((my_point, my_pisti), (ai_point, ai_pisti)) = (score(me), score(me.other()));
+        let mytxt = if my_point > ai_point {
+            "You won"
+        } else if my_point == ai_point {
+            "Draw"
+        } else {
+            "You lost"
+        };
+        format!(
+            "{} - Your points: {}({} Pisti) -- {}: {}({} Pisti)",
+            mytxt,
+            my_point,
+            my_pisti,
+            seats.get(me.other()).name(),
+            ai_point,
+            ai_pisti
+        )
+    } else {
+        let ((p1_point, p1_pisti), (p2_point, p2_pisti)) =
+            (score(Player::Player1), score(Player::Player2));
+        let (name1, name2) = (seats.player1.name(), seats.player2.name());
+        let winner = |name: &str, number: u8| {
+            if name1 == name2 {
+                format!("Player {} won", number)
+            } else {
+                format!("{} won", name)
+            }
+        };
+        let mytxt = if p1_point > p2_point {
+            winner(&name1, 1)
+        } else if p1_point == p2_point {
+            String::from("Draw")
+        } else {
+            winner(&name2, 2)
+        };
+        format!(
+            "{} - {}: {}({} Pisti) -- {}: {}({} Pisti)",
+            mytxt, name1, p1_point, p1_pisti, name2, p2_point, p2_pisti
+        )
+    }
+}
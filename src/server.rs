@@ -0,0 +1,806 @@
+//! `pistiflex-server`, many tables of the LAN protocol of `src/net.rs` without any window.
+//!
+//! Every connection has a thread that serves the lobby. Every table has a thread that owns
+//! its `Game` and deals it like the host of a LAN game: the AI seats move as soon as both
+//! seats are taken, the moves of the players come from their connections as `TableEvent`s.
+//! After join only the table writes to the connection of the player.
+//!
+//! A connection that starts with `GET ` is a WebSocket, see `src/websocket.rs`, it gets the
+//! same messages as JSON.
+//!
+//! A player that joins gets a session token. When its connection drops before the game is
+//! over the seat is paused, the player can take it back with `resume <token>` from the
+//! lobby and gets the whole game again. After `grace` the `takeover` AI plays the seat.
+//!
+//! The table pings its players every `heartbeat`. A connection that dropped without a word
+//! reads nothing, after `MISSED_HEARTBEATS` its seat is paused like that of a closed one.
+
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    game::{Card, DealStatus, Game, Player, RulePreset},
+    net::{
+        handshake_timeout, message_to_line, read_answering, read_hello, read_on, seat_guest,
+        send_on, NetMessage, RemoteSeat, TableInfo, TableStatus, Wire, DEFAULT_PORT,
+    },
+    strategy::{Strategy, StrategyKind},
+    websocket::accept_websocket,
+};
+
+pub const USAGE: &str = "usage: pistiflex-server [options]
+
+options:
+  --port <port>         listen on this port for lines and WebSockets, 7878 without it,
+                        0 for any free port
+  --max-tables <n>      at most this many tables at once, 100 without it
+  --name <name>         the name the players see, pistiflex without it
+  --grace <seconds>     how long the seat of a dropped player waits for it, 60 without it
+  --takeover <strategy> the AI that plays the seat after that, basic without it
+  --heartbeat <seconds> how often a seated player is pinged, 10 without it
+  --help                show this";
+
+pub const DEFAULT_MAX_TABLES: usize = 100;
+pub const DEFAULT_GRACE: Duration = Duration::from_secs(60);
+pub const DEFAULT_HEARTBEAT: Duration = Duration::from_secs(10);
+/// a seated player that answers none of this many pings has dropped
+const MISSED_HEARTBEATS: u32 = 3;
+/// a connection that says nothing in the lobby for this long is closed
+const LOBBY_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Clone, Debug)]
+pub struct ServerConfig {
+    pub name: String,
+    pub max_tables: usize,
+    /// how long the seat of a player whose connection dropped waits for it
+    pub grace: Duration,
+    /// plays the seat when nobody came back
+    pub takeover: StrategyKind,
+    /// how often the players at a table are pinged
+    pub heartbeat: Duration,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            name: String::from("pistiflex"),
+            max_tables: DEFAULT_MAX_TABLES,
+            grace: DEFAULT_GRACE,
+            takeover: StrategyKind::Basic,
+            heartbeat: DEFAULT_HEARTBEAT,
+        }
+    }
+}
+
+/// what a connection tells its table, `connection` is the number the server gave it
+pub enum TableEvent {
+    /// the answer goes to `reply`, the table keeps the stream when it is Ok
+    Join {
+        seat: Player,
+        name: String,
+        stream: TcpStream,
+        wire: Wire,
+        connection: usize,
+        reply: Sender<Result<(), String>>,
+    },
+    /// like join, for the seat of `token`
+    Resume {
+        seat: Player,
+        token: String,
+        stream: TcpStream,
+        wire: Wire,
+        connection: usize,
+        reply: Sender<Result<(), String>>,
+    },
+    Play {
+        seat: Player,
+        card: Card,
+        connection: usize,
+    },
+    /// a WebSocket ping of the player, the table writes the pong
+    Ping {
+        seat: Player,
+        payload: Vec<u8>,
+        connection: usize,
+    },
+    /// `dropped` when the connection failed instead of saying bye
+    Left {
+        seat: Player,
+        connection: usize,
+        dropped: bool,
+    },
+    /// the connection that created the table left the lobby
+    CreatorLeft,
+}
+
+struct TableEntry {
+    info: TableInfo,
+    events: Sender<TableEvent>,
+}
+
+#[derive(Default)]
+struct Lobby {
+    tables: BTreeMap<usize, TableEntry>,
+    next_id: usize,
+}
+
+type SharedLobby = Arc<Mutex<Lobby>>;
+
+/// a poisoned lobby is still used, a table thread that panicked only loses its table
+fn lock(lobby: &SharedLobby) -> MutexGuard<'_, Lobby> {
+    lobby.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// a player at a table, the events of other connections for the seat are old ones
+struct Seated {
+    remote: RemoteSeat,
+    token: String,
+    connection: usize,
+}
+
+enum TableSeat {
+    Open,
+    Ai(StrategyKind, Box<dyn Strategy>),
+    Player(Seated),
+    /// the connection of the player dropped, the AI takes the seat at `deadline`
+    Paused {
+        name: String,
+        token: String,
+        deadline: Instant,
+    },
+}
+
+impl TableSeat {
+    /// the word of the seat in the list of tables
+    fn word(&self) -> String {
+        match self {
+            TableSeat::Open => String::from("-"),
+            TableSeat::Ai(kind, _) => format!("ai:{}", kind),
+            TableSeat::Player(seated) => seated.remote.name.replace(' ', "_"),
+            TableSeat::Paused { name, .. } => name.replace(' ', "_"),
+        }
+    }
+}
+
+struct Table {
+    id: usize,
+    game: Game,
+    seats: [TableSeat; 2],
+    /// a player sat down once, the table is closed when the last one leaves
+    joined: bool,
+    /// its creator left before anybody sat down, nobody is waiting for it
+    forsaken: bool,
+    grace: Duration,
+    takeover: StrategyKind,
+    heartbeat: Duration,
+    next_ping: Instant,
+}
+
+fn seat_index(seat: Player) -> usize {
+    match seat {
+        Player::Player1 => 0,
+        Player::Player2 => 1,
+    }
+}
+
+/// `<table>-<seat>-<random>`, the lobby finds the table by it
+fn new_token(id: usize, seat: Player) -> String {
+    format!(
+        "{}-{}-{:016x}",
+        id,
+        seat_index(seat) + 1,
+        rand::random::<u64>()
+    )
+}
+
+/// the table and the seat of a token
+fn token_seat(token: &str) -> Option<(usize, Player)> {
+    let mut parts = token.split('-');
+    let id = parts.next()?.parse().ok()?;
+    let seat = match parts.next()? {
+        "1" => Player::Player1,
+        "2" => Player::Player2,
+        _ => return None,
+    };
+    Some((id, seat))
+}
+
+impl Table {
+    fn info(&self) -> TableInfo {
+        let status = if self.game.is_game_over() {
+            TableStatus::Over
+        } else if self.seats.iter().any(|s| matches!(s, TableSeat::Open)) {
+            TableStatus::Waiting
+        } else if self
+            .seats
+            .iter()
+            .any(|s| matches!(s, TableSeat::Paused { .. }))
+        {
+            TableStatus::Paused
+        } else {
+            TableStatus::Playing
+        };
+        TableInfo {
+            id: self.id,
+            rules: self.game.rules.preset,
+            seats: (self.seats[0].word(), self.seats[1].word()),
+            status,
+        }
+    }
+
+    fn play(&mut self, player: Player, hand_index: usize) {
+        self.game.play_turn(player, hand_index);
+        if self.game.next_deal() == DealStatus::GameOver {
+            println!(
+                "table {}: {} - {}",
+                self.id, self.game.player1_point, self.game.player2_point
+            );
+        }
+    }
+
+    /// the AI seats move while it is their turn, nobody moves while a seat is open
+    fn play_ai_turns(&mut self) {
+        while !self.game.is_game_over() && !self.seats.iter().any(|s| matches!(s, TableSeat::Open))
+        {
+            let player = self.game.turn;
+            let TableSeat::Ai(_, strategy) = &mut self.seats[seat_index(player)] else {
+                break;
+            };
+            let hand_index = strategy.pick_card(&self.game, player);
+            self.play(player, hand_index);
+        }
+    }
+
+    /// a player whose connection failed is paused like one whose connection dropped
+    fn send_updates(&mut self) {
+        for index in 0..self.seats.len() {
+            if let TableSeat::Player(seated) = &mut self.seats[index] {
+                seated.remote.send_updates(&self.game);
+                if !seated.remote.connected {
+                    self.leave(index, true);
+                }
+            }
+        }
+    }
+
+    /// pings the players when the heartbeat is due
+    fn send_pings(&mut self) {
+        if Instant::now() < self.next_ping {
+            return;
+        }
+        self.next_ping = Instant::now() + self.heartbeat;
+        for seat in self.seats.iter_mut() {
+            if let TableSeat::Player(seated) = seat {
+                seated.remote.ping();
+            }
+        }
+    }
+
+    /// the earliest deadline of a paused seat, or the next ping
+    fn deadline(&self) -> Option<Instant> {
+        self.seats
+            .iter()
+            .filter_map(|s| match s {
+                TableSeat::Paused { deadline, .. } => Some(*deadline),
+                TableSeat::Player(_) => Some(self.next_ping),
+                _ => None,
+            })
+            .min()
+    }
+
+    /// the seat of a player is paused when its connection `dropped` before the game is over,
+    /// otherwise it is open again
+    fn leave(&mut self, index: usize, dropped: bool) {
+        let TableSeat::Player(seated) = &self.seats[index] else {
+            return;
+        };
+        let name = seated.remote.name.clone();
+        if dropped && !self.game.is_game_over() {
+            println!(
+                "table {}: {} dropped, the seat waits {}s",
+                self.id,
+                name,
+                self.grace.as_secs_f32()
+            );
+            self.seats[index] = TableSeat::Paused {
+                name,
+                token: seated.token.clone(),
+                deadline: Instant::now() + self.grace,
+            };
+        } else {
+            println!("table {}: {} left", self.id, name);
+            self.seats[index] = TableSeat::Open;
+        }
+    }
+
+    /// the AI plays the paused seats whose player did not come back in time
+    fn take_over_expired(&mut self) {
+        let now = Instant::now();
+        for seat in self.seats.iter_mut() {
+            if let TableSeat::Paused { name, deadline, .. } = seat {
+                if *deadline <= now {
+                    println!("table {}: ai:{} plays for {}", self.id, self.takeover, name);
+                    *seat = TableSeat::Ai(self.takeover, self.takeover.create());
+                }
+            }
+        }
+    }
+
+    fn handle(&mut self, event: TableEvent) {
+        match event {
+            TableEvent::Join {
+                seat,
+                name,
+                stream,
+                wire,
+                connection,
+                reply,
+            } => {
+                let index = seat_index(seat);
+                let answer = match self.seats[index] {
+                    TableSeat::Open => {
+                        let host = format!("table {}", self.id);
+                        let token = new_token(self.id, seat);
+                        seat_guest(stream, wire, &name, &self.game, seat, &host, Some(&token)).map(
+                            |remote| {
+                                self.seats[index] = TableSeat::Player(Seated {
+                                    remote,
+                                    token,
+                                    connection,
+                                });
+                                self.joined = true;
+                            },
+                        )
+                    }
+                    _ => Err(String::from("the seat is taken")),
+                };
+                let _ = reply.send(answer);
+            }
+            TableEvent::Resume {
+                seat,
+                token,
+                stream,
+                wire,
+                connection,
+                reply,
+            } => {
+                let index = seat_index(seat);
+                let name = match &self.seats[index] {
+                    TableSeat::Paused { name, token: t, .. } if *t == token => Some(name.clone()),
+                    TableSeat::Player(seated) if seated.token == token => {
+                        Some(seated.remote.name.clone())
+                    }
+                    _ => None,
+                };
+                let answer = match name {
+                    Some(name) => {
+                        let host = format!("table {}", self.id);
+                        seat_guest(stream, wire, &name, &self.game, seat, &host, Some(&token)).map(
+                            |remote| {
+                                println!("table {}: {} is back", self.id, name);
+                                self.seats[index] = TableSeat::Player(Seated {
+                                    remote,
+                                    token,
+                                    connection,
+                                });
+                            },
+                        )
+                    }
+                    None => Err(String::from("the session is over")),
+                };
+                let _ = reply.send(answer);
+            }
+            TableEvent::Play {
+                seat,
+                card,
+                connection,
+            } => {
+                let TableSeat::Player(seated) = &mut self.seats[seat_index(seat)] else {
+                    return;
+                };
+                if seated.connection != connection {
+                    return;
+                }
+                let hand_index = self
+                    .game
+                    .get_player_cards(seat)
+                    .iter()
+                    .position(|c| *c == card);
+                match hand_index {
+                    Some(hand_index) if self.game.turn == seat && !self.game.is_game_over() => {
+                        self.play(seat, hand_index)
+                    }
+                    _ => seated.remote.illegal(card),
+                }
+            }
+            TableEvent::Ping {
+                seat,
+                payload,
+                connection,
+            } => {
+                if let TableSeat::Player(seated) = &mut self.seats[seat_index(seat)] {
+                    if seated.connection == connection {
+                        seated.remote.pong(&payload);
+                    }
+                }
+            }
+            TableEvent::Left {
+                seat,
+                connection,
+                dropped,
+            } => {
+                let index = seat_index(seat);
+                match &self.seats[index] {
+                    TableSeat::Player(seated) if seated.connection == connection => {
+                        self.leave(index, dropped)
+                    }
+                    _ => {}
+                }
+            }
+            TableEvent::CreatorLeft => self.forsaken = !self.joined,
+        }
+    }
+
+    /// a paused seat still counts while the game goes on
+    fn is_abandoned(&self) -> bool {
+        let over = self.game.is_game_over();
+        let players = self.seats.iter().any(|s| match s {
+            TableSeat::Player(_) => true,
+            TableSeat::Paused { .. } => !over,
+            _ => false,
+        });
+        !players && (self.joined || self.forsaken || over)
+    }
+}
+
+/// deals the game of the table and plays it until the last player leaves
+fn run_table(mut table: Table, events: Receiver<TableEvent>, lobby: SharedLobby) {
+    loop {
+        table.play_ai_turns();
+        table.send_updates();
+        let info = table.info();
+        {
+            let mut lobby = lock(&lobby);
+            if table.is_abandoned() {
+                lobby.tables.remove(&table.id);
+                break;
+            }
+            if let Some(entry) = lobby.tables.get_mut(&table.id) {
+                entry.info = info;
+            }
+        }
+        let event = match table.deadline() {
+            Some(deadline) => {
+                match events.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(event) => Some(event),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            None => match events.recv() {
+                Ok(event) => Some(event),
+                Err(_) => break,
+            },
+        };
+        if let Some(event) = event {
+            table.handle(event);
+        }
+        table.take_over_expired();
+        table.send_pings();
+    }
+    println!("table {} closed", table.id);
+}
+
+/// a new table, its thread runs until the last player leaves
+fn create_table(
+    lobby: &SharedLobby,
+    config: &ServerConfig,
+    rules: RulePreset,
+    seats: (Option<StrategyKind>, Option<StrategyKind>),
+) -> Result<usize, String> {
+    if seats.0.is_some() && seats.1.is_some() {
+        return Err(String::from("a table needs an open seat"));
+    }
+    let mut game = Game::new();
+    game.rules = rules.rules();
+    game.start_game_and_give_cards_to_players();
+    let seat = |kind: Option<StrategyKind>| match kind {
+        Some(kind) => TableSeat::Ai(kind, kind.create()),
+        None => TableSeat::Open,
+    };
+    let mut guard = lock(lobby);
+    if guard.tables.len() >= config.max_tables {
+        return Err(format!("the server has {} tables", config.max_tables));
+    }
+    guard.next_id += 1;
+    let id = guard.next_id;
+    let table = Table {
+        id,
+        game,
+        seats: [seat(seats.0), seat(seats.1)],
+        joined: false,
+        forsaken: false,
+        grace: config.grace,
+        takeover: config.takeover,
+        heartbeat: config.heartbeat,
+        next_ping: Instant::now() + config.heartbeat,
+    };
+    let (sender, receiver) = mpsc::channel();
+    guard.tables.insert(
+        id,
+        TableEntry {
+            info: table.info(),
+            events: sender,
+        },
+    );
+    drop(guard);
+    let table_lobby = Arc::clone(lobby);
+    thread::spawn(move || run_table(table, receiver, table_lobby));
+    println!("table {} created", id);
+    Ok(id)
+}
+
+/// hands the connection to the table with the event `seat_event` makes, waits until the
+/// welcome was sent
+fn sit_at_table(
+    lobby: &SharedLobby,
+    id: usize,
+    seat_event: impl FnOnce(Sender<Result<(), String>>) -> TableEvent,
+) -> Result<Sender<TableEvent>, String> {
+    let events = lock(lobby)
+        .tables
+        .get(&id)
+        .map(|entry| entry.events.clone())
+        .ok_or(format!("there is no table {}", id))?;
+    let (reply, answer) = mpsc::channel();
+    events
+        .send(seat_event(reply))
+        .map_err(|_| format!("table {} was closed", id))?;
+    answer
+        .recv()
+        .map_err(|_| format!("table {} was closed", id))??;
+    Ok(events)
+}
+
+/// passes the moves of a seated player to its table until the player leaves, only the table
+/// writes to the connection, pongs too
+fn play_at_table(
+    reader: &mut BufReader<TcpStream>,
+    wire: Wire,
+    events: Sender<TableEvent>,
+    seat: Player,
+    connection: usize,
+) {
+    loop {
+        let message = read_answering(reader, wire, |_, payload| {
+            events
+                .send(TableEvent::Ping {
+                    seat,
+                    payload: payload.to_vec(),
+                    connection,
+                })
+                .map_err(|_| String::from("the table was closed"))
+        });
+        let event = match message {
+            Ok(NetMessage::Play(card)) => TableEvent::Play {
+                seat,
+                card,
+                connection,
+            },
+            Ok(NetMessage::Bye) => TableEvent::Left {
+                seat,
+                connection,
+                dropped: false,
+            },
+            Err(_) => TableEvent::Left {
+                seat,
+                connection,
+                dropped: true,
+            },
+            Ok(_) => continue,
+        };
+        let left = matches!(event, TableEvent::Left { .. });
+        if events.send(event).is_err() || left {
+            return;
+        }
+    }
+}
+
+/// the lobby of one connection, until it says bye or sits down at a table
+fn serve_client(
+    mut stream: TcpStream,
+    lobby: SharedLobby,
+    config: Arc<ServerConfig>,
+    connection: usize,
+) {
+    let Ok(read_stream) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(read_stream);
+    if stream.set_read_timeout(handshake_timeout()).is_err() {
+        return;
+    }
+    let wire = match reader.fill_buf() {
+        Ok(start) if start.first() == Some(&b'G') => {
+            match accept_websocket(&mut stream, &mut reader) {
+                Ok(()) => Wire::WebSocket,
+                Err(e) => {
+                    println!("{}", e);
+                    return;
+                }
+            }
+        }
+        Ok(_) => Wire::Lines,
+        Err(_) => return,
+    };
+    let name = match read_hello(&mut stream, &mut reader, wire) {
+        Ok(name) => name,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    if stream.set_read_timeout(Some(LOBBY_IDLE_TIMEOUT)).is_err()
+        || send_on(&mut stream, wire, &NetMessage::Lobby(config.name.clone())).is_err()
+    {
+        return;
+    }
+    let mut created = Vec::new();
+    let seated = 'lobby: loop {
+        let Ok(message) = read_on(&mut reader, wire) else {
+            break None;
+        };
+        let answer = match message {
+            NetMessage::List => {
+                let tables: Vec<TableInfo> = lock(&lobby)
+                    .tables
+                    .values()
+                    .map(|entry| entry.info.clone())
+                    .collect();
+                let mut answer = vec![NetMessage::Tables(tables.len())];
+                answer.extend(tables.into_iter().map(NetMessage::Table));
+                answer
+            }
+            NetMessage::Create { rules, seats } => {
+                match create_table(&lobby, &config, rules, seats) {
+                    Ok(id) => {
+                        created.push(id);
+                        vec![NetMessage::Created(id)]
+                    }
+                    Err(e) => vec![NetMessage::Error(e)],
+                }
+            }
+            NetMessage::Join { id, seat } => {
+                let joined = stream
+                    .try_clone()
+                    .map_err(|e| e.to_string())
+                    .and_then(|stream| {
+                        sit_at_table(&lobby, id, |reply| TableEvent::Join {
+                            seat,
+                            name: name.clone(),
+                            stream,
+                            wire,
+                            connection,
+                            reply,
+                        })
+                    });
+                match joined {
+                    Ok(events) => break Some((events, seat)),
+                    Err(e) => vec![NetMessage::Error(e)],
+                }
+            }
+            NetMessage::Resume(token) => {
+                let resumed = token_seat(&token)
+                    .ok_or(String::from("the session is over"))
+                    .and_then(|(id, seat)| {
+                        let stream = stream.try_clone().map_err(|e| e.to_string())?;
+                        let events = sit_at_table(&lobby, id, |reply| TableEvent::Resume {
+                            seat,
+                            token,
+                            stream,
+                            wire,
+                            connection,
+                            reply,
+                        })?;
+                        Ok((events, seat))
+                    });
+                match resumed {
+                    Ok(seated) => break Some(seated),
+                    Err(e) => vec![NetMessage::Error(e)],
+                }
+            }
+            NetMessage::Bye => break None,
+            other => vec![NetMessage::Error(format!(
+                "unexpected in the lobby: {}",
+                message_to_line(&other)
+            ))],
+        };
+        for message in answer {
+            if send_on(&mut stream, wire, &message).is_err() {
+                break 'lobby None;
+            }
+        }
+    };
+    // a table nobody sat down at would wait for a player forever
+    for id in created {
+        if let Some(entry) = lock(&lobby).tables.get(&id) {
+            let _ = entry.events.send(TableEvent::CreatorLeft);
+        }
+    }
+    if let Some((events, seat)) = seated {
+        // the pongs keep the connection busy, silence means it dropped
+        let _ = stream.set_read_timeout(Some(config.heartbeat * MISSED_HEARTBEATS));
+        play_at_table(&mut reader, wire, events, seat, connection);
+    }
+}
+
+/// accepts players until the listener fails, every connection gets a thread
+pub fn run_server(listener: TcpListener, config: ServerConfig) -> Result<(), String> {
+    let lobby = SharedLobby::default();
+    let config = Arc::new(config);
+    for (connection, stream) in listener.incoming().enumerate() {
+        let stream = stream.map_err(|e| format!("cannot accept a player: {}", e))?;
+        let (lobby, config) = (Arc::clone(&lobby), Arc::clone(&config));
+        thread::spawn(move || serve_client(stream, lobby, config, connection));
+    }
+    Ok(())
+}
+
+/// `pistiflex-server [--port <port>] [--max-tables <n>] [--name <name>] [--grace <seconds>]
+/// [--takeover <strategy>] [--heartbeat <seconds>]`
+pub fn server_main(args: &[String]) -> Result<(), String> {
+    let mut port = DEFAULT_PORT;
+    let mut config = ServerConfig::default();
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        if arg == "--help" {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        let value = args_iter
+            .next()
+            .ok_or(format!("{} needs a value\n{}", arg, USAGE))?;
+        match arg.as_str() {
+            "--port" => port = value.parse().map_err(|_| "--port needs a port number")?,
+            "--max-tables" => {
+                config.max_tables = value.parse().map_err(|_| "--max-tables needs a number")?
+            }
+            "--name" => config.name = value.clone(),
+            "--grace" => {
+                config.grace = value
+                    .parse()
+                    .map(Duration::from_secs)
+                    .map_err(|_| "--grace needs a number of seconds")?
+            }
+            "--takeover" => {
+                config.takeover = value
+                    .parse()
+                    .map_err(|_| format!("unknown strategy {}\n{}", value, USAGE))?
+            }
+            "--heartbeat" => {
+                config.heartbeat = value
+                    .parse()
+                    .ok()
+                    .filter(|seconds| *seconds > 0)
+                    .map(Duration::from_secs)
+                    .ok_or("--heartbeat needs a number of seconds above 0")?
+            }
+            _ => return Err(format!("unknown option {}\n{}", arg, USAGE)),
+        }
+    }
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .map_err(|e| format!("cannot listen on port {}: {}", port, e))?;
+    if let Ok(address) = listener.local_addr() {
+        println!("{} is listening on {}", config.name, address);
+    }
+    run_server(listener, config)
+}
@@ -0,0 +1,148 @@
+use std::{fs, path::PathBuf};
+
+use crate::{
+    game::RulePreset,
+    profiles::check_name,
+    storage::{config_dir, key_value_lines, write_file},
+    strategy::Difficulty,
+};
+
+pub const DEFAULT_ANIM_SPEED: u8 = if cfg!(windows) { 9 } else { 6 };
+
+pub const THEMES: [&str; 9] = [
+    "classic",
+    "aero",
+    "metro",
+    "aqua",
+    "greybird",
+    "blue",
+    "dark",
+    "high-contrast",
+    "fluent",
+];
+pub const CARD_BACKS: [&str; 2] = ["red", "black"];
+/// the texts of the game are only in English for now
+pub const LANGUAGES: [&str; 1] = ["en"];
+
+/// what the game remembers between launches
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub anim_speed: u8,
+    pub rules: RulePreset,
+    pub difficulty: Difficulty,
+    pub theme: String,
+    pub card_back: String,
+    /// x, y, width and height of the window when it was closed
+    pub window: Option<(i32, i32, i32, i32)>,
+    pub language: String,
+    /// the profile chosen last time
+    pub profile: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            anim_speed: DEFAULT_ANIM_SPEED,
+            rules: RulePreset::Standard,
+            difficulty: Difficulty::Normal,
+            theme: String::from("metro"),
+            card_back: String::from("red"),
+            window: None,
+            language: String::from("en"),
+            profile: None,
+        }
+    }
+}
+
+fn one_of(list: &[&str], value: &str) -> Option<String> {
+    list.iter().find(|v| **v == value).map(|v| v.to_string())
+}
+
+fn parse_window(value: &str) -> Option<(i32, i32, i32, i32)> {
+    let numbers: Vec<i32> = value
+        .split_whitespace()
+        .map(|n| n.parse().ok())
+        .collect::<Option<Vec<i32>>>()?;
+    match numbers[..] {
+        [x, y, w, h] if w > 0 && h > 0 => Some((x, y, w, h)),
+        _ => None,
+    }
+}
+
+impl Settings {
+    /// unknown keys and bad lines are skipped, a bad value keeps the default of its key
+    pub fn parse(text: &str) -> Self {
+        let mut settings = Settings::default();
+        for pair in key_value_lines(text) {
+            let (line_no, key, value) = match pair {
+                Ok(pair) => pair,
+                Err(e) => {
+                    println!("settings {}, skipped", e);
+                    continue;
+                }
+            };
+            let valid = match key.as_str() {
+                "anim_speed" => match value.parse::<u8>() {
+                    Ok(speed @ 1..=9) => {
+                        settings.anim_speed = speed;
+                        true
+                    }
+                    _ => false,
+                },
+                "rules" => value.parse().map(|r| settings.rules = r).is_ok(),
+                "difficulty" => value.parse().map(|d| settings.difficulty = d).is_ok(),
+                "theme" => one_of(&THEMES, &value)
+                    .map(|t| settings.theme = t)
+                    .is_some(),
+                "card_back" => one_of(&CARD_BACKS, &value)
+                    .map(|c| settings.card_back = c)
+                    .is_some(),
+                "window" => parse_window(&value)
+                    .map(|w| settings.window = Some(w))
+                    .is_some(),
+                "language" => one_of(&LANGUAGES, &value)
+                    .map(|l| settings.language = l)
+                    .is_some(),
+                "profile" => check_name(&value)
+                    .map(|p| settings.profile = Some(p))
+                    .is_ok(),
+                _ => true,
+            };
+            if !valid {
+                println!("settings line {}: invalid {} {}", line_no, key, value);
+            }
+        }
+        settings
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "anim_speed = {}\nrules = {}\ndifficulty = {}\ntheme = {}\ncard_back = {}\nlanguage = {}\n",
+            self.anim_speed, self.rules, self.difficulty, self.theme, self.card_back, self.language
+        );
+        if let Some((x, y, w, h)) = self.window {
+            text.push_str(&format!("window = {} {} {} {}\n", x, y, w, h));
+        }
+        if let Some(profile) = &self.profile {
+            text.push_str(&format!("profile = {}\n", profile));
+        }
+        text
+    }
+}
+
+pub fn settings_path() -> PathBuf {
+    config_dir().join("settings.txt")
+}
+
+pub fn load_settings() -> Settings {
+    match fs::read_to_string(settings_path()) {
+        Ok(text) => Settings::parse(&text),
+        Err(_) => Settings::default(),
+    }
+}
+
+pub fn save_settings(settings: &Settings) {
+    if let Err(e) = write_file(&settings_path(), &settings.to_text()) {
+        println!("cannot save the settings: {}", e);
+    }
+}
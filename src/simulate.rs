@@ -0,0 +1,246 @@
+use std::{num::NonZeroUsize, thread};
+
+use crate::{
+    game::{Game, RulePreset, Rules},
+    harness::play_ai_game,
+    seat::{Seat, Seats},
+    strategy::strategy_from_spec,
+};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimulationStats {
+    /// game i is dealt with seed + i
+    pub seed: u64,
+    pub games: usize,
+    pub p1_wins: usize,
+    pub p2_wins: usize,
+    pub draws: usize,
+    pub p1_points: usize,
+    pub p2_points: usize,
+    pub p1_pistis: usize,
+    pub p2_pistis: usize,
+    /// games where the opening board had a J on top and the deck was shuffled again
+    pub reshuffled_games: usize,
+    pub reshuffles: usize,
+}
+
+impl SimulationStats {
+    fn add_game(&mut self, my_game: &Game) {
+        self.games += 1;
+        self.p1_points += my_game.player1_point;
+        self.p2_points += my_game.player2_point;
+        self.p1_pistis += my_game.player1_pisti_count as usize;
+        self.p2_pistis += my_game.player2_pisti_count as usize;
+        if my_game.player1_point > my_game.player2_point {
+            self.p1_wins += 1;
+        } else if my_game.player1_point < my_game.player2_point {
+            self.p2_wins += 1;
+        } else {
+            self.draws += 1;
+        }
+        if my_game.reshuffles > 0 {
+            self.reshuffled_games += 1;
+        }
+        self.reshuffles += my_game.reshuffles;
+    }
+
+    fn merge(&mut self, other: &SimulationStats) {
+        self.games += other.games;
+        self.p1_wins += other.p1_wins;
+        self.p2_wins += other.p2_wins;
+        self.draws += other.draws;
+        self.p1_points += other.p1_points;
+        self.p2_points += other.p2_points;
+        self.p1_pistis += other.p1_pistis;
+        self.p2_pistis += other.p2_pistis;
+        self.reshuffled_games += other.reshuffled_games;
+        self.reshuffles += other.reshuffles;
+    }
+
+    fn per_game(&self, value: usize) -> f64 {
+        value as f64 / self.games.max(1) as f64
+    }
+
+    /// player 1 always plays the first card, draws count as half a win
+    pub fn first_player_score(&self) -> f64 {
+        (self.p1_wins as f64 + self.draws as f64 / 2.0) / self.games.max(1) as f64
+    }
+
+    pub fn first_player_point_difference(&self) -> f64 {
+        self.per_game(self.p1_points) - self.per_game(self.p2_points)
+    }
+
+    pub fn reshuffle_rate(&self) -> f64 {
+        self.per_game(self.reshuffled_games)
+    }
+
+    fn values(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("seed", self.seed.to_string()),
+            ("games", self.games.to_string()),
+            ("p1_wins", self.p1_wins.to_string()),
+            ("p2_wins", self.p2_wins.to_string()),
+            ("draws", self.draws.to_string()),
+            ("p1_points", self.p1_points.to_string()),
+            ("p2_points", self.p2_points.to_string()),
+            (
+                "p1_average_points",
+                format!("{:.4}", self.per_game(self.p1_points)),
+            ),
+            (
+                "p2_average_points",
+                format!("{:.4}", self.per_game(self.p2_points)),
+            ),
+            ("p1_pistis", self.p1_pistis.to_string()),
+            ("p2_pistis", self.p2_pistis.to_string()),
+            (
+                "p1_average_pistis",
+                format!("{:.4}", self.per_game(self.p1_pistis)),
+            ),
+            (
+                "p2_average_pistis",
+                format!("{:.4}", self.per_game(self.p2_pistis)),
+            ),
+            (
+                "first_player_score",
+                format!("{:.4}", self.first_player_score()),
+            ),
+            (
+                "first_player_point_difference",
+                format!("{:.4}", self.first_player_point_difference()),
+            ),
+            ("reshuffled_games", self.reshuffled_games.to_string()),
+            ("reshuffles", self.reshuffles.to_string()),
+            ("reshuffle_rate", format!("{:.4}", self.reshuffle_rate())),
+        ]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    pub games: usize,
+    pub seed: u64,
+    pub p1: String,
+    pub p2: String,
+    pub rules: Rules,
+    pub threads: usize,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            games: 1000,
+            seed: 0,
+            p1: String::from("basic"),
+            p2: String::from("basic"),
+            rules: Rules::default(),
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        }
+    }
+}
+
+/// plays games `seed + first..seed + last` with new strategies for the thread
+fn simulate_games(
+    config: &SimulationConfig,
+    first: usize,
+    last: usize,
+) -> Result<SimulationStats, String> {
+    let mut seats = Seats::new(
+        Seat::Ai(strategy_from_spec(&config.p1)?),
+        Seat::Ai(strategy_from_spec(&config.p2)?),
+    );
+    let mut stats = SimulationStats::default();
+    for i in first..last {
+        let seed = config.seed.wrapping_add(i as u64);
+        stats.add_game(&play_ai_game(&mut seats, seed, config.rules));
+    }
+    Ok(stats)
+}
+
+/// splits the games between the threads, every game keeps its seed whatever the thread count
+pub fn run_simulation(config: &SimulationConfig) -> Result<SimulationStats, String> {
+    let threads = config.threads.clamp(1, config.games.max(1));
+    let chunk = config.games.div_ceil(threads);
+    let results: Vec<Result<SimulationStats, String>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let first = (t * chunk).min(config.games);
+                let last = ((t + 1) * chunk).min(config.games);
+                scope.spawn(move || simulate_games(config, first, last))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| {
+                h.join()
+                    .unwrap_or(Err(String::from("a simulation thread panicked")))
+            })
+            .collect()
+    });
+    let mut stats = SimulationStats {
+        seed: config.seed,
+        ..SimulationStats::default()
+    };
+    for result in results {
+        stats.merge(&result?);
+    }
+    Ok(stats)
+}
+
+pub fn stats_to_json(stats: &SimulationStats) -> String {
+    let fields: Vec<String> = stats
+        .values()
+        .iter()
+        .map(|(key, value)| format!("  \"{}\": {}", key, value))
+        .collect();
+    format!("{{\n{}\n}}", fields.join(",\n"))
+}
+
+pub fn stats_to_csv(stats: &SimulationStats) -> String {
+    let values = stats.values();
+    let header: Vec<&str> = values.iter().map(|(key, _)| *key).collect();
+    let row: Vec<&str> = values.iter().map(|(_, value)| value.as_str()).collect();
+    format!("{}\n{}", header.join(","), row.join(","))
+}
+
+const USAGE: &str = "usage: pistiflex simulate [--games N] [--seed S] [--p1 <strategy>] [--p2 <strategy>] [--rules <preset>] [--threads N] [--format json|csv]";
+
+/// `pistiflex simulate --games N --seed S --p1 <strategy> --p2 <strategy> --rules <preset>`
+pub fn simulate_main(args: &[String]) -> Result<(), String> {
+    let mut config = SimulationConfig {
+        seed: rand::random(),
+        ..SimulationConfig::default()
+    };
+    let mut format = String::from("json");
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        let value = args_iter.next().ok_or(USAGE)?;
+        match arg.as_str() {
+            "--games" => config.games = value.parse().map_err(|_| "--games needs a number")?,
+            "--seed" => config.seed = value.parse().map_err(|_| "--seed needs a number")?,
+            "--threads" => {
+                config.threads = value.parse().map_err(|_| "--threads needs a number")?
+            }
+            "--p1" => config.p1 = value.clone(),
+            "--p2" => config.p2 = value.clone(),
+            "--rules" => {
+                config.rules = value
+                    .parse::<RulePreset>()
+                    .map_err(|_| format!("unknown rules: {}", value))?
+                    .rules()
+            }
+            "--format" => match value.as_str() {
+                "json" | "csv" => format = value.clone(),
+                _ => return Err(format!("unknown format: {}", value)),
+            },
+            _ => return Err(String::from(USAGE)),
+        }
+    }
+    let stats = run_simulation(&config)?;
+    if format == "csv" {
+        println!("{}", stats_to_csv(&stats));
+    } else {
+        println!("{}", stats_to_json(&stats));
+    }
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use std::{
+    env,
+    fs::{self, File},
+    io::Write,
+    path::PathBuf,
+};
+
+/// directory for files the game writes for itself, `$XDG_DATA_HOME/pistiflex`
+pub fn data_dir() -> PathBuf {
+    let base = match env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => home_dir().join(".local").join("share"),
+    };
+    base.join("pistiflex")
+}
+
+/// directory for the files the player may edit, `$XDG_CONFIG_HOME/pistiflex`
+pub fn config_dir() -> PathBuf {
+    let base = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => home_dir().join(".config"),
+    };
+    base.join("pistiflex")
+}
+
+fn home_dir() -> PathBuf {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// `(line number, key, value)` of every `key = value` line, `#` starts a comment
+pub fn parse_key_values(text: &str) -> Result<Vec<(usize, String, String)>, String> {
+    key_value_lines(text).collect()
+}
+
+/// like `parse_key_values`, every line that is not `key = value` is an error of its own
+pub fn key_value_lines(
+    text: &str,
+) -> impl Iterator<Item = Result<(usize, String, String), String>> + '_ {
+    text.lines().enumerate().filter_map(|(line_no, line)| {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            return None;
+        }
+        Some(match line.split_once('=') {
+            Some((key, value)) => Ok((
+                line_no + 1,
+                key.trim().to_string(),
+                value.trim().to_string(),
+            )),
+            None => Err(format!("line {}: expected key = value", line_no + 1)),
+        })
+    })
+}
+
+/// writes `text` to `path`, creating the missing directories
+pub fn write_file(path: &PathBuf, text: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+    }
+    fs::write(path, text).map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
+
+/// writes `text` next to `path` and renames it over `path`, a crash leaves the old file or the new one
+pub fn replace_file(path: &PathBuf, text: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+    }
+    let new_path = path.with_extension("new");
+    let cannot_write = |e: std::io::Error| format!("cannot write {}: {}", new_path.display(), e);
+    let mut file = File::create(&new_path).map_err(cannot_write)?;
+    file.write_all(text.as_bytes()).map_err(cannot_write)?;
+    file.sync_all().map_err(cannot_write)?;
+    fs::rename(&new_path, path).map_err(|e| format!("cannot replace {}: {}", path.display(), e))
+}
@@ -0,0 +1,118 @@
+use strum_macros::{Display, EnumIter, EnumString};
+
+use crate::{
+    adaptive::AdaptiveStrategy,
+    engine::EngineStrategy,
+    game::{get_random_index, Game, Player},
+    weighted::{WeightedStrategy, DEFAULT_WEIGHTS},
+};
+
+pub trait Strategy: Send {
+    fn name(&self) -> String;
+    /// return index of the card to play from the hand of `player`
+    fn pick_card(&mut self, game: &Game, player: Player) -> usize;
+    /// called once after the points are calculated
+    fn game_over(&mut self, _game: &Game, _player: Player) {}
+    /// called before the game when the opponent is a human at this computer, with its profile
+    fn against_human(&mut self, _profile: Option<&str>) {}
+}
+
+#[derive(Debug, EnumIter, EnumString, Display, Copy, Clone, PartialEq)]
+#[strum(serialize_all = "lowercase")]
+pub enum StrategyKind {
+    Random,
+    Basic,
+    Weighted,
+    Adaptive,
+}
+
+impl StrategyKind {
+    pub fn create(&self) -> Box<dyn Strategy> {
+        match self {
+            StrategyKind::Random => Box::new(RandomStrategy),
+            StrategyKind::Basic => Box::new(BasicStrategy),
+            StrategyKind::Weighted => Box::new(WeightedStrategy::new(DEFAULT_WEIGHTS)),
+            StrategyKind::Adaptive => Box::new(AdaptiveStrategy::for_any_opponent()),
+        }
+    }
+}
+
+/// strength of the AI for players who do not want to pick a strategy
+#[derive(Debug, EnumIter, EnumString, Display, Copy, Clone, PartialEq)]
+#[strum(serialize_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub fn strategy(&self) -> StrategyKind {
+        match self {
+            Difficulty::Easy => StrategyKind::Random,
+            Difficulty::Normal => StrategyKind::Basic,
+            Difficulty::Hard => StrategyKind::Weighted,
+            Difficulty::Expert => StrategyKind::Adaptive,
+        }
+    }
+}
+
+/// opponent profile of the adaptive strategy against a human without a profile or an AI
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// creates a strategy from its name, from `engine:<command>` for an external engine,
+/// from `weighted:<file>` for weights loaded from a file or from `adaptive:<profile>`
+/// for an AI that learns how the opponent with that profile plays
+pub fn strategy_from_spec(spec: &str) -> Result<Box<dyn Strategy>, String> {
+    let spec = spec.trim();
+    if let Some(path) = spec.strip_prefix("weighted:") {
+        return Ok(Box::new(WeightedStrategy::from_file(path)?));
+    }
+    if let Some(profile) = spec.strip_prefix("adaptive:") {
+        return Ok(Box::new(AdaptiveStrategy::new(profile)));
+    }
+    match spec.strip_prefix("engine:") {
+        Some(command) => Ok(Box::new(EngineStrategy::spawn(command)?)),
+        None => spec
+            .parse::<StrategyKind>()
+            .map(|kind| kind.create())
+            .map_err(|_| format!("unknown strategy: {}", spec)),
+    }
+}
+
+/// plays any card from the hand
+pub struct RandomStrategy;
+
+impl Strategy for RandomStrategy {
+    fn name(&self) -> String {
+        StrategyKind::Random.to_string()
+    }
+
+    fn pick_card(&mut self, game: &Game, player: Player) -> usize {
+        get_random_index(game, game.get_player_cards(player))
+    }
+}
+
+/// takes the board if it holds a card with the same rank as the top card, otherwise plays randomly
+pub struct BasicStrategy;
+
+impl Strategy for BasicStrategy {
+    fn name(&self) -> String {
+        StrategyKind::Basic.to_string()
+    }
+
+    fn pick_card(&mut self, game: &Game, player: Player) -> usize {
+        let ai_cards = game.get_player_cards(player);
+        let mut tmp_i = get_random_index(game, ai_cards);
+
+        if let Some(card_on_board) = game.board.last() {
+            for (i, pp_card) in ai_cards.iter().enumerate() {
+                if pp_card.rank == card_on_board.rank {
+                    tmp_i = i;
+                }
+            }
+        }
+        tmp_i
+    }
+}
@@ -1,6 +1,1186 @@
+use crate::adaptive::OpponentModel;
+use crate::analyze::{card_outcomes, parse_deck, solve_final_deal};
 use crate::calc::{dvt};
+use crate::cards::CardSet;
+use crate::engine::EngineStrategy;
+use crate::features::{SeatView, FEATURE_NAMES};
+use crate::game::{Card, Game, Player, RulePreset, Rules, Suit, WinStatus};
+use crate::harness::{play_ai_game, run_match};
+use crate::history::{
+    entry_to_text, history_stats, opponent_stats, parse_history, score_histogram, win_rate_trend,
+    GameEntry, HistoryFilter,
+};
+use crate::json::{json_to_string, parse_json, Json};
+use crate::net::{
+    accept_guest, create_table, join_game, list_tables, message_to_line, new_session,
+    parse_message, read_message, read_on, read_welcome, resume_game, seat_guest, send_message,
+    send_on, spawn_reader, spawn_resumer, GuestGame, NetMessage,
+    TableInfo, TableState, TableStatus, Wire, MAX_LINE, PROTOCOL_VERSION,
+};
+use crate::options::{parse_options, Options};
+use crate::profiles::{check_name, parse_profiles};
+use crate::record::{game_to_record, parse_record};
+use crate::results::{result_to_json, results_to_csv};
+use crate::puzzle::{load_puzzles, parse_puzzles, PuzzleOpponent};
+use crate::save::{game_to_text, load_game, parse_saved_game};
+use crate::storage::replace_file;
+use crate::seat::{Seat, Seats};
+use crate::server::{run_server, ServerConfig};
+use crate::settings::Settings;
+use crate::simulate::{run_simulation, SimulationConfig};
+use crate::strategy::StrategyKind;
+use crate::tune::{tune_weights, TuneConfig};
+use crate::weighted::{load_weights, parse_weights, DEFAULT_WEIGHTS};
+use crate::websocket::{
+    accept_key, base64, connect_websocket, json_to_message, message_to_json, read_text, sha1,
+};
 
 #[test]
 fn my_test() {
     assert_eq!(dvt(5.0, 100.0), 0.05);
-}
\ No newline at end of file
+}
+
+#[test]
+fn ai_vs_ai_game_collects_all_cards() {
+    let mut seats = Seats::new(
+        Seat::Ai(StrategyKind::Basic.create()),
+        Seat::Ai(StrategyKind::Random.create()),
+    );
+    let my_game = play_ai_game(&mut seats, 7, Rules::default());
+    assert!(my_game.board.is_empty());
+    assert_eq!(
+        my_game.player1_won_cards.len() + my_game.player2_won_cards.len(),
+        52
+    );
+    assert_eq!(my_game.moves.len(), 48);
+}
+
+#[test]
+fn card_notation_round_trip() {
+    let mut my_game = Game::new();
+    my_game.create_deck();
+    for a_card in my_game.deck {
+        assert_eq!(a_card.notation().parse::<Card>(), Ok(a_card));
+    }
+    assert!("1S".parse::<Card>().is_err());
+    assert!("TX".parse::<Card>().is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn dummy_engine_plays_a_game() {
+    let engine = EngineStrategy::spawn("python3 engines/dummy_engine.py").unwrap();
+    let mut seats = Seats::new(
+        Seat::Ai(StrategyKind::Random.create()),
+        Seat::Ai(Box::new(engine)),
+    );
+    let my_game = play_ai_game(&mut seats, 7, Rules::default());
+    assert_eq!(my_game.moves.len(), 48);
+}
+
+#[test]
+fn same_seed_deals_same_cards() {
+    let mut first = Game::with_seed(42);
+    first.start_game_and_give_cards_to_players();
+    let mut second = Game::with_seed(42);
+    second.start_game_and_give_cards_to_players();
+    assert_eq!(first.initial_deck, second.initial_deck);
+    assert_eq!(first.bottom_hand, second.bottom_hand);
+}
+
+#[test]
+fn match_plays_every_deal_from_both_seats() {
+    let report = run_match("basic", "random", 20, 1).unwrap();
+    assert_eq!(report.games, 40);
+    assert_eq!(report.deal_scores.len(), 20);
+    assert_eq!(report.wins_a + report.wins_b + report.draws, 40);
+    let (low, high) = report.score_interval();
+    assert!(low <= report.score() && report.score() <= high);
+}
+
+#[test]
+fn match_is_reproducible() {
+    let first = run_match("basic", "random", 30, 5).unwrap();
+    let second = run_match("basic", "random", 30, 5).unwrap();
+    assert_eq!(
+        (first.wins_a, first.wins_b, first.draws, first.points_a, first.points_b),
+        (second.wins_a, second.wins_b, second.draws, second.points_a, second.points_b)
+    );
+    assert_eq!(first.deal_scores, second.deal_scores);
+    let play = || {
+        let mut seats = Seats::new(
+            Seat::Ai(StrategyKind::Basic.create()),
+            Seat::Ai(StrategyKind::Random.create()),
+        );
+        let my_game = play_ai_game(&mut seats, 5, Rules::default());
+        my_game.moves.iter().map(|m| m.card).collect::<Vec<Card>>()
+    };
+    assert_eq!(play(), play());
+}
+
+#[test]
+fn default_weights_file_matches_built_in_weights() {
+    assert_eq!(load_weights("weights/default.txt").unwrap(), DEFAULT_WEIGHTS);
+    assert!(parse_weights("pisti = ten").is_err());
+}
+
+#[test]
+fn features_see_a_pisti() {
+    let mut my_game = Game::with_seed(3);
+    my_game.start_game_and_give_cards_to_players();
+    my_game.board = [my_game.initial_board()[3]].into_iter().collect();
+    let top_card = my_game.board[0];
+    let same_rank = Card {
+        rank: top_card.rank,
+        suit: if top_card.suit == Suit::Spade { Suit::Heart } else { Suit::Spade },
+    };
+    my_game.bottom_hand[0] = same_rank;
+    let view = SeatView::new(&my_game, Player::Player1);
+    let f = view.features(same_rank);
+    assert_eq!(f[FEATURE_NAMES.iter().position(|n| *n == "pisti").unwrap()], 1.0);
+    assert_eq!(f[FEATURE_NAMES.iter().position(|n| *n == "capture").unwrap()], 1.0);
+}
+
+#[test]
+fn tuning_keeps_the_weights_norm() {
+    let config = TuneConfig {
+        iterations: 3,
+        deals: 10,
+        seed: 11,
+        ..TuneConfig::default()
+    };
+    let weights = tune_weights(&config);
+    let norm = |w: &[f64]| w.iter().map(|x| x * x).sum::<f64>().sqrt();
+    assert!((norm(&weights) - norm(&DEFAULT_WEIGHTS)).abs() < 1e-6);
+    assert_eq!(weights, tune_weights(&config));
+}
+
+#[test]
+fn opponent_model_learns_a_player_who_always_captures() {
+    let mut seats = Seats::new(
+        Seat::Ai(StrategyKind::Weighted.create()),
+        Seat::Ai(StrategyKind::Random.create()),
+    );
+    let mut model = OpponentModel::default();
+    for seed in 0..20 {
+        let my_game = play_ai_game(&mut seats, seed, Rules::default());
+        model.learn_from_game(&my_game, Player::Player1);
+    }
+    assert!(model.capture_chances > 0);
+    assert!(model.capture_rate() > 0.9);
+    assert_eq!(OpponentModel::parse(&model.to_text()), Ok(model));
+}
+
+#[test]
+fn simulation_adds_up_every_game() {
+    let config = SimulationConfig {
+        games: 25,
+        seed: 5,
+        threads: 4,
+        ..SimulationConfig::default()
+    };
+    let stats = run_simulation(&config).unwrap();
+    assert_eq!(stats.games, 25);
+    // the same seed plays the same games, whatever the thread count
+    let one_thread = SimulationConfig {
+        threads: 1,
+        ..config.clone()
+    };
+    assert_eq!(run_simulation(&one_thread).unwrap(), stats);
+    assert_eq!(stats.p1_wins + stats.p2_wins + stats.draws, 25);
+    let reshuffles: usize = (0..25)
+        .map(|i| {
+            let mut my_game = Game::with_seed(5 + i);
+            my_game.start_game_and_give_cards_to_players();
+            my_game.reshuffles
+        })
+        .sum();
+    assert_eq!(stats.reshuffles, reshuffles);
+}
+
+#[test]
+fn command_line_options() {
+    let args: Vec<String> = [
+        "--seed",
+        "9",
+        "--difficulty",
+        "hard",
+        "--speed",
+        "2",
+        "--rules",
+        "jack-pisti",
+    ]
+    .iter()
+    .map(|a| a.to_string())
+    .collect();
+    let options = parse_options(&args, Options::default()).unwrap();
+    assert_eq!(options.seed, Some(9));
+    assert_eq!(options.ai.as_deref(), Some("weighted"));
+    assert_eq!(options.anim_speed, 2);
+    assert_eq!(options.rules.jack_pisti, 20);
+    let no_animation: Vec<String> = ["--no-animation", "--speed", "4"]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+    assert_eq!(parse_options(&no_animation, Options::default()).unwrap().anim_speed, 0);
+    assert!(parse_options(&[String::from("--speed")], Options::default()).is_err());
+    let hotseat_and_seat: Vec<String> = ["--hotseat", "--seat", "1"]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+    assert!(parse_options(&hotseat_and_seat, Options::default()).is_err());
+}
+
+#[test]
+fn saved_game_continues_where_it_stopped() {
+    let mut seats = Seats::new(
+        Seat::Ai(StrategyKind::Basic.create()),
+        Seat::Ai(StrategyKind::Basic.create()),
+    );
+    let finished = play_ai_game(&mut seats, 21, Rules::default());
+    let moves: Vec<String> = finished.moves[..13]
+        .iter()
+        .map(|m| m.card.notation())
+        .collect();
+    let text = format!("version = 1\nseed = 21\nmoves = {}\n", moves.join(" "));
+    let my_game = parse_saved_game(&text).unwrap().game;
+    assert_eq!(my_game.moves[..], finished.moves[..13]);
+    assert_eq!(my_game.turn, finished.moves[13].player);
+    assert_eq!(my_game.get_player_cards(Player::Player1).len(), 1);
+    assert!(parse_saved_game("version = 3\nseed = 21\n").is_err());
+    assert!(parse_saved_game("version = 1\nseed = 21\nmoves = AS AS\n").is_err());
+
+    let text = game_to_text(&my_game, &seats);
+    let saved = parse_saved_game(&text).unwrap();
+    assert_eq!(saved.game.board, my_game.board);
+    assert_eq!(saved.game.bottom_hand, my_game.bottom_hand);
+    assert_eq!(saved.game.player2_won_cards, my_game.player2_won_cards);
+    assert_eq!(saved.game.face_down_cards(), my_game.face_down_cards());
+    assert_eq!(saved.seats, Some((String::from("basic"), String::from("basic"))));
+    let dealt = saved.game.dealt_hand(Player::Player1);
+    assert_eq!(dealt.len(), 4);
+    assert!(finished.moves[8..16]
+        .iter()
+        .filter(|m| m.player == Player::Player1)
+        .all(|m| dealt.contains(&m.card)));
+    let tampered = text.replace("turn = 2", "turn = 1");
+    assert!(parse_saved_game(&tampered).is_err());
+}
+
+#[test]
+fn journal_is_replaced_after_every_move() {
+    let mut seats = Seats::new(
+        Seat::Ai(StrategyKind::Basic.create()),
+        Seat::Ai(StrategyKind::Basic.create()),
+    );
+    let finished = play_ai_game(&mut seats, 8, Rules::default());
+    let path = std::env::temp_dir().join(format!("pistiflex-journal-{}.txt", std::process::id()));
+    let mut my_game = Game::with_seed(8);
+    my_game.start_game_and_give_cards_to_players();
+    for a_move in &finished.moves[..20] {
+        let hand_index = my_game.get_index_of_card(a_move.card, a_move.player);
+        my_game.play_turn(a_move.player, hand_index);
+        my_game.next_deal();
+        replace_file(&path, &game_to_text(&my_game, &seats)).unwrap();
+    }
+    let saved = load_game(&path).unwrap();
+    assert_eq!(saved.game.moves[..], finished.moves[..20]);
+    assert!(!path.with_extension("new").exists());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn history_round_trip_and_stats() {
+    let mut seats = Seats::new(
+        Seat::Human(Some(String::from("Ann"))),
+        Seat::Ai(StrategyKind::Basic.create()),
+    );
+    let mut entries = Vec::new();
+    let mut text = String::new();
+    for seed in 1..4 {
+        let my_game = play_ai_game(
+            &mut Seats::new(
+                Seat::Ai(StrategyKind::Basic.create()),
+                Seat::Ai(StrategyKind::Random.create()),
+            ),
+            seed,
+            Rules::default(),
+        );
+        let entry = GameEntry::new(&my_game, &seats, "2026.10.19");
+        assert_eq!(entry.player1_score.total(), my_game.player1_point);
+        text.push_str(&format!("\n{}", entry_to_text(&entry)));
+        entries.push(entry);
+    }
+    text.push_str("\ndate = 2026.10.20\nseed = x\n");
+    assert_eq!(parse_history(&text), entries);
+
+    let stats = history_stats(&entries);
+    assert_eq!(stats.games, 3);
+    let points: usize = entries.iter().map(|e| e.player1_score.total()).sum();
+    assert_eq!(stats.points, points);
+    let club_2 = "2C".parse::<Card>().unwrap();
+    let club_2_won = entries.iter().filter(|e| e.player1_won.contains(club_2)).count();
+    assert_eq!(stats.capture_rate(club_2), club_2_won as f64 / 3.0);
+    seats.player1 = Seat::Ai(StrategyKind::Basic.create());
+    let watched = GameEntry::new(&play_ai_game(&mut seats, 9, Rules::default()), &seats, "");
+    assert_eq!(history_stats([&watched]).games, 0);
+
+    entries[0].date = String::from("2026.09.30");
+    entries[2].rules = RulePreset::JackPisti;
+    let filter = HistoryFilter {
+        profile: Some(String::from("Ann")),
+        rules: Some(RulePreset::Standard),
+        from: Some(String::from("2026.10")),
+        to: Some(String::from("2026.10")),
+    };
+    let shown: Vec<&GameEntry> = entries.iter().filter(|e| filter.matches(e)).collect();
+    assert_eq!(shown, vec![&entries[1]]);
+    assert_eq!(entries[1].player1, "human:Ann");
+    let bob = HistoryFilter {
+        profile: Some(String::from("Bob")),
+        ..HistoryFilter::default()
+    };
+    assert!(!entries.iter().any(|e| bob.matches(e)));
+    let all: Vec<&GameEntry> = entries.iter().chain([&watched]).collect();
+    assert_eq!(score_histogram(&all, 10).iter().sum::<usize>(), 3);
+    let trend = win_rate_trend(&all, 2);
+    assert_eq!(trend.len(), 3);
+    assert!(trend.iter().all(|rate| (0.0..=1.0).contains(rate)));
+    let opponents = opponent_stats(&all);
+    assert_eq!(opponents.len(), 1);
+    assert_eq!(opponents[0].0, "basic");
+}
+
+#[test]
+fn results_have_the_itemized_points() {
+    let mut seats = Seats::new(
+        Seat::Ai(StrategyKind::Basic.create()),
+        Seat::Ai(StrategyKind::Random.create()),
+    );
+    let my_game = play_ai_game(&mut seats, 4, Rules::default());
+    let mut entry = GameEntry::new(&my_game, &seats, "2026.10.19");
+    entry.player1 = String::from("Ann, \"the\" player");
+    let json = result_to_json(&entry);
+    let start = "{\"date\": \"2026.10.19\", \"player1\": \"Ann, \\\"the\\\" player\"";
+    assert!(json.starts_with(start));
+    assert!(json.contains(&format!("\"player1_points\": {}", my_game.player1_point)));
+    assert!(json.contains(&format!(
+        "\"player2_card_points\": {}",
+        entry.player2_score.cards
+    )));
+    assert!(json.ends_with("\"moves\": 48, \"seconds\": null}"));
+
+    entry.seconds = Some(95);
+    assert_eq!(parse_history(&entry_to_text(&entry)), vec![entry.clone()]);
+    let csv = results_to_csv(&[entry.clone(), entry]);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0].split(',').count(), 17);
+    assert!(lines[1].starts_with("2026.10.19,\"Ann, \"\"the\"\" player\",random,standard,4,"));
+    assert!(lines[1].ends_with(",48,95"));
+}
+
+#[test]
+fn settings_skip_unknown_keys_and_bad_values() {
+    let settings = Settings::parse(
+        "anim_speed = 3\ntheme = dark\ncard_back = purple\nfuture_key = 1\nwindow = 10 20 900 700\n",
+    );
+    assert_eq!(settings.anim_speed, 3);
+    assert_eq!(settings.theme, "dark");
+    assert_eq!(settings.card_back, Settings::default().card_back);
+    assert_eq!(settings.window, Some((10, 20, 900, 700)));
+    assert_eq!(Settings::parse(&settings.to_text()), settings);
+    assert_eq!(Settings::parse("not a settings file"), Settings::default());
+    assert_eq!(Settings::parse("anim_speed = 2\nnot a line\ntheme = dark").anim_speed, 2);
+}
+
+#[test]
+fn final_deal_is_solved_exactly() {
+    let mut seats = Seats::new(
+        Seat::Ai(StrategyKind::Basic.create()),
+        Seat::Ai(StrategyKind::Basic.create()),
+    );
+    let finished = play_ai_game(&mut seats, 4, Rules::default());
+    let mut my_game = Game::with_seed(4);
+    my_game.start_game_and_give_cards_to_players();
+    // the first five deals are played like in the finished game
+    for a_move in &finished.moves[..40] {
+        let hand_index = my_game.get_index_of_card(a_move.card, a_move.player);
+        my_game.play_turn(a_move.player, hand_index);
+        my_game.next_deal();
+    }
+    assert!(my_game.deck.is_empty());
+    let (outcomes, exact) = card_outcomes(&my_game);
+    assert!(exact);
+    assert_eq!(outcomes.len(), 4);
+    let best = outcomes.iter().map(|(_, o)| *o).max().unwrap();
+    assert_eq!(best, solve_final_deal(&my_game));
+}
+
+#[test]
+fn explicit_deck_is_dealt_in_order() {
+    let mut cards: Vec<String> = Vec::new();
+    for suit in ["S", "H", "D", "C"] {
+        for rank in ["A", "2", "3", "4", "5", "6", "7", "8", "9", "T", "J", "Q", "K"] {
+            cards.push(format!("{}{}", rank, suit));
+        }
+    }
+    let mut my_game = Game::with_seed(0);
+    my_game.start_game_with_deck(&parse_deck(&cards.join(" ")).unwrap());
+    let board: Vec<String> = my_game.board.iter().map(|c| c.notation()).collect();
+    assert_eq!(board, ["AS", "2S", "3S", "4S"]);
+    assert_eq!(my_game.bottom_hand[0].notation(), "5S");
+    assert_eq!(my_game.top_hand[0].notation(), "6S");
+    assert!(parse_deck("AS AS").is_err());
+}
+
+#[test]
+fn puzzle_opponent_punishes_the_wrong_card() {
+    let puzzles = load_puzzles(std::path::Path::new("puzzles/training.txt")).unwrap();
+    let puzzle = puzzles.iter().find(|p| p.name == "Take it now").unwrap();
+    assert_eq!(puzzle.best_score(), 7);
+    let mut seats = Seats::new(
+        Seat::Ai(StrategyKind::Basic.create()),
+        Seat::Ai(Box::new(PuzzleOpponent {
+            puzzle: puzzle.clone(),
+        })),
+    );
+    let mut my_game = puzzle.to_game();
+    // 2C first lets the opponent take the board with 2H
+    let two_of_clubs = my_game.get_index_of_card("2C".parse().unwrap(), Player::Player1);
+    my_game.play_turn(Player::Player1, two_of_clubs);
+    while !my_game.is_game_over() {
+        let player = my_game.turn;
+        let hand_index = seats.pick_card_for_ai(&my_game, player).unwrap();
+        my_game.play_turn(player, hand_index);
+        my_game.next_deal();
+    }
+    assert!(puzzle.score(&my_game) < puzzle.best_score());
+    assert!(parse_puzzles("name = twice\nhand = AS\nopponent = AS\n").is_err());
+    assert!(parse_puzzles("hand = AS\n").is_err());
+}
+
+#[test]
+fn card_set_points_match_card_by_card() {
+    let mut my_game = Game::with_seed(5);
+    my_game.create_deck();
+    for preset in [RulePreset::Standard, RulePreset::JackPisti] {
+        let rules = preset.rules();
+        for size in [0, 1, 13, 30, 52] {
+            let cards = &my_game.deck[..size];
+            let set: CardSet = cards.iter().copied().collect();
+            let expected: usize = cards.iter().map(|c| rules.card_points(c)).sum();
+            assert_eq!(set.len(), size);
+            assert_eq!(set.points(&rules), expected);
+            assert_eq!(set.iter().count(), size);
+        }
+        my_game.shuffle_deck();
+    }
+    let mut copy = my_game.clone();
+    copy.deck.pop();
+    assert_eq!(copy.deck.len() + 1, my_game.deck.len());
+}
+
+#[test]
+fn record_round_trip_and_illegal_moves() {
+    let mut seats = Seats::new(
+        Seat::Ai(StrategyKind::Basic.create()),
+        Seat::Ai(StrategyKind::Random.create()),
+    );
+    let finished = play_ai_game(&mut seats, 33, Rules::default());
+    let text = game_to_record(&finished, "Ayse", "AI (random)", "2024.01.02");
+    let record = parse_record(&text).unwrap();
+    assert_eq!(record.tag("Player1"), Some("Ayse"));
+    assert_eq!(record.moves.len(), 48);
+    let my_game = record.to_game().unwrap();
+    assert_eq!(my_game.moves, finished.moves);
+    assert_eq!(my_game.player1_point, finished.player1_point);
+
+    let result = format!("{}-{}", finished.player1_point, finished.player2_point);
+    let wrong_result = text.replace(&result, "99-0");
+    assert!(parse_record(&wrong_result).unwrap().to_game().is_err());
+    // the second card of player 1 played first is missing from the hand later
+    let first = finished.moves[0].card.notation();
+    let third = finished.moves[2].card.notation();
+    let swapped = text.replacen(&format!("1. {}", first), &format!("1. {}", third), 1);
+    assert!(parse_record(&swapped).unwrap().to_game().is_err());
+}
+
+#[test]
+fn profiles_skip_bad_lines() {
+    let profiles = parse_profiles("Ann = red\nBob = 00ff80\nAnn = blue\nCem = pink\n");
+    let names: Vec<&str> = profiles.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["Ann", "Bob"]);
+    assert_eq!(profiles[1].rgb(), (0, 255, 128));
+    assert_eq!(check_name("  Deniz "), Ok(String::from("Deniz")));
+    assert!(check_name("a:b").is_err());
+    assert!(check_name("").is_err());
+}
+
+#[test]
+fn lan_guest_sees_the_game_of_the_host() {
+    let line = "played 2 7H win deal 4C KH 9S 2D";
+    let message = parse_message(line).unwrap();
+    assert_eq!(
+        message,
+        NetMessage::Played {
+            player: Player::Player2,
+            card: "7H".parse().unwrap(),
+            stat: WinStatus::Win,
+            deal: Some(["4C", "KH", "9S", "2D"].iter().map(|c| c.parse().unwrap()).collect()),
+        }
+    );
+    assert_eq!(message_to_line(&message), line);
+    assert!(parse_message("played 2 7H win deal 4C").is_err());
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let host = std::thread::spawn(move || {
+        let mut my_game = Game::with_seed(58);
+        my_game.start_game_and_give_cards_to_players();
+        // the guest joins after the first moves
+        for _ in 0..3 {
+            my_game.play_turn(my_game.turn, 0);
+        }
+        let (mut remote, mut reader) =
+            accept_guest(&listener, &my_game, Player::Player2, "Ann", &new_session()).unwrap();
+        let mut strategy = StrategyKind::Basic.create();
+        while !my_game.is_game_over() {
+            let hand_index = match my_game.turn {
+                Player::Player1 => strategy.pick_card(&my_game, Player::Player1),
+                Player::Player2 => match read_message(&mut reader).unwrap() {
+                    NetMessage::Play(a_card) => my_game.get_index_of_card(a_card, Player::Player2),
+                    other => panic!("unexpected {:?}", other),
+                },
+            };
+            my_game.play_turn(my_game.turn, hand_index);
+            my_game.next_deal();
+            remote.send_updates(&my_game);
+        }
+        my_game
+    });
+    let (mut guest, mut stream, mut reader) = join_game(&address, "Bob").unwrap();
+    assert_eq!((guest.seat, guest.host_name.as_str()), (Player::Player2, "Ann"));
+    assert_eq!(guest.game.moves.len(), 3);
+    play_as_guest(&mut guest, &mut stream, &mut reader, 48);
+    let my_game = host.join().unwrap();
+    assert!(matches!(read_message(&mut reader), Ok(NetMessage::State(_))));
+    assert!(matches!(read_message(&mut reader), Ok(NetMessage::Bye)));
+    // only the order of the cards in the hands of the host is not known to the guest
+    assert_eq!(guest.game.moves, my_game.moves);
+    assert_eq!(guest.game.player1_won_cards, my_game.player1_won_cards);
+    assert_eq!(
+        (guest.game.player1_point, guest.game.player2_point),
+        (my_game.player1_point, my_game.player2_point)
+    );
+    assert_eq!(guest.game.seed, 58);
+}
+
+#[test]
+fn lan_guest_resumes_after_its_connection_dropped() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let host = std::thread::spawn(move || {
+        let mut my_game = Game::with_seed(91);
+        my_game.start_game_and_give_cards_to_players();
+        let session = new_session();
+        let (mut remote, reader) =
+            accept_guest(&listener, &my_game, Player::Player2, "Ann", &session).unwrap();
+        // the messages of every connection of the guest, counted like the window does
+        let (messages, received) = std::sync::mpsc::channel();
+        let read = |reader, connection: usize| {
+            let messages = messages.clone();
+            spawn_reader(reader, move |message| {
+                let _ = messages.send((connection, message));
+            });
+        };
+        read(reader, 0);
+        let (resume, resumed) = std::sync::mpsc::channel();
+        spawn_resumer(listener, session.clone(), move |stream, reader| {
+            resume.send((stream, reader)).is_ok()
+        });
+        let mut connection = 0;
+        let mut strategy = StrategyKind::Basic.create();
+        while !my_game.is_game_over() {
+            let hand_index = match my_game.turn {
+                Player::Player1 => strategy.pick_card(&my_game, Player::Player1),
+                Player::Player2 => match received.recv().unwrap() {
+                    (c, Ok(NetMessage::Play(a_card))) if c == connection => {
+                        my_game.get_index_of_card(a_card, Player::Player2)
+                    }
+                    (c, Err(_)) if c == connection => {
+                        let (stream, reader) = resumed.recv().unwrap();
+                        remote = seat_guest(
+                            stream,
+                            Wire::Lines,
+                            "Bob",
+                            &my_game,
+                            Player::Player2,
+                            "Ann",
+                            Some(&session),
+                        )
+                        .unwrap();
+                        connection += 1;
+                        read(reader, connection);
+                        continue;
+                    }
+                    other => panic!("unexpected {:?}", other),
+                },
+            };
+            my_game.play_turn(my_game.turn, hand_index);
+            my_game.next_deal();
+            remote.send_updates(&my_game);
+        }
+        my_game
+    });
+    let (mut guest, mut stream, mut reader) = join_game(&address, "Bob").unwrap();
+    let token = guest.session.clone().unwrap();
+    play_as_guest(&mut guest, &mut stream, &mut reader, 11);
+    stream.shutdown(std::net::Shutdown::Both).unwrap();
+    // a wrong token is refused and the host keeps waiting for the right one
+    assert!(resume_game(&address, "Bob", "00ff00ff00ff00ff").is_err());
+
+    let (mut resumed, mut stream, mut reader) = resume_game(&address, "Bob", &token).unwrap();
+    assert_eq!(resumed.seat, Player::Player2);
+    assert_eq!(resumed.game.moves.len(), guest.game.moves.len());
+    play_as_guest(&mut resumed, &mut stream, &mut reader, 48);
+    let my_game = host.join().unwrap();
+    assert_eq!(resumed.game.moves, my_game.moves);
+    assert_eq!(
+        (resumed.game.player1_point, resumed.game.player2_point),
+        (my_game.player1_point, my_game.player2_point)
+    );
+}
+
+fn lobby_client(
+    address: &str,
+    name: &str,
+) -> (std::net::TcpStream, std::io::BufReader<std::net::TcpStream>) {
+    let mut stream = std::net::TcpStream::connect(address).unwrap();
+    stream.set_nodelay(true).unwrap();
+    let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+    let hello = NetMessage::Hello {
+        version: PROTOCOL_VERSION,
+        name: name.to_string(),
+    };
+    send_message(&mut stream, &hello).unwrap();
+    assert_eq!(
+        read_message(&mut reader),
+        Ok(NetMessage::Lobby(String::from("pistiflex")))
+    );
+    (stream, reader)
+}
+
+#[test]
+fn server_tables_are_listed_joined_and_played() {
+    let line = "table 3 jack-pisti ai:basic Ann_Lee waiting";
+    assert_eq!(message_to_line(&parse_message(line).unwrap()), line);
+    assert!(parse_message("create standard - engine:./bot").is_err());
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    std::thread::spawn(move || run_server(listener, ServerConfig::default()));
+
+    let (mut stream, mut reader) = lobby_client(&address, "Ann");
+    let both_ai = NetMessage::Create {
+        rules: RulePreset::Standard,
+        seats: (Some(StrategyKind::Basic), Some(StrategyKind::Random)),
+    };
+    assert!(create_table(&mut stream, &mut reader, &both_ai, Wire::Lines).is_err());
+    let create = NetMessage::Create {
+        rules: RulePreset::Standard,
+        seats: (None, Some(StrategyKind::Basic)),
+    };
+    let id = create_table(&mut stream, &mut reader, &create, Wire::Lines).unwrap();
+    let tables = list_tables(&mut stream, &mut reader, Wire::Lines).unwrap();
+    assert_eq!(tables.len(), 1);
+    assert_eq!(tables[0].seats, (String::from("-"), String::from("ai:basic")));
+    assert_eq!(tables[0].status, TableStatus::Waiting);
+
+    let seat = Player::Player1;
+    send_message(&mut stream, &NetMessage::Join { id, seat }).unwrap();
+    let mut guest = read_welcome(&mut reader, None, Wire::Lines).unwrap();
+    assert_eq!(guest.seat, seat);
+    assert!(guest.session.is_some());
+
+    // a second client sees the table taken and cannot sit down at it
+    let (mut other, mut other_reader) = lobby_client(&address, "Bob");
+    let tables = list_tables(&mut other, &mut other_reader, Wire::Lines).unwrap();
+    assert_eq!(tables[0].seats.0, "Ann");
+    assert_eq!(tables[0].status, TableStatus::Playing);
+    send_message(&mut other, &NetMessage::Join { id, seat }).unwrap();
+    assert!(matches!(read_message(&mut other_reader), Ok(NetMessage::Error(_))));
+
+    play_as_guest(&mut guest, &mut stream, &mut reader, 48);
+    assert!(guest.game.is_game_over());
+    assert_eq!(guest.game.moves.len(), 48);
+
+    // the table is closed once its only player left
+    send_message(&mut stream, &NetMessage::Bye).unwrap();
+    assert!(wait_until_no_tables(&mut other, &mut other_reader));
+}
+
+#[test]
+fn tables_nobody_sat_down_at_are_closed() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let config = ServerConfig {
+        max_tables: 1,
+        ..ServerConfig::default()
+    };
+    std::thread::spawn(move || run_server(listener, config));
+    let create = NetMessage::Create {
+        rules: RulePreset::Standard,
+        seats: (None, Some(StrategyKind::Basic)),
+    };
+    let (mut stream, mut reader) = lobby_client(&address, "Ann");
+    create_table(&mut stream, &mut reader, &create, Wire::Lines).unwrap();
+    let (mut other, mut other_reader) = lobby_client(&address, "Bob");
+    assert!(create_table(&mut other, &mut other_reader, &create, Wire::Lines).is_err());
+
+    send_message(&mut stream, &NetMessage::Bye).unwrap();
+    assert!(wait_until_no_tables(&mut other, &mut other_reader));
+    assert!(create_table(&mut other, &mut other_reader, &create, Wire::Lines).is_ok());
+}
+
+#[test]
+fn overlong_lines_close_the_connection() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    std::thread::spawn(move || run_server(listener, ServerConfig::default()));
+    let (mut stream, mut reader) = lobby_client(&address, "Ann");
+    let _ = std::io::Write::write_all(&mut stream, &vec![b'a'; MAX_LINE as usize + 1]);
+    assert!(read_message(&mut reader).is_err());
+}
+
+/// plays the first card of the hand until the game has `until` moves or is over
+fn play_as_guest(
+    guest: &mut GuestGame,
+    stream: &mut std::net::TcpStream,
+    reader: &mut std::io::BufReader<std::net::TcpStream>,
+    until: usize,
+) {
+    loop {
+        let message = read_message(reader).unwrap();
+        if message == NetMessage::Ping {
+            send_message(stream, &NetMessage::Pong).unwrap();
+            continue;
+        }
+        guest.apply(&message).unwrap();
+        if guest.game.is_game_over() && matches!(message, NetMessage::GameOver { .. }) {
+            return;
+        }
+        if !matches!(message, NetMessage::State(_)) || guest.game.is_game_over() {
+            continue;
+        }
+        if guest.game.moves.len() >= until {
+            return;
+        }
+        if guest.game.turn == guest.seat {
+            let a_card = guest.game.get_player_cards(guest.seat)[0];
+            send_message(stream, &NetMessage::Play(a_card)).unwrap();
+        }
+    }
+}
+
+/// lists the tables until the first one has `status`
+fn wait_for_status(
+    stream: &mut std::net::TcpStream,
+    reader: &mut std::io::BufReader<std::net::TcpStream>,
+    status: TableStatus,
+) -> TableInfo {
+    for _ in 0..200 {
+        let tables = list_tables(stream, reader, Wire::Lines).unwrap();
+        if tables.first().is_some_and(|t| t.status == status) {
+            return tables[0].clone();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    panic!("no table is {}", status);
+}
+
+/// lists the tables until there are none, false when there still are after two seconds
+fn wait_until_no_tables(
+    stream: &mut std::net::TcpStream,
+    reader: &mut std::io::BufReader<std::net::TcpStream>,
+) -> bool {
+    for _ in 0..200 {
+        if list_tables(stream, reader, Wire::Lines).unwrap().is_empty() {
+            return true;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    false
+}
+
+#[test]
+fn dropped_seats_are_resumed_or_taken_over() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let config = ServerConfig {
+        grace: std::time::Duration::from_millis(300),
+        ..ServerConfig::default()
+    };
+    std::thread::spawn(move || run_server(listener, config));
+    let (mut watcher, mut watcher_reader) = lobby_client(&address, "Cem");
+
+    // Ann drops in the middle of the game against the AI and comes back
+    let (mut stream, mut reader) = lobby_client(&address, "Ann");
+    let create = NetMessage::Create {
+        rules: RulePreset::Standard,
+        seats: (None, Some(StrategyKind::Basic)),
+    };
+    let id = create_table(&mut stream, &mut reader, &create, Wire::Lines).unwrap();
+    let seat = Player::Player1;
+    send_message(&mut stream, &NetMessage::Join { id, seat }).unwrap();
+    let mut guest = read_welcome(&mut reader, None, Wire::Lines).unwrap();
+    let token = guest.session.clone().unwrap();
+    play_as_guest(&mut guest, &mut stream, &mut reader, 11);
+    stream.shutdown(std::net::Shutdown::Both).unwrap();
+    let info = wait_for_status(&mut watcher, &mut watcher_reader, TableStatus::Paused);
+    assert_eq!(info.seats, (String::from("Ann"), String::from("ai:basic")));
+    assert!(resume_game(&address, "Ann", "3-1-00ff00ff00ff00ff").is_err());
+
+    let (mut resumed, mut stream, mut reader) = resume_game(&address, "Ann", &token).unwrap();
+    assert_eq!(resumed.seat, seat);
+    assert_eq!(resumed.game.moves.len(), guest.game.moves.len());
+    assert_eq!(
+        resumed.game.get_player_cards(seat),
+        guest.game.get_player_cards(seat)
+    );
+    play_as_guest(&mut resumed, &mut stream, &mut reader, 48);
+    assert!(resumed.game.is_game_over());
+    assert_eq!(resumed.game.moves.len(), 48);
+    send_message(&mut stream, &NetMessage::Bye).unwrap();
+    assert!(wait_until_no_tables(&mut watcher, &mut watcher_reader));
+
+    // Bob drops and does not come back in time, the AI plays his seat
+    let (mut ann, mut ann_reader) = lobby_client(&address, "Ann");
+    let open = NetMessage::Create {
+        rules: RulePreset::Standard,
+        seats: (None, None),
+    };
+    let id = create_table(&mut ann, &mut ann_reader, &open, Wire::Lines).unwrap();
+    send_message(&mut ann, &NetMessage::Join { id, seat }).unwrap();
+    let mut guest = read_welcome(&mut ann_reader, None, Wire::Lines).unwrap();
+    let (mut bob, mut bob_reader) = lobby_client(&address, "Bob");
+    let seat = Player::Player2;
+    send_message(&mut bob, &NetMessage::Join { id, seat }).unwrap();
+    let bob_guest = read_welcome(&mut bob_reader, None, Wire::Lines).unwrap();
+    let token = bob_guest.session.unwrap();
+    bob.shutdown(std::net::Shutdown::Both).unwrap();
+    wait_for_status(&mut watcher, &mut watcher_reader, TableStatus::Paused);
+    let info = wait_for_status(&mut watcher, &mut watcher_reader, TableStatus::Playing);
+    assert_eq!(info.seats, (String::from("Ann"), String::from("ai:basic")));
+    assert!(resume_game(&address, "Bob", &token).is_err());
+    play_as_guest(&mut guest, &mut ann, &mut ann_reader, 48);
+    assert!(guest.game.is_game_over());
+    assert_eq!(guest.game.moves.len(), 48);
+}
+
+#[test]
+fn silent_players_are_paused() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let config = ServerConfig {
+        heartbeat: std::time::Duration::from_millis(50),
+        ..ServerConfig::default()
+    };
+    std::thread::spawn(move || run_server(listener, config));
+    let (mut watcher, mut watcher_reader) = lobby_client(&address, "Cem");
+    let (mut stream, mut reader) = lobby_client(&address, "Ann");
+    let create = NetMessage::Create {
+        rules: RulePreset::Standard,
+        seats: (None, Some(StrategyKind::Basic)),
+    };
+    let id = create_table(&mut stream, &mut reader, &create, Wire::Lines).unwrap();
+    let seat = Player::Player1;
+    send_message(&mut stream, &NetMessage::Join { id, seat }).unwrap();
+    let guest = read_welcome(&mut reader, None, Wire::Lines).unwrap();
+    let token = guest.session.clone().unwrap();
+    // Ann thinks for longer than three heartbeats, her pongs keep the seat
+    let thinking = std::time::Instant::now();
+    while thinking.elapsed() < std::time::Duration::from_millis(400) {
+        if read_message(&mut reader).unwrap() == NetMessage::Ping {
+            send_message(&mut stream, &NetMessage::Pong).unwrap();
+        }
+    }
+    assert_eq!(
+        list_tables(&mut watcher, &mut watcher_reader, Wire::Lines).unwrap()[0].status,
+        TableStatus::Playing
+    );
+
+    // the connection stays open but Ann says nothing
+    wait_for_status(&mut watcher, &mut watcher_reader, TableStatus::Paused);
+    let (mut resumed, mut stream, mut reader) = resume_game(&address, "Ann", &token).unwrap();
+    assert_eq!(resumed.game.moves.len(), guest.game.moves.len());
+    play_as_guest(&mut resumed, &mut stream, &mut reader, 48);
+    assert!(resumed.game.is_game_over());
+}
+
+#[test]
+fn messages_are_the_same_as_json() {
+    let hex: String = sha1(b"abc").iter().map(|b| format!("{:02x}", b)).collect();
+    assert_eq!(hex, "a9993e364706816aba3e25717850c26c9cd0d89d");
+    assert_eq!(base64(b"pisti"), "cGlzdGk=");
+    assert_eq!(base64(b"pist"), "cGlzdA==");
+    // the example of RFC 6455
+    assert_eq!(
+        accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+        "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+    );
+    assert_eq!(
+        parse_json(r#" {"a": [1, -2.5e1, null, true], "b": "x\"\u00e7\ud83c\udca1"} "#),
+        Ok(Json::Object(vec![
+            (
+                String::from("a"),
+                Json::Array(vec![
+                    Json::Number(1.0),
+                    Json::Number(-25.0),
+                    Json::Null,
+                    Json::Bool(true)
+                ])
+            ),
+            (String::from("b"), Json::String(String::from("x\"ç🂡"))),
+        ]))
+    );
+    assert!(parse_json("[1, 2").is_err());
+    assert!(parse_json(&"[".repeat(100)).is_err());
+
+    let lines = [
+        "hello 2 Ann Lee",
+        "welcome 2 1 jack-pisti table 4",
+        "refused the host speaks protocol version 2",
+        "board xx xx xx 7H",
+        "deal 4C KH 9S 2D",
+        "played 2 7H win deal 4C KH 9S 2D",
+        "played 1 JD pisti",
+        "ready",
+        "play KH",
+        "illegal QS",
+        "gameover 78 23 18446744073709551615 3C JD 5S",
+        "bye",
+        "state 2 12 3 40 board xx xx xx 7H 8S hand 4C KH legal",
+        "state 1 0 0 44 board hand 4C legal 4C",
+        "lobby pistiflex",
+        "list",
+        "tables 2",
+        "table 3 last-capture - ai:adaptive waiting",
+        "create standard - basic",
+        "created 3",
+        "join 3 2",
+        "error the seat is taken",
+        "table 3 standard Ann ai:basic paused",
+        "session 3-1-00ff00ff00ff00ff",
+        "resume 3-1-00ff00ff00ff00ff",
+        "ping",
+        "pong",
+    ];
+    for line in lines {
+        let message = parse_message(line).unwrap();
+        assert_eq!(message_to_line(&message), line);
+        let text = json_to_string(&message_to_json(&message));
+        assert_eq!(json_to_message(&parse_json(&text).unwrap()), Ok(message), "{}", text);
+    }
+    let json = |text: &str| json_to_message(&parse_json(text).unwrap());
+    assert_eq!(
+        json(r#"{"type":"play","card":"KH","comment":"ignored"}"#),
+        Ok(NetMessage::Play("KH".parse().unwrap()))
+    );
+    assert!(json(r#"{"type":"create","rules":"standard","seats":[null,"engine:./bot"]}"#).is_err());
+    assert!(json(r#"{"type":"join","id":-1,"seat":1}"#).is_err());
+    assert!(json(r#"{"type":"join","id":1,"seat":3}"#).is_err());
+    assert!(json(r#"{"type":"dance"}"#).is_err());
+}
+
+/// what the server reads from a client that sends `bytes`, and the pings on the way
+fn read_client_frames(bytes: &[u8]) -> (Result<String, String>, Vec<Vec<u8>>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    std::io::Write::write_all(&mut client, bytes).unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    let mut reader = std::io::BufReader::new(stream);
+    let mut pings = Vec::new();
+    let text = read_text(&mut reader, true, |_, payload| {
+        pings.push(payload.to_vec());
+        Ok(())
+    });
+    (text, pings)
+}
+
+#[test]
+fn websocket_frames_are_checked() {
+    // fin and opcode, the mask bit and the length, a mask of zeros keeps the payload as it is
+    let frame = |head: u8, payload: &[u8]| {
+        let mut bytes = vec![head, 0x80 | payload.len() as u8, 0, 0, 0, 0];
+        bytes.extend_from_slice(payload);
+        bytes
+    };
+    let parts = [frame(0x01, b"pi"), frame(0x89, b"x"), frame(0x80, b"sti")].concat();
+    assert_eq!(
+        read_client_frames(&parts),
+        (Ok(String::from("pisti")), vec![b"x".to_vec()])
+    );
+    // not masked
+    assert!(read_client_frames(&[0x81, 0x01, b'a']).0.is_err());
+    // a continuation without its text frame, a text frame inside another message
+    assert!(read_client_frames(&frame(0x80, b"sti")).0.is_err());
+    assert!(read_client_frames(&[frame(0x01, b"pi"), frame(0x81, b"sti")].concat())
+        .0
+        .is_err());
+    // a ping in parts or longer than 125 bytes
+    assert!(read_client_frames(&frame(0x09, b"x")).0.is_err());
+    assert!(read_client_frames(&[0x89, 0x80 | 126, 0, 126, 0, 0, 0, 0]).0.is_err());
+}
+
+/// a client of `wire` through the lobby and a whole game against the basic AI, checking what
+/// every client of the server may count on
+fn check_server_conformance(address: &str, wire: Wire) {
+    let (mut stream, mut reader) = match wire {
+        Wire::Lines => {
+            let stream = std::net::TcpStream::connect(address).unwrap();
+            stream.set_nodelay(true).unwrap();
+            let reader = std::io::BufReader::new(stream.try_clone().unwrap());
+            (stream, reader)
+        }
+        _ => connect_websocket(address).unwrap(),
+    };
+    let mut send = |message: NetMessage| send_on(&mut stream, wire, &message).unwrap();
+    send(NetMessage::Hello {
+        version: PROTOCOL_VERSION,
+        name: String::from("Conformance bot"),
+    });
+    let mut read = || read_on(&mut reader, wire).unwrap();
+    assert_eq!(read(), NetMessage::Lobby(String::from("pistiflex")));
+
+    let create = |seats| NetMessage::Create {
+        rules: RulePreset::LastCapture,
+        seats,
+    };
+    send(create((Some(StrategyKind::Basic), Some(StrategyKind::Basic))));
+    assert!(matches!(read(), NetMessage::Error(_)));
+    send(create((Some(StrategyKind::Basic), None)));
+    let NetMessage::Created(id) = read() else {
+        panic!("the table was not created");
+    };
+    send(NetMessage::List);
+    let NetMessage::Tables(count) = read() else {
+        panic!("expected tables");
+    };
+    let tables: Vec<NetMessage> = (0..count).map(|_| read()).collect();
+    let listed = TableInfo {
+        id,
+        rules: RulePreset::LastCapture,
+        seats: (String::from("ai:basic"), String::from("-")),
+        status: TableStatus::Waiting,
+    };
+    assert!(tables.contains(&NetMessage::Table(listed)));
+    send(NetMessage::Join {
+        id,
+        seat: Player::Player1,
+    });
+    assert!(matches!(read(), NetMessage::Error(_)));
+    send(NetMessage::Join { id: 0, seat: Player::Player2 });
+    assert!(matches!(read(), NetMessage::Error(_)));
+
+    send(NetMessage::Join {
+        id,
+        seat: Player::Player2,
+    });
+    let NetMessage::Welcome { seat, rules, .. } = read() else {
+        panic!("expected welcome");
+    };
+    assert_eq!((seat, rules), (Player::Player2, RulePreset::LastCapture));
+    let NetMessage::Session(token) = read() else {
+        panic!("expected the session");
+    };
+    assert!(!token.contains(' '));
+    let NetMessage::Board(board) = read() else {
+        panic!("expected the board");
+    };
+    assert_eq!(board.iter().filter(|c| c.is_none()).count(), 3);
+    let NetMessage::Deal(hand) = read() else {
+        panic!("expected the hand");
+    };
+    assert_eq!(read(), NetMessage::Ready);
+    let mut state: Option<TableState> = None;
+    let mut played = Vec::new();
+    let mut game_over = None;
+    // one card that is not in the hand is sent first
+    let mut illegal = None;
+    loop {
+        match read() {
+            NetMessage::Played { card, deal, .. } => {
+                played.push(card);
+                if let Some(deal) = deal {
+                    assert_eq!(deal.len(), 4);
+                }
+            }
+            NetMessage::GameOver { points, .. } => game_over = Some(points),
+            NetMessage::Illegal(a_card) => {
+                assert_eq!(Some(a_card), illegal);
+                let legal = &state.as_ref().unwrap().legal;
+                send(NetMessage::Play(*legal.last().unwrap()));
+            }
+            NetMessage::State(next) => {
+                if state.is_none() {
+                    assert_eq!(next.hand, hand);
+                    assert_eq!(next.deck, 40);
+                }
+                assert!(next.legal.iter().all(|c| next.hand.contains(c)));
+                assert_eq!(next.legal.is_empty(), next.turn != Player::Player2);
+                let next = state.insert(next);
+                if let Some(points) = game_over {
+                    assert_eq!(next.points, points);
+                    assert!(next.legal.is_empty() && next.hand.is_empty());
+                    break;
+                }
+                if let Some(a_card) = next.legal.last() {
+                    if illegal.is_none() {
+                        let not_mine = ["AS", "AH", "AD", "AC", "KS"]
+                            .iter()
+                            .map(|c| c.parse::<Card>().unwrap())
+                            .find(|c| !next.hand.contains(c))
+                            .unwrap();
+                        illegal = Some(not_mine);
+                        send(NetMessage::Play(not_mine));
+                    } else {
+                        send(NetMessage::Play(*a_card));
+                    }
+                }
+            }
+            NetMessage::Ping => send(NetMessage::Pong),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    assert!(illegal.is_some());
+    assert_eq!(played.len(), 48);
+    let TableState { points, deck, .. } = state.unwrap();
+    assert_eq!(deck, 0);
+    assert!(points.0 + points.1 > 0);
+    send(NetMessage::Bye);
+}
+
+#[test]
+fn server_conformance_over_lines_and_websocket() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    std::thread::spawn(move || run_server(listener, ServerConfig::default()));
+    let clients: Vec<_> = [Wire::Lines, Wire::WebSocketClient]
+        .into_iter()
+        .map(|wire| {
+            let address = address.clone();
+            std::thread::spawn(move || check_server_conformance(&address, wire))
+        })
+        .collect();
+    for client in clients {
+        client.join().unwrap();
+    }
+
+    // a request that is not a WebSocket upgrade is answered and closed
+    let mut stream = std::net::TcpStream::connect(&address).unwrap();
+    std::io::Write::write_all(&mut stream, b"GET / HTTP/1.1\r\nHost: x\r\n\r\n").unwrap();
+    let mut answer = String::new();
+    std::io::Read::read_to_string(&mut stream, &mut answer).unwrap();
+    assert!(answer.starts_with("HTTP/1.1 400"));
+}
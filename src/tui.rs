@@ -0,0 +1,295 @@
+use std::{
+    io::{self, Read, Write},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    engine::seat_number,
+    game::{Card, DealStatus, Game, Player, RulePreset, Rules, Suit, WinStatus},
+    history::{remember_game, GameEntry},
+    profiles::find_or_create,
+    record::today,
+    seat::{Seat, Seats},
+    settings::load_settings,
+    strategy::{strategy_from_spec, StrategyKind},
+};
+
+const AI_MOVE_DELAY_MS: u64 = 700;
+const RED: &str = "\x1b[31m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// puts the terminal into single key mode with `stty` and restores it when dropped
+struct RawMode {
+    saved: Option<String>,
+}
+
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .ok()?;
+    if output.status.success() {
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        None
+    }
+}
+
+impl RawMode {
+    fn enable() -> Self {
+        let saved = stty(&["-g"]);
+        if saved.is_some() {
+            stty(&["-icanon", "-echo", "min", "1"]);
+        }
+        RawMode { saved }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if let Some(saved) = &self.saved {
+            stty(&[saved.as_str()]);
+        }
+    }
+}
+
+fn read_key() -> Option<char> {
+    let mut buf = [0u8; 1];
+    loop {
+        match io::stdin().read(&mut buf) {
+            Ok(0) | Err(_) => return None,
+            Ok(_) if buf[0] == b'\n' || buf[0] == b'\r' => continue,
+            Ok(_) => return Some(buf[0] as char),
+        }
+    }
+}
+
+fn card_on_terminal(a_card: &Card) -> String {
+    match a_card.suit {
+        Suit::Heart | Suit::Diamond => format!("{}{}{}", RED, a_card, RESET),
+        Suit::Spade | Suit::Club => format!("{}", a_card),
+    }
+}
+
+fn seat_label(seats: &Seats, a_player: Player) -> String {
+    match seats.get(a_player) {
+        Seat::Human(None) if seats.human_count() == 1 => String::from("You"),
+        a_seat => format!("Player {} {}", seat_number(a_player), a_seat.name()),
+    }
+}
+
+fn score_line(game: &Game, a_player: Player) -> String {
+    let mut scored = game.clone();
+    scored.calculate_points();
+    let (won, pisti, points) = match a_player {
+        Player::Player1 => (
+            scored.player1_won_cards.len(),
+            scored.player1_pisti_count,
+            scored.player1_point,
+        ),
+        Player::Player2 => (
+            scored.player2_won_cards.len(),
+            scored.player2_pisti_count,
+            scored.player2_point,
+        ),
+    };
+    format!("won {} cards, {} pisti, {} points", won, pisti, points)
+}
+
+fn hand_line(game: &Game, seats: &Seats, a_player: Player) -> String {
+    let hand = game.get_player_cards(a_player);
+    let shown = seats.get(a_player).is_human() || seats.human_count() == 0;
+    hand.iter()
+        .enumerate()
+        .map(|(i, c)| {
+            if shown {
+                format!("[{}] {}", i + 1, card_on_terminal(c))
+            } else {
+                String::from("##")
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("  ")
+}
+
+fn board_line(game: &Game) -> String {
+    let hidden = game.initial_board();
+    let cards: Vec<String> = game
+        .board
+        .iter()
+        .map(|c| {
+            if hidden[..3].contains(c) {
+                String::from("##")
+            } else {
+                card_on_terminal(c)
+            }
+        })
+        .collect();
+    if cards.is_empty() {
+        String::from("(empty)")
+    } else {
+        cards.join(" ")
+    }
+}
+
+fn render(game: &Game, seats: &Seats, bottom_player: Player, last: &str, prompt: &str) {
+    let top_player = bottom_player.other();
+    let mut out = String::new();
+    // clear the screen and move to the top left corner
+    out.push_str("\x1b[2J\x1b[H");
+    out.push_str(&format!("{}Pisti{}  seed {}\n\n", BOLD, RESET, game.seed));
+    out.push_str(&format!(
+        "{}: {}\n  {}\n\n",
+        seat_label(seats, top_player),
+        score_line(game, top_player),
+        hand_line(game, seats, top_player)
+    ));
+    out.push_str(&format!(
+        "Board ({}): {}    Deck: {}\n\n",
+        game.board.len(),
+        board_line(game),
+        game.deck.len()
+    ));
+    out.push_str(&format!(
+        "{}: {}\n  {}\n\n",
+        seat_label(seats, bottom_player),
+        score_line(game, bottom_player),
+        hand_line(game, seats, bottom_player)
+    ));
+    out.push_str(&format!("{}\n{}\n", last, prompt));
+    print!("{}", out);
+    let _ = io::stdout().flush();
+}
+
+fn move_text(seats: &Seats, a_player: Player, a_card: Card, stat: WinStatus) -> String {
+    let taken = match stat {
+        WinStatus::Pisti => " - Pisti!!!",
+        WinStatus::Win => " and took the board",
+        WinStatus::Pass => "",
+    };
+    format!(
+        "{} played {}{}",
+        seat_label(seats, a_player),
+        card_on_terminal(&a_card),
+        taken
+    )
+}
+
+fn result_text(game: &Game, seats: &Seats) -> String {
+    let winner = if game.player1_point > game.player2_point {
+        format!("{} won", seat_label(seats, Player::Player1))
+    } else if game.player1_point < game.player2_point {
+        format!("{} won", seat_label(seats, Player::Player2))
+    } else {
+        String::from("Draw")
+    };
+    format!(
+        "{} - {}: {} points ({} Pisti) -- {}: {} points ({} Pisti)",
+        winner,
+        seat_label(seats, Player::Player1),
+        game.player1_point,
+        game.player1_pisti_count,
+        seat_label(seats, Player::Player2),
+        game.player2_point,
+        game.player2_pisti_count
+    )
+}
+
+/// plays one game in the terminal, the human presses the number of a card
+pub fn play_in_terminal(mut seats: Seats, seed: u64, rules: Rules) {
+    let mut my_game = Game::with_seed(seed);
+    my_game.rules = rules;
+    my_game.start_game_and_give_cards_to_players();
+    seats.meet_humans();
+    let bottom_player = seats.bottom_player();
+    let _raw_mode = RawMode::enable();
+    let started = Instant::now();
+    let mut last = String::new();
+    while !my_game.is_game_over() {
+        let player = my_game.turn;
+        let hand_index = match seats.pick_card_for_ai(&my_game, player) {
+            Some(ai_card_index) => {
+                render(&my_game, &seats, bottom_player, &last, "");
+                thread::sleep(Duration::from_millis(AI_MOVE_DELAY_MS));
+                ai_card_index
+            }
+            None => {
+                let hand_len = my_game.get_player_cards(player).len();
+                let prompt = format!(
+                    "{}, press 1-{} to play a card (q quits)",
+                    seat_label(&seats, player),
+                    hand_len
+                );
+                render(&my_game, &seats, bottom_player, &last, &prompt);
+                match read_key() {
+                    None | Some('q') => return,
+                    Some(key) => match key.to_digit(10) {
+                        Some(d) if d >= 1 && (d as usize) <= hand_len => d as usize - 1,
+                        _ => continue,
+                    },
+                }
+            }
+        };
+        let (a_card, stat) = my_game.play_turn(player, hand_index);
+        last = move_text(&seats, player, a_card, stat);
+        if my_game.next_deal() == DealStatus::GameOver {
+            seats.game_over(&my_game);
+            let mut entry = GameEntry::new(&my_game, &seats, &today());
+            entry.seconds = Some(started.elapsed().as_secs());
+            remember_game(&entry);
+        }
+    }
+    render(
+        &my_game,
+        &seats,
+        bottom_player,
+        &last,
+        &format!("{}\npress any key", result_text(&my_game, &seats)),
+    );
+    read_key();
+}
+
+/// `pistiflex tui [--seat 1|2|watch] [--ai <strategy>] [--seed S] [--profile <name>]
+/// [--rules <preset>]`, the rules of settings.txt without `--rules`
+pub fn tui_main(args: &[String]) -> Result<(), String> {
+    let mut rules = load_settings().rules;
+    let mut seat = String::from("1");
+    let mut ai = StrategyKind::Basic.to_string();
+    let mut seed = rand::random::<u64>();
+    let mut profile = None;
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        let value = args_iter.next().ok_or(format!("{} needs a value", arg))?;
+        match arg.as_str() {
+            "--seat" => seat = value.clone(),
+            "--ai" => ai = value.clone(),
+            "--seed" => seed = value.parse().map_err(|_| "--seed needs a number")?,
+            "--profile" => profile = Some(find_or_create(value)?.name),
+            "--rules" => {
+                rules = value
+                    .parse::<RulePreset>()
+                    .map_err(|_| format!("unknown rules: {}", value))?
+            }
+            _ => {
+                return Err(String::from(
+                    "usage: pistiflex tui [--seat 1|2|watch] [--ai <strategy>] [--seed S] [--profile <name>] [--rules <preset>]",
+                ))
+            }
+        }
+    }
+    let seats = match seat.as_str() {
+        "1" => Seats::new(Seat::Human(profile), Seat::Ai(strategy_from_spec(&ai)?)),
+        "2" => Seats::new(Seat::Ai(strategy_from_spec(&ai)?), Seat::Human(profile)),
+        "watch" => Seats::new(
+            Seat::Ai(strategy_from_spec(&ai)?),
+            Seat::Ai(strategy_from_spec(&ai)?),
+        ),
+        _ => return Err(format!("unknown seat: {}", seat)),
+    };
+    play_in_terminal(seats, seed, rules.rules());
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    features::{Features, FEATURE_COUNT},
+    harness::run_match_between,
+    weighted::{load_weights, save_weights, WeightedStrategy, DEFAULT_WEIGHTS},
+};
+
+/// the candidate has to win this much more than half of the games to be kept
+const ACCEPT_MARGIN: f64 = 0.01;
+const MIN_STEP: f64 = 0.02;
+const MAX_STEP: f64 = 1.0;
+
+pub struct TuneConfig {
+    pub iterations: usize,
+    /// deals played between the candidate and the current weights, every deal is played twice
+    pub deals: usize,
+    pub seed: u64,
+    pub start: Features,
+    pub step: f64,
+}
+
+impl Default for TuneConfig {
+    fn default() -> Self {
+        TuneConfig {
+            iterations: 200,
+            deals: 500,
+            seed: rand::random(),
+            start: DEFAULT_WEIGHTS,
+            step: 0.3,
+        }
+    }
+}
+
+fn gaussian(rng: &mut StdRng) -> f64 {
+    // Box-Muller transform
+    let u1: f64 = rng.gen_range(f64::EPSILON..1.0);
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+fn norm(weights: &Features) -> f64 {
+    weights.iter().map(|w| w * w).sum::<f64>().sqrt()
+}
+
+/// (1+1) evolution strategy: a mutated copy of the weights replaces them when it wins a
+/// self-play match against them, the step grows after a success and shrinks after a failure.
+/// Only the direction of the weights changes the picked card so they are kept at the start norm.
+pub fn tune_weights(config: &TuneConfig) -> Features {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut weights = config.start;
+    let start_norm = norm(&config.start).max(f64::EPSILON);
+    let mut step = config.step;
+    for iteration in 0..config.iterations {
+        let mut candidate = weights;
+        for w in candidate.iter_mut() {
+            *w += step * start_norm / (FEATURE_COUNT as f64).sqrt() * gaussian(&mut rng);
+        }
+        let scale = start_norm / norm(&candidate).max(f64::EPSILON);
+        for w in candidate.iter_mut() {
+            *w *= scale;
+        }
+        let report = run_match_between(
+            Box::new(WeightedStrategy::new(candidate)),
+            Box::new(WeightedStrategy::new(weights)),
+            config.deals,
+            rng.gen(),
+        );
+        let accepted = report.score() > 0.5 + ACCEPT_MARGIN;
+        if accepted {
+            weights = candidate;
+            step = (step * 1.5).min(MAX_STEP);
+        } else {
+            step = (step * 0.9).max(MIN_STEP);
+        }
+        println!(
+            "iteration {}: score {:.3} {} step {:.3}",
+            iteration + 1,
+            report.score(),
+            if accepted { "accepted" } else { "rejected" },
+            step
+        );
+    }
+    weights
+}
+
+/// `pistiflex tune [--iterations N] [--deals N] [--seed S] [--start file] [--out file]`
+pub fn tune_main(args: &[String]) -> Result<(), String> {
+    let mut config = TuneConfig::default();
+    let mut out = String::from("weights/tuned.txt");
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        let value = args_iter.next().ok_or(format!("{} needs a value", arg))?;
+        match arg.as_str() {
+            "--iterations" => {
+                config.iterations = value.parse().map_err(|_| "--iterations needs a number")?
+            }
+            "--deals" => config.deals = value.parse().map_err(|_| "--deals needs a number")?,
+            "--seed" => config.seed = value.parse().map_err(|_| "--seed needs a number")?,
+            "--step" => config.step = value.parse().map_err(|_| "--step needs a number")?,
+            "--start" => config.start = load_weights(value)?,
+            "--out" => out = value.clone(),
+            _ => {
+                return Err(String::from(
+                    "usage: pistiflex tune [--iterations N] [--deals N] [--seed S] [--step X] [--start file] [--out file]",
+                ))
+            }
+        }
+    }
+    println!("seed: {}", config.seed);
+    let weights = tune_weights(&config);
+    let report = run_match_between(
+        Box::new(WeightedStrategy::new(weights)),
+        Box::new(WeightedStrategy::new(config.start)),
+        config.deals * 4,
+        config.seed.wrapping_add(1),
+    );
+    println!("tuned weights against the start weights:\n{}", report);
+    save_weights(&out, &weights)?;
+    println!("weights written to {}", out);
+    Ok(())
+}
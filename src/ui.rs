@@ -1,19 +1,23 @@
-use std::sync::{Arc, Mutex};
+use std::{panic::{self, AssertUnwindSafe}, sync::{mpsc, Arc, Mutex, MutexGuard, PoisonError}, thread};
 
-use fltk::{app::{self}, enums::{self}, frame::{self, Frame}, button::Button, prelude::*, window::{DoubleWindow}};
+use fltk::{app::{self}, enums::{self, Align, Color, Font}, frame::{self, Frame}, button::Button, prelude::*, window::{DoubleWindow, Window}};
+use fltk_theme::ThemeType;
 use spin_sleep::SpinSleeper;
 
-use crate::{
-    game::{Card, Game, Player},
-    widget::{
-        activate_all_bottom_cards, deactivate_all_bottom_cards, draw_card, 
-        insert_new_item_into_window, sleep_and_awake, button_constructor
-    },
-    calc::series_xy
+use pistiflex::{
+    game::{Card, DealStatus, Game, Player, WinStatus},
+    calc::series_xy,
+    net::NetMessage,
+    profiles::load_profiles,
+    seat::{Seat, Seats},
+};
+use crate::widget::{
+    activate_all_bottom_cards, deactivate_all_bottom_cards, draw_card, 
+    insert_new_item_into_window, sleep_and_awake, button_constructor
 };
 
 
-#[derive(Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Row {
     Top,
     Bottom,
@@ -31,7 +35,7 @@ pub struct MoveCard {
 }
 #[derive(Clone, Debug)]
 pub struct CollectCards {
-    pub player: Player,
+    pub row: Row,
 }
 
 #[derive(Clone, Debug)]
@@ -46,6 +50,9 @@ pub enum ThreadMessage {
     CC(CollectCards),
     DC(DistributeCards),
     GameOver(String),
+    /// puts every card where it is in the game at once, the animations still waiting before
+    /// it are skipped
+    Show(Box<Game>),
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -59,36 +66,198 @@ pub enum FltkMessage {
     EM(EventMessage),
     #[allow(dead_code)]
     UI(u32),
+    /// the animation speed was changed with the + and - buttons
+    Speed(u8),
+    /// the Save button was pressed
+    Save,
+    Export,
+    /// the Stats button was pressed
+    Stats,
+    /// the Result button was pressed
+    Results,
+    /// the animator thread played one animation
+    Animated,
+    Replay(ReplayControl),
+    /// a message of the host of a LAN game, the error once the connection is gone
+    Net(Result<NetMessage, String>),
+    /// a message of the guest of a LAN game on its `connection`, counted by `LanGuest`
+    Guest(usize, Result<NetMessage, String>),
+    /// the guest of a LAN game connected again with its session token
+    Resumed,
+    /// the seat of the guest waited long enough for it to come back
+    GraceOver,
+}
+
+/// buttons of the replay viewer, Tick comes from the timer while the replay plays
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ReplayControl {
+    Back,
+    Next,
+    PlayPause,
+    Jump,
+    Tick,
 }
 
 
-pub const WIN_WIDTH: i32 = 800;
-pub const WIN_HEIGHT: i32 = 800;
 pub const CARD_H: i32 = 204;
 pub const CARD_W: i32 = 144;
 pub const CARD_MARGIN: i32 = 110;
 
-pub const DEFAULT_ANIM_SPEED: u8 = if cfg!(windows) {
-    9
-} else {
-    6
-};
+/// theme of the settings file, the names are listed in `settings::THEMES`
+pub fn theme_of(name: &str) -> ThemeType {
+    match name {
+        "classic" => ThemeType::Classic,
+        "aero" => ThemeType::Aero,
+        "aqua" => ThemeType::Aqua,
+        "greybird" => ThemeType::Greybird,
+        "blue" => ThemeType::Blue,
+        "dark" => ThemeType::Dark,
+        "high-contrast" => ThemeType::HighContrast,
+        "fluent" => ThemeType::Fluent,
+        _ => ThemeType::Metro,
+    }
+}
+
 pub const MC_ANIM_TIME: f64 = 100.0; // move cards animation time
 pub const CC_ANIM_TIME: f64 = 50.0; // collect cards animation time
 pub const DC_ANIM_TIME: f64 = 50.0; // distribute cards animation time
 
-pub fn game_over_on_ui(win_clone: &mut DoubleWindow, s: String) {
+/// card frames of both rows and the cards drawn on them
+pub struct Table {
+    pub bottom_player: Player,
+    pub top_cards: Vec<Frame>,
+    pub bottom_cards: Vec<Frame>,
+    pub top_cards_values: Vec<Card>,
+    pub bottom_cards_values: Vec<Card>,
+    pub boardx: i32,
+    pub boardy: i32,
+}
+
+impl Table {
+    pub fn row_of(&self, a_player: Player) -> Row {
+        if a_player == self.bottom_player {
+            Row::Bottom
+        } else {
+            Row::Top
+        }
+    }
+
+    fn row_frames_and_values(&self, row: Row) -> (&Vec<Frame>, &Vec<Card>) {
+        match row {
+            Row::Top => (&self.top_cards, &self.top_cards_values),
+            Row::Bottom => (&self.bottom_cards, &self.bottom_cards_values),
+        }
+    }
+
+    /// card drawn on the `card_index` slot of the row of `a_player`
+    pub fn card_on_slot(&self, a_player: Player, card_index: usize) -> Card {
+        self.row_frames_and_values(self.row_of(a_player)).1[card_index]
+    }
+
+    /// animation of `a_card` going from the hand of `a_player` onto the board
+    pub fn move_card(&self, a_player: Player, a_card: Card) -> ThreadMessage {
+        let row = self.row_of(a_player);
+        let (frames, values) = self.row_frames_and_values(row);
+        let card_index = values.iter().position(|c| *c == a_card).unwrap();
+        ThreadMessage::MC(MoveCard {
+            startx: frames[card_index].x(),
+            starty: frames[card_index].y(),
+            endx: self.boardx,
+            endy: self.boardy,
+            card: a_card,
+            row,
+            card_index,
+        })
+    }
+
+    /// animation of the board going to the won cards of `a_player`
+    pub fn collect_cards(&self, a_player: Player) -> ThreadMessage {
+        ThreadMessage::CC(CollectCards {
+            row: self.row_of(a_player),
+        })
+    }
+
+    /// animations of a card `a_player` played and of what followed it, the game over text is left to the caller
+    pub fn move_animations(
+        &mut self,
+        my_game: &Game,
+        a_player: Player,
+        a_card: Card,
+        stat: WinStatus,
+        deal: DealStatus,
+    ) -> Vec<ThreadMessage> {
+        let mut animations = vec![self.move_card(a_player, a_card)];
+        if stat != WinStatus::Pass {
+            animations.push(self.collect_cards(a_player));
+        }
+        match deal {
+            DealStatus::Playing => {}
+            DealStatus::Dealt(bot_hand, top_hand) => {
+                animations.push(self.distribute_cards(bot_hand, top_hand))
+            }
+            DealStatus::GameOver => animations.push(self.collect_cards(my_game.board_taker())),
+        }
+        animations
+    }
+
+    /// animation of dealing the new hands, which are given in (Player1, Player2) order
+    pub fn distribute_cards(&mut self, player1_hand: Vec<Card>, player2_hand: Vec<Card>) -> ThreadMessage {
+        let (bot_hand, top_hand) = match self.bottom_player {
+            Player::Player1 => (player1_hand, player2_hand),
+            Player::Player2 => (player2_hand, player1_hand),
+        };
+        let bot_a = [bot_hand[0], bot_hand[1], bot_hand[2], bot_hand[3]];
+        let top_a = [top_hand[0], top_hand[1], top_hand[2], top_hand[3]];
+        self.bottom_cards_values = bot_hand;
+        self.top_cards_values = top_hand;
+        ThreadMessage::DC(DistributeCards {
+            bottom_hand: bot_a,
+            top_hand: top_a,
+        })
+    }
+}
+
+pub fn game_over_on_ui(win_clone: &mut DoubleWindow, s: String) -> Frame {
     let t_index = win_clone.children();
     let b = frame::Frame::default()
         .with_size(400, 50)
         .with_label(s.as_str());
     win_clone.insert(&b, t_index);
-    b.center_of(win_clone);
+    let b = b.center_of(win_clone);
     app::awake();
+    b
+}
+
+/// draws the hands of `my_game` on their rows, the slots of the cards played in this deal
+/// are empty
+fn show_hands(
+    top_cards: &[Frame],
+    bottom_cards: &[Frame],
+    my_game: &Game,
+    bottom_player: Player,
+    top_hidden: bool,
+) {
+    for (frames, a_player, hidden) in [
+        (top_cards, bottom_player.other(), top_hidden),
+        (bottom_cards, bottom_player, false),
+    ] {
+        let hand = my_game.get_player_cards(a_player);
+        let slots = my_game.dealt_hand(a_player);
+        for (i, a_frame) in frames.iter().enumerate() {
+            let mut a_frame = a_frame.clone();
+            match slots.get(i).filter(|c| hand.contains(c)) {
+                Some(a_card) => {
+                    draw_card(&mut a_frame, *a_card, hidden);
+                    a_frame.show();
+                }
+                None => a_frame.hide(),
+            }
+        }
+    }
 }
 
 pub fn generate_card_frames_on_deck_on_ui(
-    game_deck: &Vec<Card>,
+    game_deck: &[Card],
     card_w: i32,
     card_h: i32,
     reference_card_frame: &Frame,
@@ -113,7 +282,8 @@ pub fn generate_card_frames_on_deck_on_ui(
 }
 
 pub fn generate_card_frames_on_board_ui(
-    game_board: &Vec<Card>,
+    game_board: &[Card],
+    hidden_cards: &[Card],
     card_w: i32,
     card_h: i32,
 ) -> (Vec<Frame>, usize, i32, i32) {
@@ -127,11 +297,7 @@ pub fn generate_card_frames_on_board_ui(
             .center_of_parent();
 
         board.set_pos(board.x() + (i as i32) * 10, board.y());
-        if i < 3 {
-            draw_card(&mut board, *a_card, true);
-        } else {
-            draw_card(&mut board, *a_card, false);
-        }
+        draw_card(&mut board, *a_card, hidden_cards.contains(a_card));
         lastx = board.x();
         lasty = board.y();
         tmp_board.push(board);
@@ -166,19 +332,27 @@ pub fn set_pos_and_size_and_draw_card_on_ui(
     a_card_frame: &mut Frame,
     my_index: usize,
     y: i32,
-    a_card: Card,
+    a_card: Option<&Card>,
     hidden: bool,
 ) {
     a_card_frame.set_size(CARD_W, CARD_H);
     a_card_frame.set_pos((my_index as i32 + 1) * CARD_MARGIN + (CARD_MARGIN / 2), y);
-    draw_card(a_card_frame, a_card, hidden);
+    // a hand can start with less than 4 cards, the empty slots wait for the next deal
+    match a_card {
+        Some(a_card) => draw_card(a_card_frame, *a_card, hidden),
+        None => a_card_frame.hide(),
+    }
 }
 
 pub fn draw_and_set_callbacks_on_ui(
     top_cards: &mut Vec<Frame>,
     bottom_cards: &mut Vec<Frame>,
-    my_game: &Game,
-    bottom_cards_values: &mut Vec<Card>,
+    top_cards_values: &[Card],
+    bottom_cards_values: &[Card],
+    top_hidden: bool,
+    top_clickable: bool,
+    bottom_player: Player,
+    win_height: i32,
     but_inc: &mut Button,
     but_dec: &mut Button,
     anim_speed: Arc<Mutex<u8>>,
@@ -192,54 +366,61 @@ pub fn draw_and_set_callbacks_on_ui(
     
     let mut speed_text_inc_clone = speed_text.clone();
     let mut speed_text_dec_clone = speed_text.clone();
+    let speed_sender_inc = *app_sender;
+    let speed_sender_dec = *app_sender;
 
     but_inc.to_owned().set_callback(move|_b| {
         println!("Increase button pushed");
-        let mut anim_speed_write_clone = anim_speed_inc_clone.lock().unwrap();
+        let mut anim_speed_write_clone = locked(&anim_speed_inc_clone);
         if *anim_speed_write_clone > 1 {
             *anim_speed_write_clone -= 1;
-            println!("Animation Speed Increased to {}", anim_speed_write_clone)
+            println!("Animation Speed Increased to {}", anim_speed_write_clone);
+            speed_sender_inc.send(FltkMessage::Speed(*anim_speed_write_clone));
         }
         speed_text_inc_clone.set_label(format!("{}", anim_speed_write_clone).as_str()); 
     });
     but_dec.to_owned().set_callback(move|_b| {
         println!("Decrease button pushed");
-        let mut anim_speed_write_clone = anim_speed_dec_clone.lock().unwrap();
+        let mut anim_speed_write_clone = locked(&anim_speed_dec_clone);
         if *anim_speed_write_clone < 9 {
             *anim_speed_write_clone += 1;
-            println!("Animation Speed Decreased to {}", anim_speed_write_clone)
+            println!("Animation Speed Decreased to {}", anim_speed_write_clone);
+            speed_sender_dec.send(FltkMessage::Speed(*anim_speed_write_clone));
         }
         speed_text_dec_clone.set_label(format!("{}", anim_speed_write_clone).as_str());
     });
     for (j, a_vec) in [&top_cards, &bottom_cards].iter().enumerate() {
         for (i, a_but) in a_vec.iter().enumerate() {
             let fltk_sender = app_sender.clone();
-            if j == 1 {
+            let the_player = if j == 1 {
                 set_pos_and_size_and_draw_card_on_ui(
                     &mut a_but.to_owned(),
                     i,
-                    WIN_HEIGHT - 20 - CARD_H,
-                    my_game.bottom_hand[i],
+                    win_height - 20 - CARD_H,
+                    bottom_cards_values.get(i),
                     false,
                 );
-                bottom_cards_values.push(my_game.bottom_hand[i]);
+                Some(bottom_player)
+            } else {
+                set_pos_and_size_and_draw_card_on_ui(
+                    &mut a_but.to_owned(),
+                    i,
+                    20,
+                    top_cards_values.get(i),
+                    top_hidden,
+                );
+                top_clickable.then(|| bottom_player.other())
+            };
+            if let Some(the_player) = the_player {
                 a_but.to_owned().set_callback(move |b| {
                     b.to_owned().emit(
                         fltk_sender.to_owned(),
                         FltkMessage::EM(EventMessage {
-                            the_player: Player::Player1,
+                            the_player,
                             card_index: i,
                         }),
                     );
                 });
-            } else {
-                set_pos_and_size_and_draw_card_on_ui(
-                    &mut a_but.to_owned(),
-                    i,
-                    20,
-                    my_game.top_hand[i],
-                    true,
-                );
             }
 
             a_but.to_owned().handle(|b, ev| match ev {
@@ -260,15 +441,6 @@ pub fn create_4_cards_on_center() -> Vec<Frame> {
 }
 
 
-
-pub fn get_avaiable_cards_on_ui(card_frames: &Vec<Frame>) -> Vec<Frame> {
-    return card_frames
-        .iter()
-        .filter(|&t_f| t_f.visible())
-        .map(|t_f| t_f.to_owned())
-        .collect();
-}
-
 pub fn get_player_cards_on_ui<'a>(
     row: Row,
     top_cards: &'a Vec<Frame>,
@@ -305,9 +477,7 @@ pub fn move_card_animation(
     draw_card(&mut new_but, ba.card, false);
     // win_clone.insert(&new_but, t_index);
     insert_new_item_into_window(win_clone, &new_but);
-    let avaiable_top_cards: Vec<Frame> = get_avaiable_cards_on_ui(top_cards);
-    let card_to_hide =
-        get_player_cards_on_ui(ba.row, &avaiable_top_cards, bottom_cards, ba.card_index);
+    let card_to_hide = get_player_cards_on_ui(ba.row, top_cards, bottom_cards, ba.card_index);
     card_to_hide.to_owned().hide();
     let (x, y) = get_pos_for_new_card_on_board(cards_on_board, boardx, boardy);
     let time_len = MC_ANIM_TIME as usize;
@@ -323,17 +493,11 @@ pub fn move_card_animation(
             .unwrap()
             .to_owned()
             .set_pos(*series_x.get(i).unwrap(), *series_y.get(i).unwrap());
-        sleep_and_awake(f64::from(*anim_speed.lock().unwrap()) / 10000.0, sleeper);
-        cards_on_board
-            .last()
-            .unwrap()
-            .to_owned()
-            .parent()
-            .unwrap()
-            .redraw();
+        sleep_and_awake(f64::from(*locked(&anim_speed)) / 10000.0, sleeper);
+        redraw_parent(cards_on_board.last().unwrap());
     }
-    let mut lastx = cards_on_board_lastx.lock().unwrap();
-    let mut lasty = cards_on_board_lasty.lock().unwrap();
+    let mut lastx = locked(cards_on_board_lastx);
+    let mut lasty = locked(cards_on_board_lasty);
     *lastx = x;
     *lasty = y;
     cards_on_board
@@ -341,13 +505,7 @@ pub fn move_card_animation(
         .unwrap()
         .to_owned()
         .set_pos(*lastx, *lasty);
-    cards_on_board
-        .last()
-        .unwrap()
-        .to_owned()
-        .parent()
-        .unwrap()
-        .redraw();
+    redraw_parent(cards_on_board.last().unwrap());
     activate_all_bottom_cards(&mut bottom_cards_clone);
 }
 
@@ -355,17 +513,20 @@ pub fn collect_cards_on_ui(
     cc: CollectCards,
     boardx: i32,
     _boardy: i32,
+    win_height: i32,
     cards_on_board: &mut Vec<Frame>,
     bottom_cards: &mut Vec<Frame>,
     sleeper: SpinSleeper,
     anim_speed: Arc<Mutex<u8>>
 ) {
-    let (_, endx, endy) = match cc.player {
-        Player::Player1 => (Row::Bottom, boardx, WIN_HEIGHT),
-        Player::Player2 => (Row::Top, boardx, 0 - CARD_H),
+    let (endx, endy) = match cc.row {
+        Row::Bottom => (boardx, win_height),
+        Row::Top => (boardx, 0 - CARD_H),
     };
     deactivate_all_bottom_cards(bottom_cards);
-    sleep_and_awake(0.5, sleeper);
+    if *locked(&anim_speed) > 0 {
+        sleep_and_awake(0.5, sleeper);
+    }
     for (i, _) in cards_on_board.iter().enumerate().rev() {
         let mut a_card_frame = cards_on_board[i].to_owned();
         
@@ -376,8 +537,8 @@ pub fn collect_cards_on_ui(
         
         for i in 0..time_len {
             a_card_frame.set_pos(*series_x.get(i).unwrap(), *series_y.get(i).unwrap());
-            sleep_and_awake(f64::from(*anim_speed.lock().unwrap()) / 10000.0, sleeper);
-            a_card_frame.parent().unwrap().redraw();
+            sleep_and_awake(f64::from(*locked(&anim_speed)) / 10000.0, sleeper);
+            redraw_parent(&a_card_frame);
         }
     }
     activate_all_bottom_cards(bottom_cards);
@@ -390,11 +551,12 @@ pub fn distribute_cards_on_ui(
     p_top_cards: &mut Vec<Frame>,
     cards_on_decs: &mut Vec<Frame>,
     win_clone: &mut DoubleWindow,
+    top_hidden: bool,
     sleeper: SpinSleeper,
     anim_speed: Arc<Mutex<u8>>
 ) {
     for (player_cards, player_hand, hidden) in [
-        (p_top_cards, dc.top_hand, true),
+        (p_top_cards, dc.top_hand, top_hidden),
         (p_bottom_cards, dc.bottom_hand, false),
     ] {
         deactivate_all_bottom_cards(player_cards);
@@ -419,13 +581,13 @@ pub fn distribute_cards_on_ui(
 
             for i in 0..time_len {
                 a_card_frame.set_pos(*series_x.get(i).unwrap(), *series_y.get(i).unwrap());
-                sleep_and_awake(f64::from(*anim_speed.lock().unwrap()) / 10000.0, sleeper);
-                a_card_frame.parent().unwrap().redraw();
+                sleep_and_awake(f64::from(*locked(&anim_speed)) / 10000.0, sleeper);
+                redraw_parent(&a_card_frame);
             }
             a_card_frame.hide();
             draw_card(&mut player_cards[i], a_card, hidden);
             player_cards[i].show();
-            player_cards[i].parent().unwrap().redraw();
+            redraw_parent(&player_cards[i]);
         }
         activate_all_bottom_cards(player_cards);
     }
@@ -433,3 +595,252 @@ pub fn distribute_cards_on_ui(
     app::awake();
 }
 
+/// the value behind the lock, also when a panicking animation left the lock poisoned
+fn locked<T>(a_mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    a_mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// the window may already be gone when an animation ends
+fn redraw_parent(a_frame: &Frame) {
+    if let Some(mut parent) = a_frame.parent() {
+        parent.redraw();
+    }
+}
+
+/// where the seats sit and what they see
+#[derive(Copy, Clone, Debug)]
+pub struct TableLayout {
+    pub bottom_player: Player,
+    /// the hand on the top row is drawn face down
+    pub top_hidden: bool,
+    /// the top row can be clicked, the bottom row always can
+    pub top_clickable: bool,
+    pub width: i32,
+    pub height: i32,
+    pub position: Option<(i32, i32)>,
+}
+
+/// the game window and the thread that animates it
+pub struct TableWindow {
+    pub win: DoubleWindow,
+    pub table: Table,
+    /// animations are played one after the other, the thread ends when this is dropped
+    pub animations: mpsc::Sender<ThreadMessage>,
+}
+
+/// shows the cards of `my_game` where they are, a game in the middle of a deal included,
+/// and sends `FltkMessage::Animated` after every animation
+pub fn open_table(
+    my_game: &Game,
+    layout: &TableLayout,
+    anim_speed: Arc<Mutex<u8>>,
+    sleeper: SpinSleeper,
+    sender: &app::Sender<FltkMessage>,
+) -> TableWindow {
+    let (t_s, t_r) = mpsc::channel::<ThreadMessage>();
+    let bottom_player = layout.bottom_player;
+    let top_hidden = layout.top_hidden;
+    let win_height = layout.height;
+
+    let mut win = Window::default()
+        .with_size(layout.width, layout.height)
+        .with_label("Pisti");
+    if let Some((x, y)) = layout.position {
+        win.set_pos(x, y);
+    }
+    let mut but_inc = Button::new(10, 10, 80, 40, "+");
+    let mut but_dec = Button::new(10, 60, 80, 40, "-");
+    let mut speed_text = Button::new(10, 120, 80, 40, "");
+    speed_text.deactivate();
+    speed_text.set_label(format!("{}", *locked(&anim_speed)).as_str());
+
+    let mut top_cards = create_4_cards_on_center();
+    let mut bottom_cards = create_4_cards_on_center();
+    let top_cards_immut: Vec<Frame> = top_cards.iter().map(|f| f.clone()).collect();
+    let bottom_cards_immut: Vec<Frame> = bottom_cards.iter().map(|f| f.clone()).collect();
+
+    let reference_card_frame = bottom_cards_immut[0].clone();
+
+    let mut cards_on_decs =
+        generate_card_frames_on_deck_on_ui(&my_game.deck, CARD_W, CARD_H, &reference_card_frame);
+
+    let (boardx, boardy, _hidden_board) = generate_hidden_board_card_frame(CARD_W, CARD_H);
+
+    let (mut cards_on_board, _, lastx, lasty) = generate_card_frames_on_board_ui(
+        &my_game.board,
+        &my_game.face_down_cards(),
+        CARD_W,
+        CARD_H,
+    );
+    let cards_on_board_lastx = Mutex::new(lastx);
+    let cards_on_board_lasty = Mutex::new(lasty);
+
+    win.end();
+    win.show();
+
+    let (but_w, but_h) = (reference_card_frame.w(), reference_card_frame.h());
+
+    let table = Table {
+        bottom_player,
+        top_cards: top_cards_immut,
+        bottom_cards: bottom_cards_immut,
+        top_cards_values: my_game.dealt_hand(bottom_player.other()),
+        bottom_cards_values: my_game.dealt_hand(bottom_player),
+        boardx,
+        boardy,
+    };
+
+    draw_and_set_callbacks_on_ui(
+        &mut top_cards,
+        &mut bottom_cards,
+        &table.top_cards_values,
+        &table.bottom_cards_values,
+        top_hidden,
+        layout.top_clickable,
+        bottom_player,
+        win_height,
+        &mut but_inc,
+        &mut but_dec,
+        Arc::clone(&anim_speed),
+        &mut speed_text,
+        sender,
+    );
+    // a game can be in the middle of a deal, the slots of the played cards are empty
+    show_hands(&top_cards, &bottom_cards, my_game, bottom_player, top_hidden);
+
+    let mut win_clone = win.clone();
+    let done_sender = *sender;
+    thread::spawn(move || {
+        let mut game_over_label: Option<Frame> = None;
+        while let Ok(first) = t_r.recv() {
+            let mut queue = vec![first];
+            queue.extend(t_r.try_iter());
+            // every message is answered with Animated, also the ones a new position skips
+            let skipped = queue
+                .iter()
+                .rposition(|m| matches!(m, ThreadMessage::Show(_)))
+                .unwrap_or(0);
+            for (i, msg) in queue.into_iter().enumerate() {
+                if i < skipped {
+                    done_sender.send(FltkMessage::Animated);
+                    continue;
+                }
+                // a failed animation leaves the table out of date but keeps the thread and the game alive
+                let played = panic::catch_unwind(AssertUnwindSafe(|| match msg {
+                    ThreadMessage::MC(ba) => {
+                        let anim_speed_clone = Arc::clone(&anim_speed);
+                        move_card_animation(
+                            &mut win_clone,
+                            ba,
+                            &top_cards,
+                            &bottom_cards,
+                            &mut cards_on_board,
+                            but_w,
+                            but_h,
+                            &reference_card_frame,
+                            &cards_on_board_lastx,
+                            &cards_on_board_lasty,
+                            boardx,
+                            boardy,
+                            sleeper,
+                            anim_speed_clone,
+                        )
+                    }
+                    ThreadMessage::CC(cc) => {
+                        let anim_speed_clone = Arc::clone(&anim_speed);
+                        collect_cards_on_ui(
+                            cc,
+                            boardx,
+                            boardy,
+                            win_height,
+                            &mut cards_on_board,
+                            &mut bottom_cards,
+                            sleeper,
+                            anim_speed_clone,
+                        )
+                    }
+                    ThreadMessage::DC(dc) => {
+                        let anim_speed_clone = Arc::clone(&anim_speed);
+                        distribute_cards_on_ui(
+                            dc,
+                            &mut bottom_cards,
+                            &mut top_cards,
+                            &mut cards_on_decs,
+                            &mut win_clone,
+                            top_hidden,
+                            sleeper,
+                            anim_speed_clone,
+                        )
+                    }
+                    ThreadMessage::GameOver(s) => {
+                        game_over_label = Some(game_over_on_ui(&mut win_clone, s));
+                    }
+                    ThreadMessage::Show(my_game) => {
+                        for a_frame in game_over_label
+                            .take()
+                            .into_iter()
+                            .chain(cards_on_board.drain(..))
+                            .chain(cards_on_decs.drain(..))
+                        {
+                            app::delete_widget(a_frame);
+                        }
+                        win_clone.begin();
+                        cards_on_decs = generate_card_frames_on_deck_on_ui(
+                            &my_game.deck,
+                            CARD_W,
+                            CARD_H,
+                            &reference_card_frame,
+                        );
+                        let (board, _, lastx, lasty) = generate_card_frames_on_board_ui(
+                            &my_game.board,
+                            &my_game.face_down_cards(),
+                            CARD_W,
+                            CARD_H,
+                        );
+                        win_clone.end();
+                        cards_on_board = board;
+                        *locked(&cards_on_board_lastx) = lastx;
+                        *locked(&cards_on_board_lasty) = lasty;
+                        show_hands(&top_cards, &bottom_cards, &my_game, bottom_player, top_hidden);
+                        win_clone.redraw();
+                        app::awake();
+                    }
+                }));
+                if played.is_err() {
+                    println!("an animation failed, the cards on the table may be out of place");
+                    win_clone.redraw();
+                }
+                done_sender.send(FltkMessage::Animated);
+            }
+        }
+    });
+
+    TableWindow {
+        win,
+        table,
+        animations: t_s,
+    }
+}
+
+/// the names of the seats next to their rows, a profile in its colour
+pub fn add_name_labels(seats: &Seats, layout: &TableLayout) {
+    let profiles = load_profiles();
+    let rows = [
+        (layout.bottom_player, layout.height - 50),
+        (layout.bottom_player.other(), 20),
+    ];
+    for (a_player, y) in rows {
+        let a_seat = seats.get(a_player);
+        let mut label = Frame::new(layout.width - 150, y, 140, 30, None);
+        label.set_label(&a_seat.name());
+        label.set_align(Align::Left | Align::Inside);
+        label.set_label_size(16);
+        if let Seat::Human(Some(name)) = a_seat {
+            if let Some(profile) = profiles.iter().find(|p| p.name == *name) {
+                let (r, g, b) = profile.rgb();
+                label.set_label_color(Color::from_rgb(r, g, b));
+                label.set_label_font(Font::HelveticaBold);
+            }
+        }
+    }
+}
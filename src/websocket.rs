@@ -0,0 +1,654 @@
+//! The messages of `src/net.rs` as JSON over a WebSocket (RFC 6455), for browser and bot
+//! clients. `pistiflex-server` takes both on the same port: a connection that starts with
+//! `GET ` is upgraded, every other one speaks lines.
+//!
+//! Every message is one text frame with an object whose `type` is the first word of the line
+//! form, the other words have names. Seats are 1 and 2, cards are written as in the line form,
+//! `null` is a face down card on the board or an open seat of `create`, and the seed is a
+//! string because it does not fit into the numbers of JavaScript.
+//!
+//! ```text
+//! {"type":"hello","version":3,"name":"Ann"}
+//! {"type":"lobby","name":"pistiflex"}
+//! {"type":"create","rules":"standard","seats":[null,"basic"]}
+//! {"type":"tables","count":1}
+//! {"type":"table","id":1,"rules":"standard","seats":["-","ai:basic"],"status":"waiting"}
+//! {"type":"join","id":1,"seat":1}
+//! {"type":"welcome","version":3,"seat":1,"rules":"standard","name":"table 1"}
+//! {"type":"session","token":"1-1-9f3c08d2a4e1b7c6"}
+//! {"type":"board","cards":[null,null,null,"7H"]}
+//! {"type":"deal","cards":["4C","KH","9S","2D"]}
+//! {"type":"ready"}
+//! {"type":"state","turn":1,"points":[0,0],"deck":44,"board":[null,null,null,"7H"],
+//!  "hand":["4C","KH","9S","2D"],"legal":["4C","KH","9S","2D"]}
+//! {"type":"play","card":"KH"}
+//! {"type":"played","seat":1,"card":"KH","result":"pass","deal":null}
+//! {"type":"illegal","card":"QS"}
+//! {"type":"gameover","points":[78,23],"seed":"58","face_down":["3C","JD","5S"]}
+//! {"type":"error","reason":"the seat is taken"}
+//! {"type":"resume","token":"1-1-9f3c08d2a4e1b7c6"}
+//! {"type":"ping"}
+//! {"type":"pong"}
+//! {"type":"bye"}
+//! ```
+
+use std::{
+    io::{BufReader, Read, Write},
+    net::TcpStream,
+};
+
+use crate::{
+    game::{Card, Player, RulePreset, WinStatus},
+    json::Json,
+    net::{read_bounded_line, NetMessage, TableInfo, TableState, TableStatus},
+    strategy::StrategyKind,
+};
+
+/// added to the key of the client before hashing, from the RFC
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+/// a message of the protocol is far shorter, a bigger frame is refused
+const MAX_MESSAGE_LEN: usize = 64 * 1024;
+/// the longest payload of a ping, pong or close frame, from the RFC
+const MAX_CONTROL_LEN: usize = 125;
+/// a request with more header lines is refused
+const MAX_HEADER_LINES: usize = 64;
+
+const OP_CONTINUATION: u8 = 0x0;
+const OP_TEXT: u8 = 0x1;
+const OP_BINARY: u8 = 0x2;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xa;
+
+pub fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a827999),
+                20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+    let mut digest = [0u8; 20];
+    for (i, word) in h.iter().enumerate() {
+        digest[i * 4..i * 4 + 4].copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+pub fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// the `Sec-WebSocket-Accept` answer to `Sec-WebSocket-Key`
+pub fn accept_key(key: &str) -> String {
+    base64(&sha1(format!("{}{}", key.trim(), ACCEPT_GUID).as_bytes()))
+}
+
+/// the header lines of an HTTP request or response up to the empty line, names in lowercase
+fn read_headers(
+    reader: &mut BufReader<TcpStream>,
+) -> Result<(String, Vec<(String, String)>), String> {
+    let mut first = String::new();
+    read_bounded_line(reader, &mut first)?;
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if read_bounded_line(reader, &mut line)? == 0 {
+            return Err(String::from("the connection was closed"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            return Ok((first.trim_end().to_string(), headers));
+        }
+        if headers.len() == MAX_HEADER_LINES {
+            return Err(String::from("too many header lines"));
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| v.as_str())
+}
+
+/// answers the upgrade request of a client, an error gets `400 Bad Request`
+pub fn accept_websocket(
+    stream: &mut TcpStream,
+    reader: &mut BufReader<TcpStream>,
+) -> Result<(), String> {
+    let (request, headers) = read_headers(reader)?;
+    let upgrade = header(&headers, "upgrade").unwrap_or("");
+    let key = header(&headers, "sec-websocket-key");
+    let answer = match key {
+        Some(key)
+            if request.starts_with("GET ")
+                && upgrade.eq_ignore_ascii_case("websocket")
+                && header(&headers, "sec-websocket-version") == Some("13") =>
+        {
+            Ok(format!(
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\
+                 Connection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                accept_key(key)
+            ))
+        }
+        _ => Err(format!("not a WebSocket request: {}", request)),
+    };
+    let response = match &answer {
+        Ok(response) => response.clone(),
+        Err(_) => String::from("HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n"),
+    };
+    stream
+        .write_all(response.as_bytes())
+        .map_err(|e| format!("cannot send: {}", e))?;
+    answer.map(|_| ())
+}
+
+/// connects to the server at `address` as a WebSocket client
+pub fn connect_websocket(address: &str) -> Result<(TcpStream, BufReader<TcpStream>), String> {
+    let mut stream =
+        TcpStream::connect(address).map_err(|e| format!("cannot connect to {}: {}", address, e))?;
+    stream.set_nodelay(true).map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+    let key = base64(&rand::random::<[u8; 16]>());
+    let request = format!(
+        "GET / HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        address, key
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| format!("cannot send: {}", e))?;
+    let (status, headers) = read_headers(&mut reader)?;
+    if !status.contains(" 101 ") {
+        return Err(format!("the server did not upgrade: {}", status));
+    }
+    if header(&headers, "sec-websocket-accept") != Some(accept_key(&key).as_str()) {
+        return Err(String::from("the server answered with a wrong key"));
+    }
+    Ok((stream, reader))
+}
+
+/// a frame with the whole payload, a client masks it
+pub fn write_frame(
+    stream: &mut TcpStream,
+    opcode: u8,
+    payload: &[u8],
+    masked: bool,
+) -> Result<(), String> {
+    let mut frame = vec![0x80 | opcode];
+    let mask_bit = if masked { 0x80 } else { 0 };
+    match payload.len() {
+        len if len < 126 => frame.push(mask_bit | len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(mask_bit | 126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(mask_bit | 127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    if masked {
+        let mask = rand::random::<[u8; 4]>();
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+    } else {
+        frame.extend_from_slice(payload);
+    }
+    stream
+        .write_all(&frame)
+        .and_then(|_| stream.flush())
+        .map_err(|e| format!("cannot send: {}", e))
+}
+
+pub fn write_text(stream: &mut TcpStream, text: &str, masked: bool) -> Result<(), String> {
+    write_frame(stream, OP_TEXT, text.as_bytes(), masked)
+}
+
+fn read_exact(reader: &mut BufReader<TcpStream>, buffer: &mut [u8]) -> Result<(), String> {
+    reader.read_exact(buffer).map_err(|e| match e.kind() {
+        std::io::ErrorKind::UnexpectedEof => String::from("the connection was closed"),
+        _ => format!("cannot read: {}", e),
+    })
+}
+
+/// answers a ping with its payload
+pub fn write_pong(stream: &mut TcpStream, payload: &[u8], masked: bool) -> Result<(), String> {
+    write_frame(stream, OP_PONG, payload, masked)
+}
+
+/// the next text message, a close frame is an error. The `server` reads the masked frames of
+/// a client, a client the unmasked frames of the server. The payload of every ping on the way
+/// goes to `on_ping`, which sends the pong
+pub fn read_text(
+    reader: &mut BufReader<TcpStream>,
+    server: bool,
+    mut on_ping: impl FnMut(&mut BufReader<TcpStream>, &[u8]) -> Result<(), String>,
+) -> Result<String, String> {
+    let mut message: Vec<u8> = Vec::new();
+    // a text frame without fin was read, continuation frames follow it
+    let mut fragmented = false;
+    loop {
+        let mut head = [0u8; 2];
+        read_exact(reader, &mut head)?;
+        let (fin, opcode) = (head[0] & 0x80 != 0, head[0] & 0x0f);
+        let masked = head[1] & 0x80 != 0;
+        if masked != server {
+            return Err(String::from(if server {
+                "a frame of the client is not masked"
+            } else {
+                "a frame of the server is masked"
+            }));
+        }
+        let len = match head[1] & 0x7f {
+            126 => {
+                let mut bytes = [0u8; 2];
+                read_exact(reader, &mut bytes)?;
+                u16::from_be_bytes(bytes) as usize
+            }
+            127 => {
+                let mut bytes = [0u8; 8];
+                read_exact(reader, &mut bytes)?;
+                usize::try_from(u64::from_be_bytes(bytes)).unwrap_or(usize::MAX)
+            }
+            len => len as usize,
+        };
+        // ping, pong and close may come between the frames of a message but not in parts
+        if opcode & 0x8 != 0 && (!fin || len > MAX_CONTROL_LEN) {
+            return Err(format!("control frame {} is split or too long", opcode));
+        }
+        if message.len().saturating_add(len) > MAX_MESSAGE_LEN {
+            return Err(format!("a frame of {} bytes is too long", len));
+        }
+        let mut mask = [0u8; 4];
+        if masked {
+            read_exact(reader, &mut mask)?;
+        }
+        let mut payload = vec![0u8; len];
+        read_exact(reader, &mut payload)?;
+        if masked {
+            for (i, b) in payload.iter_mut().enumerate() {
+                *b ^= mask[i % 4];
+            }
+        }
+        match opcode {
+            OP_TEXT | OP_CONTINUATION if fragmented != (opcode == OP_CONTINUATION) => {
+                return Err(String::from("the frames of a message are out of order"));
+            }
+            OP_TEXT | OP_CONTINUATION => {
+                fragmented = true;
+                message.extend_from_slice(&payload);
+            }
+            OP_PING => {
+                on_ping(reader, &payload)?;
+                continue;
+            }
+            OP_PONG => continue,
+            OP_CLOSE => return Err(String::from("the connection was closed")),
+            OP_BINARY => return Err(String::from("binary frames are not understood")),
+            other => return Err(format!("unknown frame {}", other)),
+        }
+        if fin {
+            return String::from_utf8(message).map_err(|_| String::from("invalid UTF-8"));
+        }
+    }
+}
+
+fn object(type_name: &str, fields: Vec<(&str, Json)>) -> Json {
+    let mut pairs = vec![(String::from("type"), Json::String(type_name.to_string()))];
+    pairs.extend(fields.into_iter().map(|(k, v)| (k.to_string(), v)));
+    Json::Object(pairs)
+}
+
+fn text(s: &str) -> Json {
+    Json::String(s.to_string())
+}
+
+fn number(n: usize) -> Json {
+    Json::Number(n as f64)
+}
+
+fn seat_json(seat: Player) -> Json {
+    number(match seat {
+        Player::Player1 => 1,
+        Player::Player2 => 2,
+    })
+}
+
+fn card_json(a_card: &Card) -> Json {
+    Json::String(a_card.notation())
+}
+
+fn cards_json(cards: &[Card]) -> Json {
+    Json::Array(cards.iter().map(card_json).collect())
+}
+
+fn board_json(board: &[Option<Card>]) -> Json {
+    Json::Array(
+        board
+            .iter()
+            .map(|c| c.map_or(Json::Null, |c| card_json(&c)))
+            .collect(),
+    )
+}
+
+fn pair_json(pair: (usize, usize)) -> Json {
+    Json::Array(vec![number(pair.0), number(pair.1)])
+}
+
+pub fn message_to_json(message: &NetMessage) -> Json {
+    match message {
+        NetMessage::Hello { version, name } => object(
+            "hello",
+            vec![("version", number(*version as usize)), ("name", text(name))],
+        ),
+        NetMessage::Welcome {
+            version,
+            seat,
+            rules,
+            name,
+        } => object(
+            "welcome",
+            vec![
+                ("version", number(*version as usize)),
+                ("seat", seat_json(*seat)),
+                ("rules", text(&rules.to_string())),
+                ("name", text(name)),
+            ],
+        ),
+        NetMessage::Refused(reason) => object("refused", vec![("reason", text(reason))]),
+        NetMessage::Board(board) => object("board", vec![("cards", board_json(board))]),
+        NetMessage::Deal(hand) => object("deal", vec![("cards", cards_json(hand))]),
+        NetMessage::Played {
+            player,
+            card,
+            stat,
+            deal,
+        } => object(
+            "played",
+            vec![
+                ("seat", seat_json(*player)),
+                ("card", card_json(card)),
+                ("result", text(&stat.to_string())),
+                ("deal", deal.as_deref().map_or(Json::Null, cards_json)),
+            ],
+        ),
+        NetMessage::Ready => object("ready", vec![]),
+        NetMessage::Play(card) => object("play", vec![("card", card_json(card))]),
+        NetMessage::Illegal(card) => object("illegal", vec![("card", card_json(card))]),
+        NetMessage::GameOver {
+            points,
+            seed,
+            face_down,
+        } => object(
+            "gameover",
+            vec![
+                ("points", pair_json(*points)),
+                ("seed", text(&seed.to_string())),
+                ("face_down", cards_json(face_down)),
+            ],
+        ),
+        NetMessage::Bye => object("bye", vec![]),
+        NetMessage::Ping => object("ping", vec![]),
+        NetMessage::Pong => object("pong", vec![]),
+        NetMessage::State(state) => object(
+            "state",
+            vec![
+                ("turn", seat_json(state.turn)),
+                ("points", pair_json(state.points)),
+                ("deck", number(state.deck)),
+                ("board", board_json(&state.board)),
+                ("hand", cards_json(&state.hand)),
+                ("legal", cards_json(&state.legal)),
+            ],
+        ),
+        NetMessage::Session(token) => object("session", vec![("token", text(token))]),
+        NetMessage::Resume(token) => object("resume", vec![("token", text(token))]),
+        NetMessage::Lobby(name) => object("lobby", vec![("name", text(name))]),
+        NetMessage::List => object("list", vec![]),
+        NetMessage::Tables(count) => object("tables", vec![("count", number(*count))]),
+        NetMessage::Table(info) => object(
+            "table",
+            vec![
+                ("id", number(info.id)),
+                ("rules", text(&info.rules.to_string())),
+                (
+                    "seats",
+                    Json::Array(vec![text(&info.seats.0), text(&info.seats.1)]),
+                ),
+                ("status", text(&info.status.to_string())),
+            ],
+        ),
+        NetMessage::Create { rules, seats } => {
+            let seat =
+                |kind: Option<StrategyKind>| kind.map_or(Json::Null, |k| text(&k.to_string()));
+            object(
+                "create",
+                vec![
+                    ("rules", text(&rules.to_string())),
+                    ("seats", Json::Array(vec![seat(seats.0), seat(seats.1)])),
+                ],
+            )
+        }
+        NetMessage::Created(id) => object("created", vec![("id", number(*id))]),
+        NetMessage::Join { id, seat } => object(
+            "join",
+            vec![("id", number(*id)), ("seat", seat_json(*seat))],
+        ),
+        NetMessage::Error(reason) => object("error", vec![("reason", text(reason))]),
+    }
+}
+
+/// the fields of a JSON message as `message_to_json` writes them, other fields are ignored
+pub fn json_to_message(value: &Json) -> Result<NetMessage, String> {
+    let field = |key: &str| value.get(key).ok_or(format!("{} is missing", key));
+    let string = |key: &str| {
+        field(key)?
+            .as_str()
+            .map(|s| s.to_string())
+            .ok_or(format!("{} must be a string", key))
+    };
+    // names and reasons are one line in the line form
+    let line = |key: &str| string(key).map(|s| s.replace(['\n', '\r'], " "));
+    let whole = |key: &str| {
+        field(key)?
+            .as_usize()
+            .ok_or(format!("{} must be a whole number", key))
+    };
+    let seat = |key: &str| match whole(key)? {
+        1 => Ok(Player::Player1),
+        2 => Ok(Player::Player2),
+        _ => Err(format!("{} must be 1 or 2", key)),
+    };
+    let rules = || {
+        string("rules")?
+            .parse::<RulePreset>()
+            .map_err(|_| String::from("unknown rules"))
+    };
+    // one word in the line form
+    let token = || {
+        Some(string("token")?)
+            .filter(|t| !t.is_empty() && !t.contains(char::is_whitespace))
+            .ok_or(String::from("token must be one word"))
+    };
+    let card_of = |item: &Json| {
+        item.as_str()
+            .ok_or(String::from("a card must be a string"))?
+            .parse::<Card>()
+    };
+    let card = |key: &str| card_of(field(key)?);
+    let list = |key: &str| {
+        field(key)?
+            .as_array()
+            .ok_or(format!("{} must be a list", key))
+    };
+    let cards =
+        |key: &str| -> Result<Vec<Card>, String> { list(key)?.iter().map(card_of).collect() };
+    let board = |key: &str| -> Result<Vec<Option<Card>>, String> {
+        list(key)?
+            .iter()
+            .map(|item| match item {
+                Json::Null => Ok(None),
+                _ => card_of(item).map(Some),
+            })
+            .collect()
+    };
+    let pair = |key: &str| -> Result<(usize, usize), String> {
+        match list(key)? {
+            [a, b] => a.as_usize().zip(b.as_usize()),
+            _ => None,
+        }
+        .ok_or(format!("{} must be two whole numbers", key))
+    };
+    let message = match string("type")?.as_str() {
+        "hello" => NetMessage::Hello {
+            version: whole("version")? as u32,
+            name: line("name")?,
+        },
+        "welcome" => NetMessage::Welcome {
+            version: whole("version")? as u32,
+            seat: seat("seat")?,
+            rules: rules()?,
+            name: line("name")?,
+        },
+        "refused" => NetMessage::Refused(line("reason")?),
+        "board" => NetMessage::Board(board("cards")?),
+        "deal" => NetMessage::Deal(cards("cards")?),
+        "played" => NetMessage::Played {
+            player: seat("seat")?,
+            card: card("card")?,
+            stat: string("result")?
+                .parse::<WinStatus>()
+                .map_err(|_| String::from("result must be pass, win or pisti"))?,
+            deal: match value.get("deal") {
+                None | Some(Json::Null) => None,
+                Some(_) => Some(cards("deal")?),
+            },
+        },
+        "ready" => NetMessage::Ready,
+        "play" => NetMessage::Play(card("card")?),
+        "illegal" => NetMessage::Illegal(card("card")?),
+        "gameover" => NetMessage::GameOver {
+            points: pair("points")?,
+            seed: string("seed")?
+                .parse()
+                .map_err(|_| String::from("seed must be a number in a string"))?,
+            face_down: cards("face_down")?,
+        },
+        "bye" => NetMessage::Bye,
+        "ping" => NetMessage::Ping,
+        "pong" => NetMessage::Pong,
+        "state" => NetMessage::State(TableState {
+            turn: seat("turn")?,
+            points: pair("points")?,
+            deck: whole("deck")?,
+            board: board("board")?,
+            hand: cards("hand")?,
+            legal: cards("legal")?,
+        }),
+        "session" => NetMessage::Session(token()?),
+        "resume" => NetMessage::Resume(token()?),
+        "lobby" => NetMessage::Lobby(line("name")?),
+        "list" => NetMessage::List,
+        "tables" => NetMessage::Tables(whole("count")?),
+        "table" => {
+            let word = |item: &Json| {
+                item.as_str()
+                    .filter(|s| !s.is_empty() && !s.contains(char::is_whitespace))
+                    .map(|s| s.to_string())
+                    .ok_or(String::from("a seat must be one word"))
+            };
+            let [first, second] = list("seats")? else {
+                return Err(String::from("seats must be a list of two"));
+            };
+            NetMessage::Table(TableInfo {
+                id: whole("id")?,
+                rules: rules()?,
+                seats: (word(first)?, word(second)?),
+                status: string("status")?
+                    .parse::<TableStatus>()
+                    .map_err(|_| String::from("unknown status"))?,
+            })
+        }
+        "create" => {
+            // only the strategies built in, as in the line form
+            let kind = |item: &Json| match item {
+                Json::Null => Ok(None),
+                Json::String(name) => name
+                    .parse::<StrategyKind>()
+                    .map(Some)
+                    .map_err(|_| format!("unknown strategy: {}", name)),
+                _ => Err(String::from("a seat must be null or a strategy")),
+            };
+            let [first, second] = list("seats")? else {
+                return Err(String::from("seats must be a list of two"));
+            };
+            NetMessage::Create {
+                rules: rules()?,
+                seats: (kind(first)?, kind(second)?),
+            }
+        }
+        "created" => NetMessage::Created(whole("id")?),
+        "join" => NetMessage::Join {
+            id: whole("id")?,
+            seat: seat("seat")?,
+        },
+        "error" => NetMessage::Error(line("reason")?),
+        other => return Err(format!("unknown message: {}", other)),
+    };
+    Ok(message)
+}
@@ -0,0 +1,99 @@
+use std::{fs, path::Path};
+
+use crate::{
+    features::{evaluate, Features, SeatView, FEATURE_NAMES},
+    game::{Game, Player},
+    storage::{parse_key_values, write_file},
+    strategy::{Strategy, StrategyKind},
+};
+
+pub const DEFAULT_WEIGHTS: Features = [
+    2.0,   // capture
+    1.0,   // capture_points
+    0.1,   // capture_cards
+    10.0,  // pisti
+    -1.5,  // jack_used
+    0.3,   // jacks_held
+    -1.0,  // card_points
+    -6.0,  // pisti_threat
+    -1.0,  // capture_risk
+    -0.2,  // board_points
+    -0.05, // board_cards
+    0.0,   // unseen_same_rank
+    0.0,   // deck_remaining
+    -1.0,  // jack_used_early
+];
+
+/// parses `name = value` lines, `#` starts a comment and missing names keep their default weight
+pub fn parse_weights(text: &str) -> Result<Features, String> {
+    let mut weights = DEFAULT_WEIGHTS;
+    for (line_no, name, value) in parse_key_values(text)? {
+        let value: f64 = value
+            .parse()
+            .map_err(|_| format!("line {}: invalid weight {}", line_no, value))?;
+        match FEATURE_NAMES.iter().position(|n| *n == name) {
+            Some(i) => weights[i] = value,
+            None => println!("line {}: unknown feature {} is ignored", line_no, name),
+        }
+    }
+    Ok(weights)
+}
+
+pub fn weights_to_string(weights: &Features) -> String {
+    FEATURE_NAMES
+        .iter()
+        .zip(weights.iter())
+        .map(|(name, weight)| format!("{} = {}\n", name, weight))
+        .collect()
+}
+
+pub fn load_weights<P: AsRef<Path>>(path: P) -> Result<Features, String> {
+    let text = fs::read_to_string(path.as_ref())
+        .map_err(|e| format!("cannot read {}: {}", path.as_ref().display(), e))?;
+    parse_weights(&text)
+}
+
+pub fn save_weights<P: AsRef<Path>>(path: P, weights: &Features) -> Result<(), String> {
+    write_file(&path.as_ref().to_path_buf(), &weights_to_string(weights))
+}
+
+/// plays the card with the highest linear evaluation of its features
+pub struct WeightedStrategy {
+    pub weights: Features,
+    name: String,
+}
+
+impl WeightedStrategy {
+    pub fn new(weights: Features) -> Self {
+        WeightedStrategy {
+            weights,
+            name: StrategyKind::Weighted.to_string(),
+        }
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let mut strategy = WeightedStrategy::new(load_weights(path)?);
+        strategy.name = format!("{}:{}", StrategyKind::Weighted, path);
+        Ok(strategy)
+    }
+}
+
+impl Strategy for WeightedStrategy {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn pick_card(&mut self, game: &Game, player: Player) -> usize {
+        let view = SeatView::new(game, player);
+        let mut best_i = 0;
+        let mut best_value = f64::NEG_INFINITY;
+        for (i, a_card) in view.hand.iter().enumerate() {
+            let value = evaluate(&self.weights, &view.features(*a_card));
+            if value > best_value {
+                best_i = i;
+                best_value = value;
+            }
+        }
+        best_i
+    }
+}
@@ -2,12 +2,11 @@ use fltk::{app, enums::Color, frame::Frame, image, prelude::*, window::DoubleWin
 use fltk_theme::widget_themes;
 use spin_sleep::SpinSleeper;
 
-use std::sync::mpsc::Sender;
+use std::sync::{mpsc::Sender, OnceLock};
 
-use crate::{
-    game::{Card, Suit},
-    ui::ThreadMessage,
-};
+use pistiflex::game::{Card, Suit};
+
+use crate::ui::ThreadMessage;
 
 pub fn button_constructor(a_label: String) -> Frame {
     let x = Frame::default().with_label(&a_label);
@@ -28,28 +27,22 @@ pub fn _set_button_color(but: &Frame, suit: Suit) {
 }
 
 pub fn card_into_filename(card: Card) -> String {
-    let first_letter = match card.rank {
-        11 => "J".to_string(),
-        12 => "Q".to_string(),
-        13 => "K".to_string(),
-        1 => "A".to_string(),
-        10 => "T".to_string(),
-        x => x.to_string(),
-    };
-    let second_letter = match card.suit {
-        crate::game::Suit::Spade => "S".to_string(),
-        crate::game::Suit::Heart => "H".to_string(),
-        crate::game::Suit::Diamond => "D".to_string(),
-        crate::game::Suit::Club => "C".to_string(),
-    };
-    return format!("{}{}", first_letter, second_letter);
+    card.notation()
+}
+
+static CARD_BACK: OnceLock<&str> = OnceLock::new();
+
+/// image of the face down cards, `black` or `red`, it can be set once before drawing
+pub fn set_card_back(card_back: &str) {
+    let filename = if card_back == "black" { "1B" } else { "2B" };
+    let _ = CARD_BACK.set(filename);
 }
 
 pub fn draw_card(a_button: &mut Frame, card: Card, hidden: bool) {
     let filename = card_into_filename(card);
     // let mut path = format!("src/img/{}.svg.png", filename);
     let path = if hidden {
-        "src/img/2B.svg.png".to_string()
+        format!("src/img/{}.svg.png", CARD_BACK.get().unwrap_or(&"2B"))
     } else {
         format!("src/img/{}.svg.png", filename)
     };
@@ -0,0 +1,702 @@
+//! The game window, everything `pistiflex` does when no subcommand is given.
+
+use std::{path::Path, sync::Arc, sync::Mutex, time::Instant};
+
+use fltk::{app, button::Button, dialog, prelude::*, window::DoubleWindow};
+use fltk_theme::WidgetTheme;
+
+use pistiflex::{
+    game::{DealStatus, Game, Player, WinStatus},
+    history::{history_path, load_history, remember_game, GameEntry},
+    journal::{clear_journal, interrupted_game, write_journal},
+    net::{join_game, resume_game, spawn_reader, NetMessage},
+    options::{parse_options, Options, USAGE},
+    puzzle::{load_puzzles, mark_solved, solved_puzzles, Puzzle, PuzzleOpponent},
+    record::{export_record, today},
+    results::write_results,
+    save::{autosave_path, load_game, save_game},
+    profiles::{find_or_create, load_profiles},
+    seat::{game_over_text, human_from_spec, Seat, Seats},
+    settings::{load_settings, save_settings, Settings},
+    storage::data_dir,
+    strategy::{strategy_from_spec, StrategyKind},
+};
+use spin_sleep::SpinSleeper;
+
+use crate::{
+    dashboard::open_dashboard,
+    lan::{run_guest, LanGuest},
+    replay::{run_replay, Replay},
+    ui::*,
+    widget::{draw_game, set_card_back},
+};
+use strum::IntoEnumIterator;
+
+fn choose_strategy(label: &str, default: StrategyKind) -> Seat {
+    let names: Vec<String> = StrategyKind::iter().map(|k| k.to_string()).collect();
+    let txt = format!(
+        "Strategy of {} ({}, weighted:<file>, adaptive:<profile> or engine:<command>)",
+        label,
+        names.join(", ")
+    );
+    let spec =
+        dialog::input_default(&txt, &default.to_string()).unwrap_or_else(|| default.to_string());
+    match strategy_from_spec(&spec) {
+        Ok(strategy) => Seat::Ai(strategy),
+        Err(e) => {
+            dialog::alert_default(&format!("{}, playing with the basic strategy", e));
+            Seat::Ai(StrategyKind::Basic.create())
+        }
+    }
+}
+
+/// the AI given on the command line, otherwise asked in a dialog
+fn ai_seat(options: &Options, label: &str) -> Seat {
+    match &options.ai {
+        Some(spec) => match strategy_from_spec(spec) {
+            Ok(strategy) => Seat::Ai(strategy),
+            Err(e) => {
+                println!("{}, playing with the basic strategy", e);
+                Seat::Ai(StrategyKind::Basic.create())
+            }
+        },
+        None => choose_strategy(label, options.difficulty.strategy()),
+    }
+}
+
+/// plays for a LAN guest who left, the AI of `--ai` or of the difficulty
+fn takeover_seat(options: &Options) -> Seat {
+    let spec = options
+        .ai
+        .clone()
+        .unwrap_or(options.difficulty.strategy().to_string());
+    match strategy_from_spec(&spec) {
+        Ok(strategy) => Seat::Ai(strategy),
+        Err(e) => {
+            println!("{}, playing with the basic strategy", e);
+            Seat::Ai(StrategyKind::Basic.create())
+        }
+    }
+}
+
+fn choose_seats(options: &Options) -> Seats {
+    if options.hotseat {
+        return Seats::new(Seat::Human(None), Seat::Human(None));
+    }
+    let choice = match options.seat.as_deref() {
+        Some("1") => Some(0),
+        Some("2") => Some(1),
+        Some("watch") => Some(2),
+        _ => dialog::choice2_default(
+            "Choose your seat",
+            "Play first",
+            "Play second",
+            "Watch AI vs AI",
+        ),
+    };
+    match choice {
+        Some(1) => Seats::new(ai_seat(options, "Player 1"), Seat::Human(None)),
+        Some(2) => Seats::new(ai_seat(options, "Player 1"), ai_seat(options, "Player 2")),
+        _ => Seats::new(Seat::Human(None), ai_seat(options, "Player 2")),
+    }
+}
+
+/// gives the human seats without a profile one, `--profile` is the first of them,
+/// otherwise the name is asked when there are profiles to choose from
+fn choose_profiles(seats: &mut Seats, options: &Options, settings: &mut Settings) {
+    let mut first = true;
+    let single = seats.human_count() == 1;
+    for (a_player, label) in [(Player::Player1, "Player 1"), (Player::Player2, "Player 2")] {
+        if !matches!(seats.get(a_player), Seat::Human(None)) {
+            continue;
+        }
+        let given = if first { options.profile.clone() } else { None };
+        let name = given.or_else(|| {
+            let profiles = load_profiles();
+            if profiles.is_empty() {
+                return None;
+            }
+            let names: Vec<&str> = profiles.iter().map(|p| p.name.as_str()).collect();
+            let question = if single {
+                String::from("Who plays?")
+            } else {
+                format!("Who plays as {}?", label)
+            };
+            let txt = format!(
+                "{} ({}, a new name makes a new profile, empty plays without one)",
+                question,
+                names.join(", ")
+            );
+            let last = match (first, &settings.profile) {
+                (true, Some(name)) => name.clone(),
+                _ => String::new(),
+            };
+            dialog::input_default(&txt, &last)
+        });
+        match name.filter(|n| !n.trim().is_empty()).map(|n| find_or_create(&n)) {
+            Some(Ok(profile)) => {
+                if first && settings.profile.as_ref() != Some(&profile.name) {
+                    settings.profile = Some(profile.name.clone());
+                    save_settings(settings);
+                }
+                *seats.get_mut(a_player) = Seat::Human(Some(profile.name));
+            }
+            Some(Err(e)) => dialog::alert_default(&format!("{}, playing without a profile", e)),
+            None => {}
+        }
+        first = false;
+    }
+}
+
+/// the seat a saved game was played with, a human with or without a profile or a strategy
+fn saved_seat(spec: &str) -> Seat {
+    if let Some(seat) = human_from_spec(spec) {
+        return seat;
+    }
+    if let Some(name) = spec.strip_prefix("remote:") {
+        println!("{} played at another computer, the basic AI plays on", name);
+        return Seat::Ai(StrategyKind::Basic.create());
+    }
+    match strategy_from_spec(spec) {
+        Ok(strategy) => Seat::Ai(strategy),
+        Err(e) => {
+            println!("{}, playing with the basic strategy", e);
+            Seat::Ai(StrategyKind::Basic.create())
+        }
+    }
+}
+
+/// the entry of the game in the history, it is stored the first time the game is over
+fn finish_game(
+    my_game: &Game,
+    seats: &Seats,
+    puzzle: Option<&Puzzle>,
+    started: Instant,
+    finished: Option<GameEntry>,
+) -> Option<GameEntry> {
+    if finished.is_some() || puzzle.is_some() || !my_game.is_game_over() {
+        return finished;
+    }
+    let mut entry = GameEntry::new(my_game, seats, &today());
+    entry.seconds = Some(started.elapsed().as_secs());
+    remember_game(&entry);
+    Some(entry)
+}
+
+/// writes the result of the finished game as JSON lines, or as CSV to a `.csv` file
+fn results_from_dialog(finished: Option<&GameEntry>) {
+    let Some(entry) = finished else {
+        dialog::message_default("The result is written when the game is over");
+        return;
+    };
+    let default_path = data_dir()
+        .join("results")
+        .join(format!("{}.json", entry.seed));
+    let path = dialog::input_default("Write the result to", &default_path.to_string_lossy());
+    if let Some(path) = path {
+        if let Err(e) = write_results(Path::new(&path), std::slice::from_ref(entry)) {
+            dialog::alert_default(&e);
+        }
+    }
+}
+
+/// asks where to save, puzzles and finished games are not saved
+fn save_from_dialog(my_game: &Game, seats: &Seats, puzzle: Option<&Puzzle>) {
+    if puzzle.is_some() || my_game.is_game_over() {
+        dialog::message_default("Only a game in progress can be saved");
+        return;
+    }
+    let default_path = data_dir().join("saved_game.txt");
+    let path = dialog::input_default("Save the game to", &default_path.to_string_lossy());
+    if let Some(path) = path {
+        if let Err(e) = save_game(Path::new(&path), my_game, seats) {
+            dialog::alert_default(&e);
+        }
+    }
+}
+
+/// puzzle `number` of the file, otherwise the first one not solved yet
+fn choose_puzzle(path: &Path, number: Option<usize>) -> Result<Puzzle, String> {
+    let puzzles = load_puzzles(path)?;
+    let solved = solved_puzzles();
+    for (i, puzzle) in puzzles.iter().enumerate() {
+        let mark = if solved.contains(&puzzle.name) {
+            "x"
+        } else {
+            " "
+        };
+        println!("[{}] {}. {}", mark, i + 1, puzzle.name);
+    }
+    let index = match number {
+        Some(number) => number - 1,
+        None => puzzles
+            .iter()
+            .position(|p| !solved.contains(&p.name))
+            .unwrap_or(0),
+    };
+    puzzles
+        .get(index)
+        .cloned()
+        .ok_or(format!("{} has {} puzzles", path.display(), puzzles.len()))
+}
+
+/// checks the result against the solver and remembers the solved puzzle
+fn puzzle_over_text(my_game: &Game, puzzle: &Puzzle) -> String {
+    let score = puzzle.score(my_game);
+    let best = puzzle.best_score();
+    if score >= best {
+        mark_solved(&puzzle.name);
+        format!("Solved! {} - the best result is {}", puzzle.name, best)
+    } else {
+        format!("Not yet - you got {}, the best result is {}", score, best)
+    }
+}
+
+/// plays a card of `player` and returns the animations of the move and of the next deal or the game over
+fn play_move(
+    my_game: &mut Game,
+    seats: &mut Seats,
+    table: &mut Table,
+    player: Player,
+    hand_index: usize,
+    puzzle: Option<&Puzzle>,
+) -> Vec<ThreadMessage> {
+    let (a_card, stat) = my_game.play_turn(player, hand_index);
+    if let WinStatus::Pisti = stat {
+        println!("Pisti!!!");
+    }
+    let deal = my_game.next_deal();
+    let game_over = deal == DealStatus::GameOver;
+    let mut animations = table.move_animations(my_game, player, a_card, stat, deal);
+    if puzzle.is_none() {
+        write_journal(my_game, seats);
+    }
+    if game_over {
+        seats.game_over(my_game);
+        let text = match puzzle {
+            Some(puzzle) => puzzle_over_text(my_game, puzzle),
+            None => game_over_text(my_game, seats),
+        };
+        animations.push(ThreadMessage::GameOver(text));
+    }
+    animations
+}
+
+/// plays for the AI seats until a human has to move or the game is over
+fn play_ai_turns(
+    my_game: &mut Game,
+    seats: &mut Seats,
+    table: &mut Table,
+    puzzle: Option<&Puzzle>,
+) -> Vec<ThreadMessage> {
+    let mut animations = Vec::new();
+    while !my_game.is_game_over() {
+        let player = my_game.turn;
+        match seats.pick_card_for_ai(my_game, player) {
+            Some(ai_card_index) => {
+                let mut move_animations =
+                    play_move(my_game, seats, table, player, ai_card_index, puzzle);
+                animations.append(&mut move_animations)
+            }
+            None => break,
+        }
+    }
+    animations
+}
+
+/// remembers where the window was for the next start
+fn save_window(settings: &mut Settings, win: &DoubleWindow) {
+    let geometry = Some((win.x(), win.y(), win.w(), win.h()));
+    if settings.window != geometry {
+        settings.window = geometry;
+        save_settings(settings);
+    }
+}
+
+/// asks where to write the record of the game, finished or not
+fn export_from_dialog(my_game: &Game, seats: &Seats, puzzle: Option<&Puzzle>) {
+    if puzzle.is_some() {
+        dialog::message_default("A puzzle has no record");
+        return;
+    }
+    let default_path = data_dir()
+        .join("records")
+        .join(format!("{}.pisti", my_game.seed));
+    let path = dialog::input_default("Export the game to", &default_path.to_string_lossy());
+    if let Some(path) = path {
+        if let Err(e) = export_record(Path::new(&path), my_game, seats) {
+            dialog::alert_default(&e);
+        }
+    }
+}
+
+/// opens the table with the options of `args` and plays until the window is closed
+pub fn run_window(args: &[String]) {
+    let mut settings = load_settings();
+    let options = match parse_options(args, Options::from_settings(&settings)) {
+        Ok(options) => options,
+        Err(e) => {
+            println!("{}\n{}", e, USAGE);
+            std::process::exit(1);
+        }
+    };
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
+    let sleeper = SpinSleeper::new(1_000_000);
+    let anim_speed = Arc::new(Mutex::new(options.anim_speed));
+    println!("native sleep accuracy: {}", sleeper.native_accuracy_ns());
+    // native sleep accuracy on linux: 125000
+    // native sleep accuracy on windo: 1000000
+    let a = app::App::default();
+    let (s, r) = app::channel::<FltkMessage>();
+    WidgetTheme::new(theme_of(&settings.theme)).apply();
+    set_card_back(&settings.card_back);
+    if let Some(path) = &options.replay {
+        let replay = Replay::load(path).unwrap_or_else(|e| {
+            println!("{}", e);
+            std::process::exit(1);
+        });
+        println!("seed: {}", replay.last.seed);
+        // both hands are face up and neither can be clicked
+        let layout = TableLayout {
+            bottom_player: Player::Player1,
+            top_hidden: false,
+            top_clickable: false,
+            width: options.width,
+            height: options.height,
+            position: settings.window.map(|(x, y, _, _)| (x, y)),
+        };
+        let win = run_replay(replay, layout, anim_speed, sleeper, &mut settings, s, r);
+        save_window(&mut settings, &win);
+        return;
+    }
+    if let Some(address) = &options.join {
+        // the name is sent when joining, the seat is only known after it
+        let mut seats = Seats::new(Seat::Human(None), Seat::Remote(String::new()));
+        choose_profiles(&mut seats, &options, &mut settings);
+        let profile = match seats.player1 {
+            Seat::Human(profile) => profile,
+            _ => None,
+        };
+        let name = profile.clone().unwrap_or(String::from("Guest"));
+        let (guest, stream, reader) = join_game(address, &name).unwrap_or_else(|e| {
+            println!("{}", e);
+            std::process::exit(1);
+        });
+        println!("joined the game of {}", guest.host_name);
+        let host = Seat::Remote(guest.host_name.clone());
+        let seats = match guest.seat {
+            Player::Player1 => Seats::new(Seat::Human(profile), host),
+            Player::Player2 => Seats::new(host, Seat::Human(profile)),
+        };
+        let layout = TableLayout {
+            bottom_player: guest.seat,
+            top_hidden: true,
+            top_clickable: false,
+            width: options.width,
+            height: options.height,
+            position: settings.window.map(|(x, y, _, _)| (x, y)),
+        };
+        let open_guest_table = |game: &Game, reader| {
+            let table_window = open_table(game, &layout, anim_speed.clone(), sleeper, &s);
+            table_window.win.begin();
+            add_name_labels(&seats, &layout);
+            table_window.win.end();
+            spawn_reader(reader, move |message| s.send(FltkMessage::Net(message)));
+            table_window
+        };
+        let table_window = open_guest_table(&guest.game, reader);
+        // the seat of the session, the table is opened again with the whole game
+        let reconnect = |token: &str| {
+            let (guest, stream, reader) = resume_game(address, &name, token)?;
+            let table_window = open_guest_table(&guest.game, reader);
+            Ok((guest, stream, table_window))
+        };
+        let win = run_guest(guest, stream, table_window, &seats, &mut settings, r, reconnect);
+        save_window(&mut settings, &win);
+        return;
+    }
+    let puzzle = options.puzzle.as_ref().map(|path| {
+        choose_puzzle(path, options.puzzle_number).unwrap_or_else(|e| {
+            println!("{}", e);
+            std::process::exit(1);
+        })
+    });
+    // the journal is left behind when the last run crashed or was killed
+    let interrupted = match (&puzzle, &options.load) {
+        (None, None) => interrupted_game().filter(|saved| {
+            let text = format!(
+                "The last game ended without closing the window, after {} moves. Resume it?",
+                saved.game.moves.len()
+            );
+            let resume = dialog::choice2_default(&text, "New game", "Resume", "") == Some(1);
+            if !resume {
+                clear_journal();
+            }
+            resume
+        }),
+        _ => None,
+    };
+    let mut saved_seats = None;
+    let mut my_game = match (&puzzle, &options.load, interrupted) {
+        (Some(puzzle), _, _) => puzzle.to_game(),
+        (None, None, Some(saved)) => {
+            saved_seats = saved.seats;
+            saved.game
+        }
+        (None, Some(path), _) => match load_game(path) {
+            Ok(saved) if !saved.game.is_game_over() => {
+                saved_seats = saved.seats;
+                saved.game
+            }
+            Ok(_) => {
+                println!("{}: the saved game is already over", path.display());
+                std::process::exit(1);
+            }
+            Err(e) => {
+                println!("{}", e);
+                std::process::exit(1);
+            }
+        },
+        (None, None, None) => {
+            let mut my_game = options.seed.map_or_else(Game::new, Game::with_seed);
+            my_game.rules = options.rules;
+            my_game.start_game_and_give_cards_to_players();
+            my_game
+        }
+    };
+    println!("seed: {}", my_game.seed);
+    if my_game.reshuffles > 0 {
+        println!("J was the top card on board. Reshuffled {} times", my_game.reshuffles);
+    }
+    let mut seats = match &puzzle {
+        Some(puzzle) => {
+            dialog::message_default(&format!("{}: {}", puzzle.name, puzzle.goal_text()));
+            let opponent = Seat::Ai(Box::new(PuzzleOpponent {
+                puzzle: puzzle.clone(),
+            }));
+            match puzzle.player() {
+                Player::Player1 => Seats::new(Seat::Human(None), opponent),
+                Player::Player2 => Seats::new(opponent, Seat::Human(None)),
+            }
+        }
+        // the guest of a LAN game gets the seat that is not played here
+        None if options.host.is_some() => match options.seat.as_deref() {
+            Some("2") => Seats::new(Seat::Remote(String::new()), Seat::Human(None)),
+            _ => Seats::new(Seat::Human(None), Seat::Remote(String::new())),
+        },
+        None => match saved_seats {
+            Some((player1, player2)) if options.seat.is_none() && !options.hotseat => {
+                Seats::new(saved_seat(&player1), saved_seat(&player2))
+            }
+            _ => choose_seats(&options),
+        },
+    };
+    if puzzle.is_none() {
+        choose_profiles(&mut seats, &options, &mut settings);
+    }
+    let mut lan = options.host.map(|port| {
+        let host = seats.bottom_player();
+        let guest = LanGuest::accept(port, &my_game, host.other(), &seats.get(host).name(), s)
+            .unwrap_or_else(|e| {
+                println!("{}", e);
+                std::process::exit(1);
+            });
+        println!("{} joined", guest.name);
+        *seats.get_mut(host.other()) = Seat::Remote(guest.name.clone());
+        guest
+    });
+    seats.meet_humans();
+    let bottom_player = seats.bottom_player();
+    // the statistics window shows the games of the profile at the bottom
+    let stats_profile = match seats.get(bottom_player) {
+        Seat::Human(Some(name)) => Some(name.clone()),
+        _ => None,
+    };
+    // the hand of the AI opponent stays hidden, both hands are shown to hotseat and AI vs AI,
+    // a puzzle is solved knowing the hand of the opponent so it is shown too
+    let top_hidden = seats.human_count() == 1 && puzzle.is_none();
+    let top_clickable = seats.get(bottom_player.other()).is_human();
+    let layout = TableLayout {
+        bottom_player,
+        top_hidden,
+        top_clickable,
+        width: options.width,
+        height: options.height,
+        position: settings.window.map(|(x, y, _, _)| (x, y)),
+    };
+    let TableWindow {
+        mut win,
+        mut table,
+        animations: t_s,
+    } = open_table(&my_game, &layout, anim_speed, sleeper, &s);
+    win.begin();
+    let mut but_save = Button::new(10, 180, 80, 40, "Save");
+    but_save.emit(s, FltkMessage::Save);
+    let mut but_export = Button::new(10, 230, 80, 40, "Export");
+    but_export.emit(s, FltkMessage::Export);
+    let mut but_stats = Button::new(10, 280, 80, 40, "Stats");
+    but_stats.emit(s, FltkMessage::Stats);
+    let mut but_results = Button::new(10, 330, 80, 40, "Result");
+    but_results.emit(s, FltkMessage::Results);
+    add_name_labels(&seats, &layout);
+    win.end();
+    win.redraw();
+
+    let started = Instant::now();
+    // the AI may have to open the game, or there may be no human at all
+    draw_game(
+        play_ai_turns(&mut my_game, &mut seats, &mut table, puzzle.as_ref()),
+        t_s.clone(),
+    );
+    let mut finished = finish_game(&my_game, &seats, puzzle.as_ref(), started, None);
+
+    while a.wait() {
+        if let Some(fltk_msg) = r.recv() {
+            match fltk_msg {
+                FltkMessage::UI(ui_code) => {
+                    println!("recevied code: {}", ui_code);
+                }
+                FltkMessage::Speed(anim_speed) => {
+                    settings.anim_speed = anim_speed;
+                    save_settings(&settings);
+                }
+                FltkMessage::Save => save_from_dialog(&my_game, &seats, puzzle.as_ref()),
+                FltkMessage::Export => export_from_dialog(&my_game, &seats, puzzle.as_ref()),
+                FltkMessage::Results => results_from_dialog(finished.as_ref()),
+                FltkMessage::Stats => match load_history(&history_path()) {
+                    Ok(entries) => open_dashboard(entries, stats_profile.clone()),
+                    Err(e) => dialog::alert_default(&e),
+                },
+                FltkMessage::Animated | FltkMessage::Replay(_) => {}
+                FltkMessage::EM(msg) => {
+                    // println!("eventmessage: {:#?}", msg);
+                    if my_game.is_game_over()
+                        || my_game.turn != msg.the_player
+                        || !seats.get(msg.the_player).is_human()
+                    {
+                        continue;
+                    }
+                    let human_player_card = table.card_on_slot(msg.the_player, msg.card_index);
+                    let bot_i = my_game.get_index_of_card(human_player_card, msg.the_player);
+                    let mut animations = play_move(
+                        &mut my_game,
+                        &mut seats,
+                        &mut table,
+                        msg.the_player,
+                        bot_i,
+                        puzzle.as_ref(),
+                    );
+                    animations.append(&mut play_ai_turns(
+                        &mut my_game,
+                        &mut seats,
+                        &mut table,
+                        puzzle.as_ref(),
+                    ));
+
+                    draw_game(animations, t_s.clone());
+                    finished = finish_game(&my_game, &seats, puzzle.as_ref(), started, finished);
+                    if let Some(link) = lan.as_mut().and_then(|guest| guest.link.as_mut()) {
+                        link.send_updates(&my_game);
+                    }
+                }
+                FltkMessage::Guest(connection, Ok(NetMessage::Play(a_card))) => {
+                    let Some(guest) = lan.as_mut().filter(|g| g.is_current(connection)) else {
+                        continue;
+                    };
+                    let Some(link) = guest.link.as_mut() else {
+                        continue;
+                    };
+                    let player = link.seat;
+                    if my_game.is_game_over()
+                        || my_game.turn != player
+                        || !my_game.get_player_cards(player).contains(&a_card)
+                    {
+                        link.illegal(a_card);
+                        continue;
+                    }
+                    let hand_index = my_game.get_index_of_card(a_card, player);
+                    let mut animations = play_move(
+                        &mut my_game,
+                        &mut seats,
+                        &mut table,
+                        player,
+                        hand_index,
+                        puzzle.as_ref(),
+                    );
+                    animations.append(&mut play_ai_turns(
+                        &mut my_game,
+                        &mut seats,
+                        &mut table,
+                        puzzle.as_ref(),
+                    ));
+                    draw_game(animations, t_s.clone());
+                    finished = finish_game(&my_game, &seats, puzzle.as_ref(), started, finished);
+                    link.send_updates(&my_game);
+                }
+                FltkMessage::Guest(connection, Err(e)) => {
+                    let Some(guest) = lan.as_mut().filter(|g| g.is_current(connection)) else {
+                        continue;
+                    };
+                    println!("{}", e);
+                    if my_game.is_game_over() {
+                        guest.leave();
+                    } else {
+                        // the seat waits for the guest to come back with its session token
+                        guest.pause();
+                    }
+                }
+                FltkMessage::Resumed => {
+                    let Some(guest) = lan.as_mut() else {
+                        continue;
+                    };
+                    if guest.resume(&my_game) {
+                        println!("{} is back", guest.name);
+                    }
+                }
+                msg @ (FltkMessage::Guest(_, Ok(NetMessage::Bye)) | FltkMessage::GraceOver) => {
+                    let Some(guest) = lan.as_mut() else {
+                        continue;
+                    };
+                    let left = match msg {
+                        FltkMessage::Guest(connection, _) => guest.is_current(connection),
+                        _ => guest.expired(),
+                    };
+                    if !left {
+                        continue;
+                    }
+                    guest.leave();
+                    if my_game.is_game_over() {
+                        continue;
+                    }
+                    let takeover = takeover_seat(&options);
+                    let txt = format!("{} left the game, {} plays on", guest.name, takeover.name());
+                    dialog::message_default(&txt);
+                    *seats.get_mut(guest.seat) = takeover;
+                    draw_game(
+                        play_ai_turns(&mut my_game, &mut seats, &mut table, puzzle.as_ref()),
+                        t_s.clone(),
+                    );
+                    finished = finish_game(&my_game, &seats, puzzle.as_ref(), started, finished);
+                }
+                FltkMessage::Net(_) | FltkMessage::Guest(_, Ok(_)) => {}
+            }
+        }
+    }
+    // closing the window keeps the game for --resume
+    if puzzle.is_none() {
+        clear_journal();
+        let autosave = autosave_path();
+        if !my_game.is_game_over() {
+            match save_game(&autosave, &my_game, &seats) {
+                Ok(()) => println!("game saved, continue it with --resume"),
+                Err(e) => println!("{}", e),
+            }
+        } else if autosave.exists() {
+            let _ = std::fs::remove_file(&autosave);
+        }
+    }
+    save_window(&mut settings, &win);
+}